@@ -7,7 +7,14 @@ use deep_space::Contact;
 use gravity_proto::auction::query_client::QueryClient as AuctionQueryClient;
 use gravity_proto::auction::Params as AuctionParams;
 use gravity_proto::auction::QueryParamsRequest as QueryAuctionParamsRequest;
+use gravity_proto::auction::{
+    Auction, AuctionPeriod, QueryAllAuctionsByBidderRequest, QueryAuctionByIdRequest,
+    QueryAuctionPeriodRequest, QueryAuctionPoolRequest, QueryAuctionPoolResponse,
+    QueryAuctionsRequest,
+};
 use gravity_proto::cosmos_sdk_proto::cosmos::bank::v1beta1::Metadata;
+use gravity_proto::cosmos_sdk_proto::cosmos::mint::v1beta1::query_client::QueryClient as MintQueryClient;
+use gravity_proto::cosmos_sdk_proto::cosmos::mint::v1beta1::QueryParamsRequest as QueryMintParamsRequest;
 use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
 
 use gravity_proto::gravity::v1::Params;
@@ -22,6 +29,8 @@ use gravity_proto::gravity::v1::QueryDenomToErc20Response;
 use gravity_proto::gravity::v1::QueryErc20ToDenomRequest;
 use gravity_proto::gravity::v1::QueryErc20ToDenomResponse;
 use gravity_proto::gravity::v1::QueryLastEventNonceByAddrRequest;
+use gravity_proto::gravity::v1::QueryLastObservedEthBlockRequest;
+use gravity_proto::gravity::v1::QueryLastObservedEthNonceRequest;
 use gravity_proto::gravity::v1::QueryLastPendingBatchRequestByAddrRequest;
 use gravity_proto::gravity::v1::QueryLastPendingLogicCallByAddrRequest;
 use gravity_proto::gravity::v1::QueryLastPendingValsetRequestByAddrRequest;
@@ -78,6 +87,17 @@ pub async fn get_valset(
     Ok(valset)
 }
 
+/// get the Cosmos block height at which the valset with the given nonce was created
+pub async fn get_valset_height(
+    client: &mut GravityQueryClient<Channel>,
+    nonce: u64,
+) -> Result<Option<u64>, GravityError> {
+    let request = client
+        .valset_request(QueryValsetRequestRequest { nonce })
+        .await?;
+    Ok(request.into_inner().valset.map(|v| v.height))
+}
+
 /// get the current valset. You should never sign this valset
 /// valset requests create a consensus point around the block height
 /// that transaction got in. Without that consensus point everyone trying
@@ -216,6 +236,27 @@ pub async fn get_last_event_nonce_for_validator(
     Ok(request.into_inner().event_nonce)
 }
 
+/// Gets the event nonce of the latest Ethereum event observed by the validator set, every
+/// event up to and including this nonce has been executed on the Gravity Bridge chain
+pub async fn get_last_observed_eth_nonce(
+    client: &mut GravityQueryClient<Channel>,
+) -> Result<u64, GravityError> {
+    let request = client
+        .get_last_observed_eth_nonce(QueryLastObservedEthNonceRequest { use_v1_key: false })
+        .await?;
+    Ok(request.into_inner().nonce)
+}
+
+/// Gets the Ethereum block height of the latest Ethereum event observed by the validator set
+pub async fn get_last_observed_eth_block(
+    client: &mut GravityQueryClient<Channel>,
+) -> Result<u64, GravityError> {
+    let request = client
+        .get_last_observed_eth_block(QueryLastObservedEthBlockRequest { use_v1_key: false })
+        .await?;
+    Ok(request.into_inner().block)
+}
+
 /// Gets the 100 latest logic calls for a relayer to consider relaying
 pub async fn get_latest_logic_calls(
     client: &mut GravityQueryClient<Channel>,
@@ -447,3 +488,100 @@ pub async fn get_auction_module_params(
 
     Ok(params)
 }
+
+// Gets the current auction period
+pub async fn get_auction_period(contact: &Contact) -> Result<AuctionPeriod, CosmosGrpcError> {
+    let mut auction_qc = AuctionQueryClient::connect(contact.get_url()).await?;
+
+    let period = auction_qc
+        .auction_period(QueryAuctionPeriodRequest {})
+        .await?
+        .into_inner()
+        .auction_period
+        .ok_or(CosmosGrpcError::BadResponse(
+            "no auction period returned".to_string(),
+        ))?;
+
+    Ok(period)
+}
+
+// Gets all the auctions of the current auction period
+pub async fn get_auctions(contact: &Contact) -> Result<Vec<Auction>, CosmosGrpcError> {
+    let mut auction_qc = AuctionQueryClient::connect(contact.get_url()).await?;
+
+    let auctions = auction_qc
+        .auctions(QueryAuctionsRequest {})
+        .await?
+        .into_inner()
+        .auctions;
+
+    Ok(auctions)
+}
+
+// Gets an auction of the current auction period by its id
+pub async fn get_auction_by_id(
+    contact: &Contact,
+    auction_id: u64,
+) -> Result<Auction, CosmosGrpcError> {
+    let mut auction_qc = AuctionQueryClient::connect(contact.get_url()).await?;
+
+    let auction = auction_qc
+        .auction_by_id(QueryAuctionByIdRequest { auction_id })
+        .await?
+        .into_inner()
+        .auction
+        .ok_or(CosmosGrpcError::BadResponse(format!(
+            "no auction with id {auction_id} returned"
+        )))?;
+
+    Ok(auction)
+}
+
+// Gets the auctions of the current auction period where `bidder` holds the highest bid
+pub async fn get_auctions_by_bidder(
+    contact: &Contact,
+    bidder: Address,
+) -> Result<Vec<Auction>, CosmosGrpcError> {
+    let mut auction_qc = AuctionQueryClient::connect(contact.get_url()).await?;
+
+    let auctions = auction_qc
+        .all_auctions_by_bidder(QueryAllAuctionsByBidderRequest {
+            address: bidder.to_string(),
+        })
+        .await?
+        .into_inner()
+        .auctions;
+
+    Ok(auctions)
+}
+
+// Gets the auction pool account and the balances to be auctioned in the next auction period
+pub async fn get_auction_pool(
+    contact: &Contact,
+) -> Result<QueryAuctionPoolResponse, CosmosGrpcError> {
+    let mut auction_qc = AuctionQueryClient::connect(contact.get_url()).await?;
+
+    let pool = auction_qc
+        .auction_pool(QueryAuctionPoolRequest {})
+        .await?
+        .into_inner();
+
+    Ok(pool)
+}
+
+// Gets the denom minted by the mint module, this is the chain's native token which auction bids
+// and bid fees are paid in
+pub async fn get_mint_denom(contact: &Contact) -> Result<String, CosmosGrpcError> {
+    let mut mint_qc = MintQueryClient::connect(contact.get_url()).await?;
+
+    let params = mint_qc
+        .params(QueryMintParamsRequest {})
+        .await?
+        .into_inner()
+        .params
+        .ok_or(CosmosGrpcError::BadResponse(
+            "no params returned".to_string(),
+        ))?;
+
+    Ok(params.mint_denom)
+}
@@ -1,5 +1,5 @@
 use clarity::Address as EthAddress;
-use clarity::{PrivateKey as EthPrivateKey, Signature};
+use clarity::Signature;
 use deep_space::address::Address as CosmosAddress;
 use deep_space::client::send::TransactionResponse;
 use deep_space::error::CosmosGrpcError;
@@ -11,12 +11,14 @@ use ethereum_gravity::message_signatures::{
     encode_logic_call_confirm, encode_tx_batch_confirm, encode_valset_confirm,
 };
 
+use gravity_proto::auction::MsgBid;
 use gravity_proto::gravity::v1::{
     MsgCancelSendToEth, MsgConfirmBatch, MsgConfirmLogicCall, MsgExecuteIbcAutoForwards,
     MsgRequestBatch, MsgSendToEth, MsgSetOrchestratorAddress, MsgSubmitBadSignatureEvidence,
     MsgValsetConfirm,
 };
 
+use gravity_utils::eth_signer::EthSigner;
 use gravity_utils::types::*;
 
 use std::{collections::HashMap, time::Duration};
@@ -87,21 +89,21 @@ pub async fn set_gravity_delegate_addresses(
 #[allow(clippy::too_many_arguments)]
 pub async fn send_valset_confirms(
     contact: &Contact,
-    eth_private_key: EthPrivateKey,
+    eth_signer: &EthSigner,
     fee: Coin,
     valsets: Vec<Valset>,
     private_key: impl PrivateKey,
     gravity_id: String,
 ) -> Result<TransactionResponse, CosmosGrpcError> {
     let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
-    let our_eth_address = eth_private_key.to_address();
+    let our_eth_address = eth_signer.get_address();
 
     let mut messages = Vec::new();
 
     for valset in valsets {
         trace!("Submitting signature for valset {valset:?}");
         let message = encode_valset_confirm(gravity_id.clone(), valset.clone());
-        let eth_signature = eth_private_key.sign_ethereum_msg(&message);
+        let eth_signature = sign_checkpoint(eth_signer, &message).await?;
         trace!(
             "Sending valset update with address {} and sig {}",
             our_eth_address,
@@ -130,24 +132,36 @@ pub async fn send_valset_confirms(
     res
 }
 
+/// Signs a confirm checkpoint, signer failures are reported as a CosmosGrpcError so that
+/// callers handle them like any other failure to submit the confirm
+async fn sign_checkpoint(
+    eth_signer: &EthSigner,
+    message: &[u8],
+) -> Result<Signature, CosmosGrpcError> {
+    eth_signer
+        .sign_checkpoint(message)
+        .await
+        .map_err(|e| CosmosGrpcError::BadInput(format!("Failed to sign checkpoint {e}")))
+}
+
 /// Send in a confirmation for a specific transaction batch
 pub async fn send_batch_confirm(
     contact: &Contact,
-    eth_private_key: EthPrivateKey,
+    eth_signer: &EthSigner,
     fee: Coin,
     transaction_batches: Vec<TransactionBatch>,
     private_key: impl PrivateKey,
     gravity_id: String,
 ) -> Result<TransactionResponse, CosmosGrpcError> {
     let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
-    let our_eth_address = eth_private_key.to_address();
+    let our_eth_address = eth_signer.get_address();
 
     let mut messages = Vec::new();
 
     for batch in transaction_batches {
         trace!("Submitting signature for batch {batch:?}");
         let message = encode_tx_batch_confirm(gravity_id.clone(), batch.clone());
-        let eth_signature = eth_private_key.sign_ethereum_msg(&message);
+        let eth_signature = sign_checkpoint(eth_signer, &message).await?;
         trace!(
             "Sending batch update with address {} and sig {}",
             our_eth_address,
@@ -178,21 +192,21 @@ pub async fn send_batch_confirm(
 /// Send in a confirmation for a specific logic call
 pub async fn send_logic_call_confirm(
     contact: &Contact,
-    eth_private_key: EthPrivateKey,
+    eth_signer: &EthSigner,
     fee: Coin,
     logic_calls: Vec<LogicCall>,
     private_key: impl PrivateKey,
     gravity_id: String,
 ) -> Result<TransactionResponse, CosmosGrpcError> {
     let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
-    let our_eth_address = eth_private_key.to_address();
+    let our_eth_address = eth_signer.get_address();
 
     let mut messages = Vec::new();
 
     for call in logic_calls {
         trace!("Submitting signature for LogicCall {call:?}");
         let message = encode_logic_call_confirm(gravity_id.clone(), call.clone());
-        let eth_signature = eth_private_key.sign_ethereum_msg(&message);
+        let eth_signature = sign_checkpoint(eth_signer, &message).await?;
         trace!(
             "Sending LogicCall update with address {} and sig {}",
             our_eth_address,
@@ -475,6 +489,38 @@ pub async fn cancel_send_to_eth(
         .await
 }
 
+/// Bids `amount` of the native token on an auction, `bid_fee` is paid to the stakers whether
+/// or not the bid wins
+pub async fn bid_on_auction(
+    private_key: impl PrivateKey,
+    fee: Coin,
+    contact: &Contact,
+    auction_id: u64,
+    amount: u64,
+    bid_fee: u64,
+) -> Result<TransactionResponse, CosmosGrpcError> {
+    let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
+
+    let msg_bid = MsgBid {
+        auction_id,
+        bidder: our_address.to_string(),
+        amount,
+        bid_fee,
+    };
+
+    let msg = Msg::new(MSG_BID_TYPE_URL, msg_bid);
+    contact
+        .send_message(
+            &[msg],
+            Some(MEMO.to_string()),
+            &[fee],
+            Some(TIMEOUT),
+            None,
+            private_key,
+        )
+        .await
+}
+
 /// Executes a MsgExecuteIbcAutoForwards on the gravity chain, which will process forwards_to_clear number of pending ibc auto forwards
 pub async fn execute_pending_ibc_auto_forwards(
     contact: &Contact,
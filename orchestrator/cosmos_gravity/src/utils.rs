@@ -3,6 +3,8 @@ use deep_space::client::ChainStatus;
 use deep_space::error::CosmosGrpcError;
 use deep_space::utils::encode_any;
 use deep_space::{Address as CosmosAddress, Contact};
+use gravity_proto::cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::service_client::ServiceClient as TendermintServiceClient;
+use gravity_proto::cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::GetBlockByHeightRequest;
 use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
 use gravity_proto::gravity::v1::OutgoingLogicCall as ProtoLogicCall;
 use gravity_proto::gravity::v1::OutgoingTxBatch as ProtoBatch;
@@ -88,6 +90,26 @@ pub async fn get_current_cosmos_height(contact: &Contact) -> Result<u64, CosmosG
     }
 }
 
+/// Gets the time of the Cosmos block at `height` in unix seconds
+pub async fn get_block_time(
+    client: &mut TendermintServiceClient<Channel>,
+    height: u64,
+) -> Result<u64, CosmosGrpcError> {
+    let block = client
+        .get_block_by_height(GetBlockByHeightRequest {
+            height: height as i64,
+        })
+        .await?
+        .into_inner()
+        .block;
+    match block.and_then(|b| b.header).and_then(|h| h.time) {
+        Some(time) => Ok(time.seconds.max(0) as u64),
+        None => Err(CosmosGrpcError::BadResponse(format!(
+            "No time for block {height}"
+        ))),
+    }
+}
+
 /// Fetches the current Gravity Bridge MinChainFeeBasisPoints and calculates the minimum ChainFee for a MsgSendToEth
 pub async fn get_reasonable_send_to_eth_fee(
     contact: &Contact,
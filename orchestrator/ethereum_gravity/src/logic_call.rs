@@ -1,5 +1,5 @@
 use crate::message_signatures::encode_logic_call_confirm_hashed;
-use crate::utils::{encode_valset_struct, get_logic_call_nonce, GasCost};
+use crate::utils::{encode_valset_struct, get_logic_call_nonce, Eip1559Fees, GasCost};
 use clarity::{abi::AbiToken as Token, utils::bytes_to_hex_str, PrivateKey as EthPrivateKey};
 use clarity::{Address as EthAddress, Uint256};
 use gravity_utils::error::GravityError;
@@ -20,6 +20,7 @@ pub async fn send_eth_logic_call(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_key: EthPrivateKey,
+    tx_options: Vec<SendTxOption>,
 ) -> Result<(), GravityError> {
     let new_call_nonce = call.invalidation_nonce;
     let eth_address = our_eth_key.to_address();
@@ -57,10 +58,7 @@ pub async fn send_eth_logic_call(
                 payload,
                 0u32.into(),
                 our_eth_key,
-                // we maintain a 20% gas price increase to compensate for the 12.5% maximum
-                // base fee increase allowed per block in eip1559, if we overpay we'll
-                // be refunded.
-                vec![SendTxOption::GasPriceMultiplier(1.20f32)],
+                tx_options,
             )
             .await?,
         )
@@ -84,11 +82,10 @@ pub async fn send_eth_logic_call(
     Ok(())
 }
 
-/// Returns the cost in Eth of sending this logic call, because of the way eip1559 fee
-/// computation works the minimum allowed gas price will trend up or down by as much
-/// as 12.5% per block. In order to prevent race conditions we pad our estimate by 20%
-/// if the gas price has in fact gone down we'll be refunded. But we must bake
-/// this uncertainty into our cost estimates
+/// Returns the cost in Eth of sending this logic call, the expected cost is the current
+/// gas price, but because of the way eip1559 fee computation works the base fee may rise
+/// by as much as 12.5% per block, so the worst case cost at `fees.max_fee_per_gas` is
+/// also returned. If the gas price has in fact gone down we'll be refunded
 pub async fn estimate_logic_call_cost(
     current_valset: Valset,
     call: LogicCall,
@@ -97,14 +94,13 @@ pub async fn estimate_logic_call_cost(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_address: EthAddress,
+    fees: Eip1559Fees,
 ) -> Result<GasCost, GravityError> {
     let our_balance = web3.eth_get_balance(our_eth_address).await?;
     let our_nonce = web3.eth_get_transaction_count(our_eth_address).await?;
     let chain_id = web3.eth_chainid().await?;
     let gas_limit = min((u64::MAX - 1).into(), our_balance);
-    let gas_price = web3.eth_gas_price().await?;
-    // increase the value by 20% without using floating point multiplication
-    let gas_price = gas_price + (gas_price / 5u8.into());
+    let gas_price = min(web3.eth_gas_price().await?, fees.max_fee_per_gas);
     let zero: Uint256 = 0u8.into();
     let val = web3
         .eth_estimate_gas(TransactionRequest::Eip1559 {
@@ -113,8 +109,8 @@ pub async fn estimate_logic_call_cost(
             from: our_eth_address,
             to: gravity_contract_address,
             nonce: Some(our_nonce.into()),
-            max_fee_per_gas: Some(gas_price.into()),
-            max_priority_fee_per_gas: None,
+            max_fee_per_gas: Some(fees.max_fee_per_gas.into()),
+            max_priority_fee_per_gas: fees.max_priority_fee_per_gas.map(|v| v.into()),
             gas: Some(gas_limit.into()),
             value: Some(zero.into()),
             data: Some(
@@ -126,11 +122,12 @@ pub async fn estimate_logic_call_cost(
     Ok(GasCost {
         gas: val,
         gas_price,
+        max_fee_per_gas: fees.max_fee_per_gas,
     })
 }
 
 /// Encodes the logic call payload for both cost estimation and submission to EThereum
-fn encode_logic_call_payload(
+pub fn encode_logic_call_payload(
     current_valset: Valset,
     call: &LogicCall,
     confirms: &[LogicCallConfirmResponse],
@@ -220,6 +220,7 @@ mod test {
             }],
             total_fee: token,
             token_contract: erc20_addr,
+            cosmos_block_created: 0,
         };
 
         let checkpoint = encode_tx_batch_confirm("foo".to_string(), batch.clone());
@@ -267,6 +268,7 @@ mod test {
             }],
             total_fee: token,
             token_contract: erc20_addr,
+            cosmos_block_created: 0,
         };
 
         let mut rng = rand::thread_rng();
@@ -48,8 +48,10 @@ pub async fn send_erc721_to_cosmos(
         }
     }
     let address_approved = address_approved?;
-    if address_approved.is_none() {
-        info!("Approval for ERC721 contract {erc721} token id {token_id} is empty...approving",);
+    // a token has only one approved address, any approval other than the GravityERC721
+    // contract must be replaced
+    if address_approved != Some(gravityerc721_contract) {
+        info!("Approval for ERC721 contract {erc721} token id {token_id} is not set to GravityERC721...approving",);
         let mut options = options.clone();
         let nonce = web3.eth_get_transaction_count(sender_address).await?;
         options.push(SendTxOption::Nonce(nonce));
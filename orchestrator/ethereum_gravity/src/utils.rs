@@ -4,7 +4,7 @@ use clarity::Uint256;
 use clarity::{abi::AbiToken as Token, constants::zero_address};
 use gravity_utils::num_conversion::downcast_uint256;
 use gravity_utils::types::*;
-use web30::types::TransactionRequest;
+use web30::types::{SendTxOption, TransactionRequest};
 use web30::{client::Web3, jsonrpc::error::Web3Error};
 
 /// Gets the latest validator set nonce
@@ -123,21 +123,49 @@ pub async fn get_gravity_sol_address(
     }
 }
 
+/// The EIP-1559 fee parameters a relayed transaction is submitted with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Eip1559Fees {
+    /// The most that will be paid per unit of gas, base fee and priority fee combined
+    pub max_fee_per_gas: Uint256,
+    /// The tip paid to the block producer per unit of gas, if None the node's suggestion is used
+    pub max_priority_fee_per_gas: Option<Uint256>,
+}
+
+impl Eip1559Fees {
+    /// The options to pass to prepare_transaction to submit with these fees
+    pub fn to_send_options(&self) -> Vec<SendTxOption> {
+        let mut options = vec![SendTxOption::GasMaxFee(self.max_fee_per_gas)];
+        if let Some(priority_fee) = self.max_priority_fee_per_gas {
+            options.push(SendTxOption::GasPriorityFee(priority_fee));
+        }
+        options
+    }
+}
+
 /// Just a helper struct to represent the cost of actions on Ethereum
 #[derive(Debug, Default, Clone)]
 pub struct GasCost {
     /// The amount of gas spent
     pub gas: Uint256,
-    /// The price of the gas
+    /// The expected price of the gas
     pub gas_price: Uint256,
+    /// The maximum price of the gas, under EIP-1559 the transaction may pay anything up to this
+    pub max_fee_per_gas: Uint256,
 }
 
 impl GasCost {
-    /// Gets the total cost in Eth (or other EVM chain native token)
+    /// Gets the expected total cost in Eth (or other EVM chain native token)
     /// of executing the batch
     pub fn get_total(&self) -> Uint256 {
         self.gas * self.gas_price
     }
+
+    /// Gets the most the transaction could possibly cost, if the base fee rises to
+    /// consume the entire max fee before it is included
+    pub fn get_worst_case_total(&self) -> Uint256 {
+        self.gas * self.max_fee_per_gas
+    }
 }
 
 /// This encodes the solidity struct ValsetArgs from the Gravity
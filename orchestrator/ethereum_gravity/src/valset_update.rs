@@ -1,11 +1,12 @@
 use crate::message_signatures::encode_valset_confirm_hashed;
-use crate::utils::{encode_valset_struct, get_valset_nonce, GasCost};
+use crate::utils::{encode_valset_struct, get_valset_nonce, Eip1559Fees, GasCost};
 use clarity::PrivateKey as EthPrivateKey;
 use clarity::{Address as EthAddress, Uint256};
 use gravity_utils::error::GravityError;
 use gravity_utils::types::*;
 use std::{cmp::min, time::Duration};
-use web30::{client::Web3, types::TransactionRequest};
+use web30::client::Web3;
+use web30::types::{SendTxOption, TransactionRequest};
 
 /// this function generates an appropriate Ethereum transaction
 /// to submit the provided validator set and signatures.
@@ -19,6 +20,7 @@ pub async fn send_eth_valset_update(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_key: EthPrivateKey,
+    tx_options: Vec<SendTxOption>,
 ) -> Result<(), GravityError> {
     let old_nonce = old_valset.nonce;
     let new_nonce = new_valset.nonce;
@@ -43,7 +45,7 @@ pub async fn send_eth_valset_update(
                 payload,
                 0u32.into(),
                 our_eth_key,
-                vec![],
+                tx_options,
             )
             .await?,
         )
@@ -61,11 +63,10 @@ pub async fn send_eth_valset_update(
     Ok(())
 }
 
-/// Returns the cost in Eth of sending this valset update, because of the way eip1559 fee
-/// computation works the minimum allowed gas price will trend up or down by as much
-/// as 12.5% per block. In order to prevent race conditions we pad our estimate by 20%
-/// if the gas price has in fact gone down we'll be refunded. But we must bake
-/// this uncertainty into our cost estimates
+/// Returns the cost in Eth of sending this valset update, the expected cost is the current
+/// gas price, but because of the way eip1559 fee computation works the base fee may rise
+/// by as much as 12.5% per block, so the worst case cost at `fees.max_fee_per_gas` is
+/// also returned. If the gas price has in fact gone down we'll be refunded
 pub async fn estimate_valset_cost(
     new_valset: &Valset,
     old_valset: &Valset,
@@ -74,14 +75,13 @@ pub async fn estimate_valset_cost(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_address: EthAddress,
+    fees: Eip1559Fees,
 ) -> Result<GasCost, GravityError> {
     let our_balance = web3.eth_get_balance(our_eth_address).await?;
     let our_nonce = web3.eth_get_transaction_count(our_eth_address).await?;
     let chain_id = web3.eth_chainid().await?;
     let gas_limit = min((u64::MAX - 1).into(), our_balance);
-    let gas_price = web3.eth_gas_price().await?;
-    // increase the value by 20% without using floating point multiplication
-    let gas_price = gas_price + (gas_price / 5u8.into());
+    let gas_price = min(web3.eth_gas_price().await?, fees.max_fee_per_gas);
     let zero: Uint256 = 0u8.into();
     let val = web3
         .eth_estimate_gas(TransactionRequest::Eip1559 {
@@ -90,8 +90,8 @@ pub async fn estimate_valset_cost(
             from: our_eth_address,
             to: gravity_contract_address,
             nonce: Some(our_nonce.into()),
-            max_fee_per_gas: Some(gas_price.into()),
-            max_priority_fee_per_gas: None,
+            max_fee_per_gas: Some(fees.max_fee_per_gas.into()),
+            max_priority_fee_per_gas: fees.max_priority_fee_per_gas.map(|v| v.into()),
             gas: Some(gas_limit.into()),
             value: Some(zero.into()),
             data: Some(
@@ -110,6 +110,7 @@ pub async fn estimate_valset_cost(
     Ok(GasCost {
         gas: val,
         gas_price,
+        max_fee_per_gas: fees.max_fee_per_gas,
     })
 }
 
@@ -1,9 +1,11 @@
 //! Command line argument definitions for Gravity bridge tools
 //! See the clap documentation for how exactly this works, note that doc comments are displayed to the user
 
+use crate::client::track::TrackTarget;
 use clap::Parser;
 use clarity::Address as EthAddress;
 use clarity::PrivateKey as EthPrivateKey;
+use clarity::Uint256;
 use deep_space::{address::Address as CosmosAddress, Coin};
 use deep_space::{CosmosPrivateKey, EthermintPrivateKey};
 use std::path::PathBuf;
@@ -33,9 +35,11 @@ pub struct Opts {
 pub enum SubCommand {
     Orchestrator(OrchestratorOpts),
     Relayer(RelayerOpts),
+    RemoteSigner(RemoteSignerOpts),
     JsonrpcServer(JsonrpcServerOpts),
     Client(ClientOpts),
     Gov(GovOpts),
+    Auction(AuctionOpts),
     Keys(KeyOpts),
     Init(InitOpts),
 }
@@ -57,6 +61,11 @@ pub struct OrchestratorOpts {
     /// (Optional) The Cosmos gRPC server that will be used
     #[clap(long, default_value = "http://localhost:9090")]
     pub cosmos_grpc: String,
+    /// (Optional) Fallback Cosmos gRPC servers, used in order if the primary server is
+    /// unhealthy or more than 10 blocks behind another server. Unreachable fallbacks are retried
+    /// on every health check. May be specified multiple times
+    #[clap(long)]
+    pub cosmos_grpc_fallback: Vec<String>,
     /// (Optional) The Ethereum RPC server that will be used
     #[clap(long, default_value = "http://localhost:8545")]
     pub ethereum_rpc: String,
@@ -66,6 +75,19 @@ pub struct OrchestratorOpts {
     /// The address fo the Gravity contract on Ethereum
     #[clap(short, long, parse(try_from_str))]
     pub gravity_contract_address: Option<EthAddress>,
+    /// (Optional) A file containing the passphrase for the encrypted keys file, if not provided
+    /// the GBT_KEYSTORE_PASSPHRASE environment variable is used or the passphrase is prompted for
+    #[clap(long, parse(from_str))]
+    pub keystore_passphrase_file: Option<PathBuf>,
+    /// (Optional) The Unix socket of a remote signer holding the delegate Ethereum key, see `gbt remote-signer`.
+    /// If set validator set, batch, and logic call confirms are signed by the remote signer and the
+    /// Ethereum key is only used for relaying
+    #[clap(long, parse(from_str))]
+    pub remote_signer: Option<PathBuf>,
+    /// (Optional) A second Cosmos gRPC server, ideally operated independently of --cosmos-grpc, validator
+    /// sets are only signed if both servers agree on them
+    #[clap(long)]
+    pub signer_cross_check_grpc: Option<String>,
 }
 
 /// The Gravity Bridge Relayer is an unpermissioned role that takes data from the Cosmos blockchain
@@ -95,6 +117,63 @@ pub struct RelayerOpts {
     /// (Optional) The Cosmos gRPC server that will be used to
     #[clap(short, long, default_value = "http://localhost:9090")]
     pub cosmos_grpc: String,
+    /// (Optional) Fallback Cosmos gRPC servers, used in order if the primary server is
+    /// unhealthy or more than 10 blocks behind another server. Unreachable fallbacks are retried
+    /// on every health check. May be specified multiple times
+    #[clap(long)]
+    pub cosmos_grpc_fallback: Vec<String>,
+    /// (Optional) A file containing the passphrase for the encrypted keys file, if not provided
+    /// the GBT_KEYSTORE_PASSPHRASE environment variable is used or the passphrase is prompted for
+    #[clap(long, parse(from_str))]
+    pub keystore_passphrase_file: Option<PathBuf>,
+    #[clap(subcommand)]
+    pub subcmd: Option<RelayerSubcommand>,
+}
+
+#[derive(Parser)]
+pub enum RelayerSubcommand {
+    Report(RelayerReportOpts),
+    GasHistory(RelayerGasHistoryOpts),
+}
+
+/// Summarize the relayer's profit and loss from its ledger of settled transactions, per reward
+/// token and per period. Values are in ETH
+#[derive(Parser)]
+pub struct RelayerReportOpts {
+    /// The length of each period in the report
+    #[clap(long, default_value = "day", possible_values = &["day", "week", "month"])]
+    pub period: String,
+    /// Only include transactions settled within this many days
+    #[clap(long, default_value = "30")]
+    pub days: u64,
+}
+
+/// Print the distribution of the gas price samples the altruistic relayer is currently using, as
+/// saved by a running relayer
+#[derive(Parser)]
+pub struct RelayerGasHistoryOpts {
+    /// Also print every sample
+    #[clap(long)]
+    pub all: bool,
+}
+
+/// A signer holding the orchestrator's delegate Ethereum key in a separate process, the orchestrator
+/// connects to it using `gbt orchestrator --remote-signer`. Only validator set, batch, and logic call
+/// checkpoints for the allowed gravity ids will be signed
+#[derive(Parser)]
+pub struct RemoteSignerOpts {
+    /// The Unix socket path to listen on
+    #[clap(long, parse(from_str))]
+    pub socket: PathBuf,
+    /// The gravity id of the bridge to sign for, may be repeated to allow several
+    #[clap(long, required = true)]
+    pub gravity_id: Vec<String>,
+    /// The delegate Ethereum private key, if not provided the key set in the config is used
+    #[clap(short, long, parse(try_from_str))]
+    pub ethereum_key: Option<EthPrivateKey>,
+    /// (Optional) A file containing the passphrase for the encrypted keys file
+    #[clap(long, parse(from_str))]
+    pub keystore_passphrase_file: Option<PathBuf>,
 }
 
 /// The Gravity Bridge Jsonrpc Server is an HTTP Server that roughly mimics the results of an Ethereum-based blockchain
@@ -140,6 +219,12 @@ pub enum ClientSubcommand {
     RelayValset(RelayValsetOpts),
     RequestAllBatches(RequestAllBatchesOpts),
     IbcAutoForward(IbcAutoForwardOpts),
+    PendingSends(PendingSendsOpts),
+    CancelSendToEth(CancelSendToEthOpts),
+    Track(TrackOpts),
+    Status(StatusOpts),
+    Erc721ToCosmos(Erc721ToCosmosOpts),
+    Erc721Holdings(Erc721HoldingsOpts),
 }
 
 /// Send Cosmos tokens to Ethereum
@@ -194,6 +279,49 @@ pub struct EthToCosmosOpts {
     pub destination: CosmosAddress,
 }
 
+/// Send an Ethereum ERC721 token to Cosmos through the GravityERC721 contract, the contract
+/// is approved to transfer the token first if needed
+#[derive(Parser)]
+pub struct Erc721ToCosmosOpts {
+    /// The Ethereum private key that owns the token
+    #[clap(long, parse(try_from_str))]
+    pub ethereum_key: EthPrivateKey,
+    /// (Optional) The Ethereum RPC server that will be used to submit the transaction
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS)]
+    pub ethereum_rpc: String,
+    /// The address of the GravityERC721 contract on Ethereum
+    #[clap(long, parse(try_from_str))]
+    pub gravity_erc721_address: EthAddress,
+    /// The ERC721 contract address of the token you are sending
+    #[clap(short, long, parse(try_from_str))]
+    pub token_contract_address: EthAddress,
+    /// The id of the token you are sending
+    #[clap(long, parse(try_from_str))]
+    pub token_id: Uint256,
+    /// The destination address on the Cosmos blockchain
+    #[clap(short, long, parse(try_from_str))]
+    pub destination: CosmosAddress,
+}
+
+/// List the ERC721 tokens held by the GravityERC721 contract and the Cosmos address each was
+/// sent to, decoded from the contract's deposit events
+#[derive(Parser)]
+pub struct Erc721HoldingsOpts {
+    /// (Optional) The Ethereum RPC server that will be used
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS)]
+    pub ethereum_rpc: String,
+    /// The address of the GravityERC721 contract on Ethereum
+    #[clap(long, parse(try_from_str))]
+    pub gravity_erc721_address: EthAddress,
+    /// (Optional) Only list tokens of this ERC721 contract
+    #[clap(short, long, parse(try_from_str))]
+    pub token_contract_address: Option<EthAddress>,
+    /// (Optional) The Ethereum block to start searching for deposits at, searching from the block
+    /// the GravityERC721 contract was deployed in saves a lot of time
+    #[clap(long, default_value = "0")]
+    pub start_block: u64,
+}
+
 /// Deploy an ERC20 representation of a Cosmos asset on the Ethereum chain
 /// this can only be run once for each time of Cosmos asset
 #[derive(Parser)]
@@ -289,6 +417,84 @@ pub struct IbcAutoForwardOpts {
     pub forwards_to_execute: u64,
 }
 
+/// Lists the SendToEth transfers of a sender that have not yet reached Ethereum, both those
+/// waiting to be batched and those already in a batch
+#[derive(Parser)]
+pub struct PendingSendsOpts {
+    /// (Optional) The Cosmos gRPC server that will be used
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    pub cosmos_grpc: String,
+    /// The Cosmos address that sent the transfers
+    #[clap(short, long, parse(try_from_str))]
+    pub sender: CosmosAddress,
+}
+
+/// Cancels SendToEth transfers that have not yet been batched, refunding the amount and bridge fee.
+/// Transfers already in a batch can not be cancelled, they are refunded only if the batch times out
+#[derive(Parser)]
+pub struct CancelSendToEthOpts {
+    /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    pub cosmos_grpc: String,
+    /// Cosmos mnemonic phrase of the sender of the transfers
+    #[clap(short, long, parse(try_from_str))]
+    pub cosmos_phrase: CosmosPrivateKey,
+    /// The id of the transfer to cancel, see `gbt client pending-sends`
+    #[clap(short, long, required_unless_present = "all", conflicts_with = "all")]
+    pub transaction_id: Option<u64>,
+    /// Cancel every transfer of the sender that has not yet been batched
+    #[clap(long)]
+    pub all: bool,
+    /// The Cosmos Denom and amount to pay Cosmos chain fees for each cancellation, if blank no
+    /// fee will be paid
+    #[clap(short, long, parse(try_from_str))]
+    pub fees: Option<Coin>,
+}
+
+/// Follows a bridge transfer through every stage until it completes or the timeout is reached.
+/// SendToEth transfers are followed through batching, signing, execution on Ethereum, and
+/// attestation. Deposits are followed through attestation, minting, and any IBC auto forward
+#[derive(Parser)]
+pub struct TrackOpts {
+    /// The transfer to track, either the hash of the Cosmos transaction that sent it, a SendToEth
+    /// transfer id, or the 0x prefixed hash of an Ethereum deposit transaction
+    #[clap(parse(try_from_str))]
+    pub transfer: TrackTarget,
+    /// (Optional) The Cosmos gRPC server that will be used
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS)]
+    pub ethereum_rpc: String,
+    /// (Optional) The address of the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str))]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// How long to keep polling for progress in seconds before giving up
+    #[clap(long, default_value = "3600")]
+    pub timeout: u64,
+}
+
+/// Reports the health of the bridge: validator set and event nonces on Ethereum and Cosmos,
+/// pending batch fees and unbatched transfers per token, batches awaiting signatures, pending
+/// IBC auto forwards, and whether the bridge is paused
+#[derive(Parser)]
+pub struct StatusOpts {
+    /// (Optional) The Cosmos gRPC server that will be used
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS)]
+    pub ethereum_rpc: String,
+    /// (Optional) The address of the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str))]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// Print the status as json
+    #[clap(long)]
+    pub json: bool,
+}
+
 /// Requests all possible batches for all token types. Useful to deal with relayers that will
 /// only relay a batch if it is profitable and already requested.
 #[derive(Parser)]
@@ -322,6 +528,9 @@ pub enum KeysSubcommand {
     SetEthereumKey(SetEthereumKeyOpts),
     SetOrchestratorKey(SetOrchestratorKeyOpts),
     Show,
+    /// Encrypt the stored keys, keys stored unencrypted by older versions will be
+    /// migrated to the encrypted keystore format
+    Encrypt,
     RecoverFunds(RecoverFundsOpts),
 }
 
@@ -548,3 +757,73 @@ pub struct CosmosBridgeableTokensProposalOpts {
     #[clap(short, long, parse(try_from_str))]
     pub fees: Coin,
 }
+
+/// The Gravity Bridge Auction subcommand contains tools for viewing and bidding on the auctions of
+/// the tokens collected by the auction pool
+#[derive(Parser)]
+pub struct AuctionOpts {
+    #[clap(subcommand)]
+    pub subcmd: AuctionSubcommand,
+}
+
+#[derive(Parser)]
+pub enum AuctionSubcommand {
+    List(AuctionListOpts),
+    Pool(AuctionPoolOpts),
+    Bidder(AuctionBidderOpts),
+    Bid(AuctionBidOpts),
+}
+
+/// Lists the auctions of the current auction period with their highest bids and the blocks
+/// remaining until the period ends
+#[derive(Parser)]
+pub struct AuctionListOpts {
+    /// (Optional) The Cosmos gRPC server that will be used
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    pub cosmos_grpc: String,
+}
+
+/// Shows the tokens in the auction pool, which will be auctioned in the next auction period, and
+/// the current auction period
+#[derive(Parser)]
+pub struct AuctionPoolOpts {
+    /// (Optional) The Cosmos gRPC server that will be used
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    pub cosmos_grpc: String,
+}
+
+/// Shows the auctions of the current auction period where an address holds the highest bid
+#[derive(Parser)]
+pub struct AuctionBidderOpts {
+    /// (Optional) The Cosmos gRPC server that will be used
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    pub cosmos_grpc: String,
+    /// The bidder address to look up
+    #[clap(parse(try_from_str))]
+    pub bidder: CosmosAddress,
+}
+
+/// Bids on an auction of the current auction period, the bid is paid in the native token and
+/// returned if another bidder outbids you
+#[derive(Parser)]
+pub struct AuctionBidOpts {
+    /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    pub cosmos_grpc: String,
+    /// Cosmos mnemonic phrase containing the tokens you would like to bid
+    #[clap(short, long, parse(try_from_str))]
+    pub cosmos_phrase: CosmosPrivateKey,
+    /// The id of the auction to bid on, see `gbt auction list`
+    #[clap(short, long)]
+    pub auction_id: u64,
+    /// The amount to bid, in the smallest unit of the native token
+    #[clap(long)]
+    pub amount: u64,
+    /// (Optional) The fee paid to stakers for the bid, whether or not it wins. Defaults to the
+    /// minimum bid fee
+    #[clap(long)]
+    pub bid_fee: Option<u64>,
+    /// (Optional) The Cosmos Denom and amount to pay Cosmos chain fees, if blank no fee will be paid
+    #[clap(short, long, parse(try_from_str))]
+    pub fees: Option<Coin>,
+}
@@ -0,0 +1,184 @@
+use crate::args::AuctionBidOpts;
+use crate::auction::queries::{format_auction, get_bid_denom};
+use crate::utils::TIMEOUT;
+use clarity::Uint256;
+use cosmos_gravity::query::{get_auction_by_id, get_auction_module_params};
+use cosmos_gravity::send::bid_on_auction;
+use deep_space::{Coin, PrivateKey};
+use gravity_proto::auction::Bid;
+use gravity_utils::connection_prep::{check_for_fee, create_rpc_connections};
+use std::process::exit;
+
+/// Checks a bid the same way the auction module will, returning the reason it would be rejected.
+/// `chain_fee` is the part of the transaction fee paid in the bid denom, it is deducted from the
+/// same balance before the bid is processed
+#[allow(clippy::too_many_arguments)]
+pub fn check_bid(
+    bidder: &str,
+    amount: u64,
+    bid_fee: u64,
+    min_bid_fee: u64,
+    highest_bid: Option<&Bid>,
+    balance: Uint256,
+    chain_fee: Uint256,
+    bid_denom: &str,
+) -> Result<(), String> {
+    if amount == 0 {
+        return Err("Bid amount must be positive".to_string());
+    }
+    if bid_fee < min_bid_fee {
+        return Err(format!(
+            "Bid fee {bid_fee}{bid_denom} is less than the minimum bid fee {min_bid_fee}{bid_denom}"
+        ));
+    }
+    if let Some(highest) = highest_bid {
+        if highest.bidder_address == bidder {
+            return Err(format!(
+                "{bidder} already holds the highest bid, bidding again is not allowed"
+            ));
+        }
+        // the module only rejects bids below the highest bid, an equal bid replaces it
+        if amount < highest.bid_amount {
+            return Err(format!(
+                "Bid of {amount}{bid_denom} is less than the highest bid of {}{bid_denom}",
+                highest.bid_amount
+            ));
+        }
+    }
+    // the bid and the bid fee are both taken from the bidder's balance
+    let required = Uint256::from(amount as u128 + bid_fee as u128) + chain_fee;
+    if balance < required {
+        return Err(format!(
+            "Your bid of {amount}{bid_denom} with bid fee {bid_fee}{bid_denom} and chain fee {chain_fee}{bid_denom} is greater than your balance of {balance}{bid_denom}"
+        ));
+    }
+    Ok(())
+}
+
+pub async fn bid(opts: AuctionBidOpts, prefix: String) {
+    let cosmos_key = opts.cosmos_phrase;
+    let bidder = cosmos_key.to_address(&prefix).unwrap();
+    let auction_id = opts.auction_id;
+    let amount = opts.amount;
+
+    let connections = create_rpc_connections(prefix, Some(opts.cosmos_grpc), None, TIMEOUT).await;
+    let contact = connections.contact.unwrap();
+    let bid_denom = get_bid_denom(&contact).await;
+
+    let fee = match opts.fees {
+        None => Coin {
+            amount: 0u8.into(),
+            denom: bid_denom.clone(),
+        },
+        Some(f) => f,
+    };
+
+    let params = match get_auction_module_params(&contact).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to get auction params {e:?}");
+            exit(1);
+        }
+    };
+    if !params.enabled {
+        error!("Auctions are disabled by governance, bidding will fail");
+        exit(1);
+    }
+    let bid_fee = opts.bid_fee.unwrap_or(params.min_bid_fee);
+
+    let auction = match get_auction_by_id(&contact, auction_id).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not find auction {auction_id} in the current auction period, see `gbt auction list` {e:?}");
+            exit(1);
+        }
+    };
+    info!("{}", format_auction(&auction, &bid_denom));
+
+    let chain_fee = if fee.denom == bid_denom {
+        fee.amount
+    } else {
+        check_for_fee(&fee, bidder, &contact).await;
+        0u8.into()
+    };
+    let balance = contact
+        .get_balance(bidder, bid_denom.clone())
+        .await
+        .expect("Failed to get balances!")
+        .map(|c| c.amount)
+        .unwrap_or_else(|| 0u8.into());
+    if let Err(e) = check_bid(
+        &bidder.to_string(),
+        amount,
+        bid_fee,
+        params.min_bid_fee,
+        auction.highest_bid.as_ref(),
+        balance,
+        chain_fee,
+        &bid_denom,
+    ) {
+        error!("{e}");
+        exit(1);
+    }
+
+    info!("Bidding {amount}{bid_denom} on auction {auction_id} with bid fee {bid_fee}{bid_denom}");
+    match bid_on_auction(cosmos_key, fee, &contact, auction_id, amount, bid_fee).await {
+        Ok(res) => info!(
+            "Bid on auction {auction_id} with txid {}, your bid is returned if you are outbid",
+            res.txhash()
+        ),
+        Err(e) => {
+            error!("Failed to bid {e:?}");
+            exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_bid() {
+        let bidder = "gravity1bidder";
+        let highest = Bid {
+            bid_amount: 1000,
+            bidder_address: "gravity1other".to_string(),
+        };
+        let balance = Uint256::from(2000u64);
+        let zero = Uint256::from(0u8);
+        let check = |amount, bid_fee, highest: Option<&Bid>, balance, chain_fee| {
+            check_bid(
+                bidder,
+                amount,
+                bid_fee,
+                10,
+                highest,
+                balance,
+                chain_fee,
+                "ugraviton",
+            )
+        };
+
+        assert!(check(1001, 10, Some(&highest), balance, zero).is_ok());
+        // the module accepts a bid equal to the highest bid
+        assert!(check(1000, 10, Some(&highest), balance, zero).is_ok());
+        assert!(check(999, 10, Some(&highest), balance, zero).is_err());
+        assert!(check(1, 10, None, balance, zero).is_ok());
+        assert!(check(0, 10, None, balance, zero).is_err());
+        // the bid fee must meet the minimum
+        assert!(check(1001, 9, Some(&highest), balance, zero).is_err());
+        // the highest bidder may not bid again
+        let own = Bid {
+            bid_amount: 1000,
+            bidder_address: bidder.to_string(),
+        };
+        assert!(check(1500, 10, Some(&own), balance, zero).is_err());
+        // the balance must cover the bid, the bid fee and the chain fee
+        assert!(check(1990, 10, None, balance, zero).is_ok());
+        assert!(check(1991, 10, None, balance, zero).is_err());
+        assert!(check(1980, 10, None, balance, Uint256::from(10u8)).is_ok());
+        assert!(check(1980, 10, None, balance, Uint256::from(11u8)).is_err());
+        assert!(check(1, 10, None, zero, zero).is_err());
+    }
+}
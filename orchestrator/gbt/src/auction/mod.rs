@@ -0,0 +1,2 @@
+pub mod bid;
+pub mod queries;
@@ -0,0 +1,142 @@
+use crate::args::{AuctionBidderOpts, AuctionListOpts, AuctionPoolOpts};
+use crate::utils::TIMEOUT;
+use cosmos_gravity::query::{
+    get_auction_module_params, get_auction_period, get_auction_pool, get_auctions,
+    get_auctions_by_bidder, get_mint_denom,
+};
+use cosmos_gravity::utils::get_current_cosmos_height;
+use deep_space::Contact;
+use gravity_proto::auction::Auction;
+use gravity_utils::connection_prep::create_rpc_connections;
+use std::process::exit;
+
+pub async fn list_auctions(opts: AuctionListOpts, prefix: String) {
+    let contact = connect(opts.cosmos_grpc, prefix).await;
+    let bid_denom = get_bid_denom(&contact).await;
+
+    print_period(&contact).await;
+    let auctions = match get_auctions(&contact).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to get auctions {e:?}");
+            exit(1);
+        }
+    };
+    if auctions.is_empty() {
+        info!("No auctions in the current auction period");
+    }
+    for auction in auctions.iter() {
+        info!("{}", format_auction(auction, &bid_denom));
+    }
+}
+
+pub async fn auction_pool(opts: AuctionPoolOpts, prefix: String) {
+    let contact = connect(opts.cosmos_grpc, prefix).await;
+    let bid_denom = get_bid_denom(&contact).await;
+
+    let params = match get_auction_module_params(&contact).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to get auction params {e:?}");
+            exit(1);
+        }
+    };
+    if params.enabled {
+        info!(
+            "Auctions enabled, each auction period lasts {} blocks, the minimum bid fee is {}{bid_denom}",
+            params.auction_length, params.min_bid_fee
+        );
+    } else {
+        warn!("Auctions are disabled by governance, bids will fail");
+    }
+    print_period(&contact).await;
+
+    let pool = match get_auction_pool(&contact).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to get the auction pool {e:?}");
+            exit(1);
+        }
+    };
+    info!(
+        "Auction pool {} holds {} tokens to auction in the next auction period",
+        pool.account,
+        pool.balances.len()
+    );
+    for balance in pool.balances.iter() {
+        info!("{}{}", balance.amount, balance.denom);
+    }
+}
+
+pub async fn bidder_auctions(opts: AuctionBidderOpts, prefix: String) {
+    let contact = connect(opts.cosmos_grpc, prefix).await;
+    let bid_denom = get_bid_denom(&contact).await;
+
+    let auctions = match get_auctions_by_bidder(&contact, opts.bidder).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to get auctions for {} {e:?}", opts.bidder);
+            exit(1);
+        }
+    };
+    if auctions.is_empty() {
+        info!(
+            "{} is not the highest bidder on any auction of the current auction period",
+            opts.bidder
+        );
+    }
+    for auction in auctions.iter() {
+        info!("{}", format_auction(auction, &bid_denom));
+    }
+}
+
+async fn connect(cosmos_grpc: String, prefix: String) -> Contact {
+    let connections = create_rpc_connections(prefix, Some(cosmos_grpc), None, TIMEOUT).await;
+    connections.contact.unwrap()
+}
+
+/// Gets the denom bids and bid fees are paid in, the auction module uses the mint module's denom
+pub async fn get_bid_denom(contact: &Contact) -> String {
+    match get_mint_denom(contact).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to get the denom bids are paid in {e:?}");
+            exit(1);
+        }
+    }
+}
+
+/// Prints the current auction period and the blocks remaining until it ends and its auctions close
+async fn print_period(contact: &Contact) {
+    let period = match get_auction_period(contact).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to get the auction period {e:?}");
+            exit(1);
+        }
+    };
+    let height = get_current_cosmos_height(contact)
+        .await
+        .expect("Failed to get the current block height");
+    info!(
+        "Auction period from block {} to {}, {} blocks remaining",
+        period.start_block_height,
+        period.end_block_height,
+        period.end_block_height.saturating_sub(height)
+    );
+}
+
+pub fn format_auction(auction: &Auction, bid_denom: &str) -> String {
+    let amount = match &auction.amount {
+        Some(c) => format!("{}{}", c.amount, c.denom),
+        None => "unknown".to_string(),
+    };
+    let bid = match &auction.highest_bid {
+        Some(b) => format!(
+            "highest bid {}{bid_denom} by {}",
+            b.bid_amount, b.bidder_address
+        ),
+        None => "no bids".to_string(),
+    };
+    format!("Auction id {} of {amount}, {bid}", auction.id)
+}
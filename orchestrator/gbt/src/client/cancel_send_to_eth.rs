@@ -0,0 +1,79 @@
+use crate::args::CancelSendToEthOpts;
+use crate::client::pending_sends::{format_transfer, get_pending_sends};
+use crate::utils::TIMEOUT;
+use cosmos_gravity::send::cancel_send_to_eth;
+use deep_space::{Coin, PrivateKey};
+use gravity_utils::connection_prep::create_rpc_connections;
+use std::process::exit;
+
+pub async fn cancel_send_to_eth_cmd(args: CancelSendToEthOpts, address_prefix: String) {
+    let cosmos_key = args.cosmos_phrase;
+    let sender = cosmos_key.to_address(&address_prefix).unwrap();
+
+    let connections =
+        create_rpc_connections(address_prefix, Some(args.cosmos_grpc), None, TIMEOUT).await;
+    let contact = connections.contact.unwrap();
+    let mut grpc_v2 = connections.grpc_v2.unwrap();
+
+    let fee = match args.fees {
+        None => Coin {
+            amount: 0u8.into(),
+            denom: "ugraviton".to_string(),
+        },
+        Some(f) => f,
+    };
+
+    let pending = get_pending_sends(&mut grpc_v2, sender).await;
+
+    let to_cancel = match args.transaction_id {
+        Some(id) => {
+            if let Some(in_batch) = pending
+                .transfers_in_batches
+                .iter()
+                .find(|t| t.transfer.as_ref().map(|t| t.id) == Some(id))
+            {
+                let batch = in_batch.batch.clone().unwrap_or_default();
+                error!(
+                    "Transfer {id} is already in batch nonce {} and can not be cancelled, it will be refunded only if the batch times out at Ethereum block {}",
+                    batch.batch_nonce, batch.batch_timeout
+                );
+                exit(1);
+            }
+            match pending.unbatched_transfers.iter().find(|t| t.id == id) {
+                Some(t) => vec![t.clone()],
+                None => {
+                    error!("Transfer {id} is not a pending transfer sent by {sender}, see `gbt client pending-sends`");
+                    exit(1);
+                }
+            }
+        }
+        None => pending.unbatched_transfers,
+    };
+
+    if to_cancel.is_empty() {
+        info!(
+            "{sender} has no cancellable transfers, {} transfers are already in batches",
+            pending.transfers_in_batches.len()
+        );
+        return;
+    }
+
+    let mut failed = false;
+    for transfer in to_cancel {
+        info!("Cancelling {}", format_transfer(&transfer));
+        match cancel_send_to_eth(cosmos_key, fee.clone(), &contact, transfer.id).await {
+            Ok(res) => info!(
+                "Cancelled transfer {} with txid {}",
+                transfer.id,
+                res.txhash()
+            ),
+            Err(e) => {
+                error!("Failed to cancel transfer {} {e:?}", transfer.id);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        exit(1);
+    }
+}
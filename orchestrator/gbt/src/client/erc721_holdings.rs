@@ -0,0 +1,96 @@
+//! Lists the ERC721 tokens held by the GravityERC721 contract. Withdrawals from the contract emit no
+//! event, so every deposit found is checked against the token's current owner
+
+use crate::args::Erc721HoldingsOpts;
+use crate::utils::TIMEOUT;
+use clarity::Address as EthAddress;
+use clarity::Uint256;
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::types::event_signatures::SEND_ERC721_TO_COSMOS_EVENT_SIG;
+use gravity_utils::types::SendErc721ToCosmosEvent;
+use std::collections::HashMap;
+use std::process::exit;
+
+const BLOCKS_TO_SEARCH: u64 = 5_000;
+
+pub async fn erc721_holdings(args: Erc721HoldingsOpts, prefix: String) {
+    let gravity_erc721_address = args.gravity_erc721_address;
+    let connections = create_rpc_connections(prefix, None, Some(args.ethereum_rpc), TIMEOUT).await;
+    let web3 = connections.web3.unwrap();
+
+    let latest_block = web3
+        .eth_block_number()
+        .await
+        .expect("Failed to get the latest Ethereum block");
+    let latest_block: u64 = latest_block.to_string().parse().unwrap();
+
+    // a token may be deposited, withdrawn and deposited again, only the latest deposit is kept
+    let mut deposits: HashMap<(EthAddress, Uint256), SendErc721ToCosmosEvent> = HashMap::new();
+    let mut start = args.start_block;
+    while start <= latest_block {
+        let end = latest_block.min(start + BLOCKS_TO_SEARCH);
+        let logs = match web3
+            .check_for_events(
+                start.into(),
+                Some(end.into()),
+                vec![gravity_erc721_address],
+                vec![SEND_ERC721_TO_COSMOS_EVENT_SIG],
+            )
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Failed to search blocks {start} to {end} for deposits {e:?}");
+                exit(1);
+            }
+        };
+        let events = match SendErc721ToCosmosEvent::from_logs(&logs) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Failed to parse GravityERC721 deposit events {e:?}");
+                exit(1);
+            }
+        };
+        for event in events {
+            if args
+                .token_contract_address
+                .map_or(false, |t| t != event.erc721)
+            {
+                continue;
+            }
+            deposits.insert((event.erc721, event.token_id), event);
+        }
+        start = end + 1;
+    }
+
+    let mut deposits: Vec<SendErc721ToCosmosEvent> = deposits.into_values().collect();
+    deposits.sort_by_key(|d| d.event_nonce);
+
+    let mut held = 0;
+    for deposit in deposits {
+        let (erc721, token_id) = (deposit.erc721, deposit.token_id);
+        let owner = match web3
+            .get_erc721_owner_of(erc721, gravity_erc721_address, token_id, vec![])
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("Failed to get the owner of {erc721} token id {token_id} {e:?}");
+                continue;
+            }
+        };
+        if owner != gravity_erc721_address {
+            continue;
+        }
+        held += 1;
+        let destination = match deposit.validated_destination {
+            Some(d) => d.to_string(),
+            None => format!("{} (invalid Cosmos address)", deposit.destination),
+        };
+        info!(
+            "{erc721} token id {token_id} sent to {destination} by {} at block {}",
+            deposit.sender, deposit.block_height
+        );
+    }
+    info!("{held} tokens held by GravityERC721 {gravity_erc721_address}");
+}
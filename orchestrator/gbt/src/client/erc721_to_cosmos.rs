@@ -0,0 +1,77 @@
+use std::process::exit;
+
+use crate::args::Erc721ToCosmosOpts;
+use crate::utils::TIMEOUT;
+use ethereum_gravity::send_erc721_to_cosmos::send_erc721_to_cosmos;
+use ethereum_gravity::utils::get_gravity_sol_address;
+use gravity_utils::connection_prep::{check_for_eth, create_rpc_connections};
+
+pub async fn erc721_to_cosmos(args: Erc721ToCosmosOpts, prefix: String) {
+    let gravity_erc721_address = args.gravity_erc721_address;
+    let erc721_address = args.token_contract_address;
+    let token_id = args.token_id;
+    let cosmos_dest = args.destination;
+    let ethereum_key = args.ethereum_key;
+    let ethereum_public_key = ethereum_key.to_address();
+
+    let connections = create_rpc_connections(prefix, None, Some(args.ethereum_rpc), TIMEOUT).await;
+
+    let web3 = connections.web3.unwrap();
+
+    get_gravity_sol_address(gravity_erc721_address, ethereum_public_key, &web3)
+        .await
+        .expect("Incorrect GravityERC721 Address or otherwise unable to contact GravityERC721");
+
+    check_for_eth(ethereum_public_key, &web3).await;
+
+    let owner = web3
+        .get_erc721_owner_of(erc721_address, ethereum_public_key, token_id, vec![])
+        .await
+        .expect("Failed to get token owner, check ERC721 contract address and token id");
+    if owner != ethereum_public_key {
+        error!(
+            "Token id {token_id} of {erc721_address} is owned by {owner} not {ethereum_public_key}, please double check your sender and token!"
+        );
+        exit(1);
+    }
+
+    let symbol = web3
+        .get_erc721_symbol(erc721_address, ethereum_public_key, vec![])
+        .await
+        .unwrap_or_else(|_| erc721_address.to_string());
+    let approved = web3
+        .check_erc721_approved(erc721_address, ethereum_public_key, token_id, vec![])
+        .await
+        .expect("Failed to check ERC721 approval");
+    if approved == Some(gravity_erc721_address) {
+        info!("GravityERC721 is already approved to transfer {symbol} token id {token_id}");
+    } else {
+        info!("GravityERC721 is not approved to transfer {symbol} token id {token_id}, an approval will be sent first");
+    }
+
+    info!(
+        "Sending {symbol} token id {token_id} to Cosmos from {ethereum_public_key} to {cosmos_dest}"
+    );
+    let res = send_erc721_to_cosmos(
+        erc721_address,
+        gravity_erc721_address,
+        token_id,
+        cosmos_dest,
+        ethereum_key,
+        Some(TIMEOUT),
+        &web3,
+        vec![],
+    )
+    .await;
+    match res {
+        Ok(tx_id) => info!("Send ERC721 to Cosmos txid: {tx_id:#066x}"),
+        Err(e) => {
+            error!("Failed to send token! {e:?}");
+            exit(1);
+        }
+    }
+
+    info!(
+        "Your token is now held by the GravityERC721 contract {gravity_erc721_address} on behalf of {cosmos_dest}, see `gbt client erc721-holdings`"
+    )
+}
@@ -1,7 +1,13 @@
+pub mod cancel_send_to_eth;
 pub mod cosmos_to_eth;
 pub mod deploy_erc20_representation;
+pub mod erc721_holdings;
+pub mod erc721_to_cosmos;
 pub mod eth_to_cosmos;
 pub mod ibc_auto_forward;
+pub mod pending_sends;
 pub mod relay_valset;
 pub mod request_all_batches;
 pub mod spot_relay;
+pub mod status;
+pub mod track;
@@ -0,0 +1,83 @@
+use crate::args::PendingSendsOpts;
+use crate::utils::TIMEOUT;
+use cosmos_gravity::query::get_pending_send_to_eth_v2_by_sender;
+use deep_space::Address as CosmosAddress;
+use gravity_proto::gravity::v1::{Erc20Token, OutgoingTransferTx};
+use gravity_proto::gravity::v2::query_client::QueryClient as GravityQueryClientV2;
+use gravity_proto::gravity::v2::QueryPendingSendToEthV2BySenderResponse;
+use gravity_utils::connection_prep::create_rpc_connections;
+use std::process::exit;
+use tonic::transport::Channel;
+
+pub async fn pending_sends(args: PendingSendsOpts, address_prefix: String) {
+    let connections =
+        create_rpc_connections(address_prefix, Some(args.cosmos_grpc), None, TIMEOUT).await;
+    let mut grpc_v2 = connections.grpc_v2.unwrap();
+    let pending = get_pending_sends(&mut grpc_v2, args.sender).await;
+
+    if pending.unbatched_transfers.is_empty() && pending.transfers_in_batches.is_empty() {
+        info!("No pending transfers to Ethereum found for {}", args.sender);
+        return;
+    }
+
+    info!(
+        "{} unbatched transfers, these can be cancelled with `gbt client cancel-send-to-eth`",
+        pending.unbatched_transfers.len()
+    );
+    for transfer in pending.unbatched_transfers.iter() {
+        info!("{}", format_transfer(transfer));
+    }
+
+    info!(
+        "{} transfers in batches, these can not be cancelled and are refunded only if the batch times out",
+        pending.transfers_in_batches.len()
+    );
+    for in_batch in pending.transfers_in_batches.iter() {
+        let (transfer, batch) = match (&in_batch.transfer, &in_batch.batch) {
+            (Some(t), Some(b)) => (t, b),
+            _ => continue,
+        };
+        info!(
+            "{} batch nonce {} times out at Ethereum block {}",
+            format_transfer(transfer),
+            batch.batch_nonce,
+            batch.batch_timeout
+        );
+    }
+    info!(
+        "Chain fees are not shown, the pending transfers query does not include them. The chain fee of a transfer is paid when it is sent and is not refunded on cancellation"
+    );
+}
+
+/// Queries the transfers of `sender` that have not yet been executed on Ethereum, exiting on failure
+pub async fn get_pending_sends(
+    grpc_v2: &mut GravityQueryClientV2<Channel>,
+    sender: CosmosAddress,
+) -> QueryPendingSendToEthV2BySenderResponse {
+    match get_pending_send_to_eth_v2_by_sender(grpc_v2, sender).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to query pending transfers {e:?}");
+            exit(1);
+        }
+    }
+}
+
+fn format_token(token: &Option<Erc20Token>) -> String {
+    match token {
+        Some(t) => format!("{} {}", t.amount, t.contract),
+        None => "unknown".to_string(),
+    }
+}
+
+/// Describes a pending transfer, the chain fee is not part of the transfer as stored by the chain
+/// so it can not be shown
+pub fn format_transfer(transfer: &OutgoingTransferTx) -> String {
+    format!(
+        "Transfer id {} of {} to {} with bridge fee {}",
+        transfer.id,
+        format_token(&transfer.erc20_token),
+        transfer.dest_address,
+        format_token(&transfer.erc20_fee)
+    )
+}
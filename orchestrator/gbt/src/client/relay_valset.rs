@@ -119,6 +119,7 @@ pub async fn relay_valset(args: RelayValsetOpts, address_prefix: String) {
         gravity_contract_address,
         gravity_id,
         ethereum_key,
+        vec![],
     )
     .await;
 
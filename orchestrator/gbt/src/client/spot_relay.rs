@@ -134,6 +134,7 @@ pub async fn spot_relay(args: SpotRelayOpts, address_prefix: String) {
             gravity_contract_address,
             gravity_id.clone(),
             ethereum_key,
+            vec![],
         )
         .await;
         match res {
@@ -0,0 +1,371 @@
+//! Reports the health of the bridge in one place: whether validator sets and Ethereum events are
+//! keeping up on both sides, what is waiting to be batched or signed, and whether the bridge is paused
+
+use crate::args::StatusOpts;
+use crate::utils::TIMEOUT;
+use clarity::constants::zero_address;
+use clarity::Address as EthAddress;
+use clarity::Uint256;
+use cosmos_gravity::query::{
+    get_all_pending_ibc_auto_forwards, get_current_valset, get_gravity_params,
+    get_last_observed_eth_block, get_last_observed_eth_nonce, get_latest_transaction_batches,
+    get_latest_valsets, get_pending_batch_fees, get_pending_send_to_eth_v2,
+    get_transaction_batch_signatures,
+};
+use cosmos_gravity::utils::get_current_cosmos_height;
+use ethereum_gravity::utils::get_event_nonce;
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::service_client::ServiceClient as TxServiceClient;
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::{GetTxsEventRequest, OrderBy};
+use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
+use gravity_proto::gravity::v1::QueryDelegateKeysByEthAddress;
+use gravity_proto::gravity::v2::query_client::QueryClient as GravityQueryClientV2;
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::types::{GRAVITY_POWER_TO_PASS, TOTAL_GRAVITY_POWER};
+use relayer::find_latest_valset::find_latest_valset;
+use std::collections::BTreeMap;
+use std::process::exit;
+use tonic::transport::Channel;
+
+/// The pending transfers of a single token
+#[derive(Serialize, Debug, Default)]
+struct TokenStatus {
+    token: String,
+    /// the total bridge fees of the transfers that are not yet batched
+    pending_batch_fees: String,
+    unbatched_transfers: u64,
+    /// transfer ids are assigned in order, the lowest id is the transfer waiting the longest
+    oldest_unbatched_transfer_id: Option<u64>,
+    /// when the oldest unbatched transfer entered the pool, None if the node does not index
+    /// transactions or the transfer could not be found
+    oldest_unbatched_transfer_created: Option<TransferCreation>,
+}
+
+/// The Cosmos block and time at which a transfer entered the pool
+#[derive(Serialize, Debug)]
+struct TransferCreation {
+    block: u64,
+    time: String,
+    age_blocks: u64,
+}
+
+/// A batch that does not yet have enough signatures to be executed
+#[derive(Serialize, Debug)]
+struct UnsignedBatch {
+    token: EthAddress,
+    nonce: u64,
+    signed_power_percent: f64,
+    /// the validators that have not signed, by operator address if their keys could be looked up
+    /// or by Ethereum address otherwise
+    missing_signers: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+struct BridgeStatus {
+    bridge_active: bool,
+    cosmos_block: u64,
+    ethereum_block: Uint256,
+    last_observed_ethereum_block: u64,
+    ethereum_valset_nonce: Option<u64>,
+    cosmos_valset_nonce: Option<u64>,
+    ethereum_event_nonce: u64,
+    last_observed_event_nonce: u64,
+    tokens: Vec<TokenStatus>,
+    unsigned_batches: Vec<UnsignedBatch>,
+    pending_ibc_auto_forwards: usize,
+}
+
+fn to_percent(power: u64) -> f64 {
+    power as f64 / TOTAL_GRAVITY_POWER as f64 * 100f64
+}
+
+/// Finds when the transfer `id` entered the pool using the EventWithdrawalReceived event emitted
+/// by the transaction that created it, this requires the node to index transactions
+async fn get_transfer_creation(
+    tx_client: &mut TxServiceClient<Channel>,
+    id: u64,
+    cosmos_block: u64,
+) -> Option<TransferCreation> {
+    // typed event attributes are json encoded
+    let query = format!("gravity.v1.EventWithdrawalReceived.outgoing_tx_id='\"{id}\"'");
+    let res = tx_client
+        .get_txs_event(GetTxsEventRequest {
+            query,
+            order_by: OrderBy::Asc as i32,
+            page: 1,
+            limit: 1,
+            ..Default::default()
+        })
+        .await;
+    let txs = match res {
+        Ok(v) => v.into_inner().tx_responses,
+        Err(e) => {
+            warn!("Could not search for the transaction that created transfer {id}, is transaction indexing enabled on the node? {e:?}");
+            return None;
+        }
+    };
+    txs.first().map(|tx| {
+        let block = tx.height as u64;
+        TransferCreation {
+            block,
+            time: tx.timestamp.clone(),
+            age_blocks: cosmos_block.saturating_sub(block),
+        }
+    })
+}
+
+/// Collects the pending batch fees and unbatched transfers for every token with either
+/// waiting to be batched
+async fn get_token_statuses(
+    grpc: &mut GravityQueryClient<Channel>,
+    grpc_v2: &mut GravityQueryClientV2<Channel>,
+    tx_client: &mut TxServiceClient<Channel>,
+    cosmos_block: u64,
+) -> Vec<TokenStatus> {
+    let mut tokens: BTreeMap<String, TokenStatus> = BTreeMap::new();
+    let batch_fees = get_pending_batch_fees(grpc)
+        .await
+        .expect("Failed to get pending batch fees");
+    for fee in batch_fees.batch_fees {
+        let status = tokens.entry(fee.token.clone()).or_default();
+        status.token = fee.token;
+        status.pending_batch_fees = fee.total_fees;
+    }
+    let pending = get_pending_send_to_eth_v2(grpc_v2)
+        .await
+        .expect("Failed to get pending transfers");
+    for transfer in pending.unbatched_transfers {
+        let token = match transfer.erc20_token {
+            Some(t) => t.contract,
+            None => continue,
+        };
+        let status = tokens.entry(token.clone()).or_default();
+        status.token = token;
+        status.unbatched_transfers += 1;
+        status.oldest_unbatched_transfer_id = Some(
+            status
+                .oldest_unbatched_transfer_id
+                .map_or(transfer.id, |id| id.min(transfer.id)),
+        );
+    }
+    for status in tokens.values_mut() {
+        if let Some(id) = status.oldest_unbatched_transfer_id {
+            status.oldest_unbatched_transfer_created =
+                get_transfer_creation(tx_client, id, cosmos_block).await;
+        }
+    }
+    tokens.into_values().collect()
+}
+
+/// Finds the batches on the Gravity Bridge chain that do not yet have enough signatures to be
+/// executed and the validators that have not signed them
+async fn get_unsigned_batches(grpc: &mut GravityQueryClient<Channel>) -> Vec<UnsignedBatch> {
+    let valset = get_current_valset(grpc)
+        .await
+        .expect("Failed to get the current validator set");
+    let batches = get_latest_transaction_batches(grpc)
+        .await
+        .expect("Failed to get pending batches");
+    let mut unsigned = Vec::new();
+    for batch in batches {
+        let confirms = get_transaction_batch_signatures(grpc, batch.nonce, batch.token_contract)
+            .await
+            .expect("Failed to get batch signatures");
+        let (signed, missing): (Vec<_>, Vec<_>) = valset
+            .members
+            .iter()
+            .partition(|m| confirms.iter().any(|c| c.ethereum_signer == m.eth_address));
+        let signed_power: u64 = signed.iter().map(|m| m.power).sum();
+        if signed_power >= GRAVITY_POWER_TO_PASS {
+            continue;
+        }
+        let mut missing_signers = Vec::new();
+        for member in missing {
+            let validator = grpc
+                .get_delegate_key_by_eth(QueryDelegateKeysByEthAddress {
+                    eth_address: member.eth_address.to_string(),
+                })
+                .await
+                .map(|r| r.into_inner().validator_address);
+            missing_signers.push(validator.unwrap_or_else(|_| member.eth_address.to_string()));
+        }
+        unsigned.push(UnsignedBatch {
+            token: batch.token_contract,
+            nonce: batch.nonce,
+            signed_power_percent: to_percent(signed_power),
+            missing_signers,
+        });
+    }
+    unsigned
+}
+
+pub async fn status(args: StatusOpts, address_prefix: String) {
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let web3 = connections.web3.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let contact = connections.contact.unwrap();
+    let mut grpc_v2 = connections.grpc_v2.unwrap();
+    // the url create_rpc_connections settled on, after any localhost or https corrections
+    let cosmos_url = connections.cosmos_pool.unwrap().get_active_url();
+    let mut tx_client = match TxServiceClient::connect(cosmos_url).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not connect to the Cosmos gRPC server {e:?}");
+            exit(1);
+        }
+    };
+    let cosmos_block = get_current_cosmos_height(&contact)
+        .await
+        .expect("Failed to get the current Cosmos block");
+
+    let params = get_gravity_params(&mut grpc)
+        .await
+        .expect("Failed to get Gravity Bridge module parameters!");
+    let gravity_contract_address = if let Some(c) = args.gravity_contract_address {
+        c
+    } else {
+        match params.bridge_ethereum_address.parse() {
+            Ok(v) => v,
+            Err(_) => {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+        }
+    };
+
+    let ethereum_valset_nonce =
+        match find_latest_valset(&mut grpc, gravity_contract_address, &web3).await {
+            Ok(v) => Some(v.nonce),
+            Err(e) => {
+                warn!("Could not find the latest validator set on Ethereum {e:?}");
+                None
+            }
+        };
+    let cosmos_valset_nonce = get_latest_valsets(&mut grpc)
+        .await
+        .expect("Failed to get the latest validator sets")
+        .iter()
+        .map(|v| v.nonce)
+        .max();
+
+    let status = BridgeStatus {
+        bridge_active: params.bridge_active,
+        cosmos_block,
+        ethereum_block: web3
+            .eth_block_number()
+            .await
+            .expect("Failed to get the latest Ethereum block"),
+        last_observed_ethereum_block: get_last_observed_eth_block(&mut grpc)
+            .await
+            .expect("Failed to get the last observed Ethereum block"),
+        ethereum_valset_nonce,
+        cosmos_valset_nonce,
+        ethereum_event_nonce: get_event_nonce(gravity_contract_address, zero_address(), &web3)
+            .await
+            .expect("Failed to get the Gravity contract event nonce"),
+        last_observed_event_nonce: get_last_observed_eth_nonce(&mut grpc)
+            .await
+            .expect("Failed to get the last observed event nonce"),
+        tokens: get_token_statuses(&mut grpc, &mut grpc_v2, &mut tx_client, cosmos_block).await,
+        unsigned_batches: get_unsigned_batches(&mut grpc).await,
+        pending_ibc_auto_forwards: get_all_pending_ibc_auto_forwards(&mut grpc).await.len(),
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&status).unwrap());
+    } else {
+        print_status(&status);
+    }
+}
+
+fn print_status(status: &BridgeStatus) {
+    if status.bridge_active {
+        info!("Bridge active");
+    } else {
+        warn!("Bridge paused by governance, transfers to Ethereum and batch creation are disabled");
+    }
+
+    info!(
+        "Cosmos block {}, Ethereum block {}, last observed Ethereum block {}",
+        status.cosmos_block, status.ethereum_block, status.last_observed_ethereum_block
+    );
+    let event_lag = status
+        .ethereum_event_nonce
+        .saturating_sub(status.last_observed_event_nonce);
+    if event_lag == 0 {
+        info!(
+            "Event nonce {} on Ethereum, all events observed",
+            status.ethereum_event_nonce
+        );
+    } else {
+        warn!(
+            "Event nonce {} on Ethereum, last observed {}, {event_lag} events waiting for attestation",
+            status.ethereum_event_nonce, status.last_observed_event_nonce
+        );
+    }
+
+    match (status.ethereum_valset_nonce, status.cosmos_valset_nonce) {
+        (Some(eth), Some(cosmos)) if eth >= cosmos => {
+            info!("Validator set {eth} on Ethereum is up to date")
+        }
+        (Some(eth), Some(cosmos)) => warn!(
+            "Validator set {eth} on Ethereum is behind the latest validator set {cosmos} on Cosmos"
+        ),
+        (eth, cosmos) => warn!(
+            "Validator set on Ethereum {}, latest on Cosmos {}",
+            eth.map(|n| n.to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            cosmos
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| "unknown".to_string())
+        ),
+    }
+
+    if status.tokens.is_empty() {
+        info!("No transfers waiting to be batched");
+    }
+    for token in status.tokens.iter() {
+        let oldest = match (
+            token.oldest_unbatched_transfer_id,
+            &token.oldest_unbatched_transfer_created,
+        ) {
+            (Some(id), Some(created)) => format!(
+                "id {id} created at block {} ({} blocks ago, {})",
+                created.block, created.age_blocks, created.time
+            ),
+            (Some(id), None) => format!("id {id} of unknown age"),
+            (None, _) => "none".to_string(),
+        };
+        info!(
+            "Token {}: {} unbatched transfers, oldest is {oldest}, pending batch fees {}",
+            token.token,
+            token.unbatched_transfers,
+            if token.pending_batch_fees.is_empty() {
+                "0"
+            } else {
+                &token.pending_batch_fees
+            }
+        );
+    }
+
+    for batch in status.unsigned_batches.iter() {
+        warn!(
+            "Batch {} for token {} awaiting signatures, {:.2}% of {:.2}% signed, missing {}",
+            batch.nonce,
+            batch.token,
+            batch.signed_power_percent,
+            to_percent(GRAVITY_POWER_TO_PASS),
+            batch.missing_signers.join(", ")
+        );
+    }
+
+    info!(
+        "{} pending IBC auto forwards",
+        status.pending_ibc_auto_forwards
+    );
+}
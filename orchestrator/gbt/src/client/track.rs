@@ -0,0 +1,781 @@
+//! Follows a bridge transfer through every stage until it completes. SendToEth transfers are followed
+//! from the pool into a batch, through batch signing, execution on Ethereum, and the attestation of
+//! that execution. Deposits are followed from inclusion on Ethereum through attestation to minting
+//! on the Gravity Bridge chain and any IBC auto forward
+
+use crate::args::TrackOpts;
+use crate::utils::TIMEOUT;
+use clarity::constants::zero_address;
+use clarity::Address as EthAddress;
+use clarity::Uint256;
+use cosmos_gravity::query::{
+    get_all_pending_ibc_auto_forwards, get_current_valset, get_gravity_params,
+    get_last_observed_eth_nonce, get_pending_send_to_eth_v2, get_transaction_batch_signatures,
+};
+use deep_space::utils::bytes_to_hex_str;
+use deep_space::Contact;
+use ethereum_gravity::utils::get_tx_batch_nonce;
+use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
+use gravity_proto::gravity::v1::{Attestation, QueryAttestationsRequest};
+use gravity_proto::gravity::v2::query_client::QueryClient as GravityQueryClientV2;
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::{
+    GravityEvent, TransactionBatchExecutedEvent, GRAVITY_POWER_TO_PASS, TOTAL_GRAVITY_POWER,
+};
+use orchestrator::eth_quorum::EthQuorumClient;
+use orchestrator::ethereum_event_watcher::get_gravity_logs_adaptive;
+use orchestrator::oracle_resync::BLOCKS_TO_SEARCH;
+use std::cmp::min;
+use std::fmt::{self, Display};
+use std::process::exit;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::time::sleep as delay_for;
+use tonic::transport::Channel;
+use web30::client::Web3;
+
+/// How often the transfer is checked for progress
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Batches may be executed on Ethereum some time before the Gravity Bridge chain observes it, so
+/// the execution is searched for starting this many blocks before the batch was first seen
+const BATCH_SEARCH_MARGIN: u64 = 10_000;
+
+/// A bridge transfer to track
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackTarget {
+    /// The hash of the Cosmos transaction that sent a SendToEth transfer
+    CosmosTx(String),
+    /// The id of a SendToEth transfer
+    SendToEth(u64),
+    /// The hash of the Ethereum transaction that deposited to the Gravity contract
+    EthDeposit(Uint256),
+}
+
+impl FromStr for TrackTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let is_hash = |h: &str| h.len() == 64 && h.chars().all(|c| c.is_ascii_hexdigit());
+        if let Ok(id) = s.parse() {
+            return Ok(TrackTarget::SendToEth(id));
+        }
+        match s.strip_prefix("0x") {
+            Some(hash) if is_hash(hash) => s
+                .parse()
+                .map(TrackTarget::EthDeposit)
+                .map_err(|e| format!("Invalid Ethereum transaction hash {e:?}")),
+            None if is_hash(s) => Ok(TrackTarget::CosmosTx(s.to_uppercase())),
+            _ => Err(
+                "Expected a Cosmos transaction hash, a SendToEth id, or a 0x prefixed Ethereum transaction hash"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+/// The progress of a transfer, printed every time it changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Stage {
+    /// The transfer is waiting in the pool to be included in a batch
+    InPool,
+    InBatch {
+        nonce: u64,
+        token: EthAddress,
+        timeout: u64,
+        signed_power: u64,
+        signers: usize,
+        validators: usize,
+    },
+    BatchTimedOut {
+        nonce: u64,
+        timeout: u64,
+    },
+    /// A later batch for the same token was executed first
+    BatchInvalidated {
+        nonce: u64,
+    },
+    BatchExecuted {
+        nonce: u64,
+        txid: Option<String>,
+        votes: usize,
+        validators: usize,
+    },
+    /// The deposit transaction has not yet been included in a block
+    DepositPending,
+    DepositIncluded {
+        event_nonce: u64,
+        block: Uint256,
+        votes: usize,
+        validators: usize,
+    },
+    IbcForwardPending {
+        receiver: String,
+        channel: String,
+    },
+    Complete(String),
+    /// The transfer can not be followed any further
+    Failed(String),
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let to_percent = |power: u64| power as f64 / TOTAL_GRAVITY_POWER as f64 * 100f64;
+        match self {
+            Stage::InPool => write!(f, "Waiting in the pool to be included in a batch"),
+            Stage::InBatch {
+                nonce,
+                token,
+                timeout,
+                signed_power,
+                signers,
+                validators,
+            } => {
+                write!(
+                    f,
+                    "Included in batch {nonce} for token {token}, signed by {signers} of {validators} validators with {:.2}% of the voting power, ",
+                    to_percent(*signed_power)
+                )?;
+                if *signed_power >= GRAVITY_POWER_TO_PASS {
+                    write!(f, "waiting for a relayer to execute it on Ethereum")?;
+                } else {
+                    write!(
+                        f,
+                        "{:.2}% is needed before it can be executed",
+                        to_percent(GRAVITY_POWER_TO_PASS)
+                    )?;
+                }
+                write!(f, ". The batch times out at Ethereum block {timeout}")
+            }
+            Stage::BatchTimedOut { nonce, timeout } => write!(
+                f,
+                "Batch {nonce} timed out at Ethereum block {timeout}, the transfer will return to the pool"
+            ),
+            Stage::BatchInvalidated { nonce } => write!(
+                f,
+                "A later batch was executed before batch {nonce}, the transfer will return to the pool"
+            ),
+            Stage::BatchExecuted {
+                nonce,
+                txid,
+                votes,
+                validators,
+            } => write!(
+                f,
+                "Batch {nonce} executed on Ethereum{}, {votes} of {validators} validators have attested to the execution",
+                txid.as_ref().map(|t| format!(" in {t}")).unwrap_or_default()
+            ),
+            Stage::DepositPending => write!(
+                f,
+                "The deposit transaction has not yet been included in an Ethereum block"
+            ),
+            Stage::DepositIncluded {
+                event_nonce,
+                block,
+                votes,
+                validators,
+            } => write!(
+                f,
+                "Deposit event {event_nonce} included at Ethereum block {block}, {votes} of {validators} validators have attested to it"
+            ),
+            Stage::IbcForwardPending { receiver, channel } => write!(
+                f,
+                "Deposit credited, waiting for the IBC auto forward to {receiver} over {channel} to execute"
+            ),
+            Stage::Complete(msg) => write!(f, "Complete: {msg}"),
+            Stage::Failed(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+/// The execution of a batch found on Ethereum and the hash of the transaction that executed it
+type BatchExecution = (TransactionBatchExecutedEvent, Option<String>);
+
+/// A batch the tracked transfer was seen in
+#[derive(Debug, Clone)]
+struct SeenBatch {
+    nonce: u64,
+    token: EthAddress,
+    timeout: u64,
+    /// the Ethereum block height when the batch was first seen
+    seen_at: Uint256,
+    /// the last Ethereum block searched for the execution of the batch, so that each poll only
+    /// searches the blocks produced since the previous one
+    searched_to: Option<Uint256>,
+    /// the execution of the batch, once found
+    execution: Option<BatchExecution>,
+}
+
+impl SeenBatch {
+    fn new(nonce: u64, token: EthAddress, timeout: u64, seen_at: Uint256) -> SeenBatch {
+        SeenBatch {
+            nonce,
+            token,
+            timeout,
+            seen_at,
+            searched_to: None,
+            execution: None,
+        }
+    }
+
+    /// The next range of blocks to search for the execution of this batch, at most
+    /// BLOCKS_TO_SEARCH long. None once every block up to `latest_block` has been searched
+    fn next_search_range(&self, latest_block: Uint256) -> Option<(Uint256, Uint256)> {
+        let start = match self.searched_to {
+            Some(b) => b + 1u8.into(),
+            None if self.seen_at > BATCH_SEARCH_MARGIN.into() => {
+                self.seen_at - BATCH_SEARCH_MARGIN.into()
+            }
+            None => 0u8.into(),
+        };
+        if start > latest_block {
+            return None;
+        }
+        Some((start, min(start + BLOCKS_TO_SEARCH.into(), latest_block)))
+    }
+
+    /// Records a search of every block up to `searched_to`, which found the batch `executions`
+    fn record_search(&mut self, executions: Vec<BatchExecution>, searched_to: Uint256) {
+        self.searched_to = Some(searched_to);
+        if let Some(execution) = executions
+            .into_iter()
+            .find(|(e, _)| e.batch_nonce == self.nonce && e.erc20 == self.token)
+        {
+            self.execution = Some(execution);
+        }
+    }
+}
+
+/// How far the batch the tracked transfer is in has progressed on Ethereum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BatchProgress {
+    /// The batch is waiting for signatures or a relayer
+    Pending,
+    TimedOut,
+    /// The batch was removed from the Gravity Bridge chain without being executed
+    Dropped,
+    /// The Gravity contract has reached the batch nonce, by executing this batch or a later one
+    Executed,
+}
+
+/// Determines the progress of `batch` from whether it is `still_pending` on the Gravity Bridge
+/// chain and the last batch nonce the Gravity contract executed for its token
+fn get_batch_progress(
+    batch: &SeenBatch,
+    still_pending: bool,
+    ethereum_nonce: u64,
+    latest_block: Uint256,
+) -> BatchProgress {
+    if ethereum_nonce >= batch.nonce {
+        BatchProgress::Executed
+    } else if !still_pending {
+        BatchProgress::Dropped
+    } else if latest_block > batch.timeout.into() {
+        BatchProgress::TimedOut
+    } else {
+        BatchProgress::Pending
+    }
+}
+
+/// The stage of a transfer in an executed batch, `votes` is the number of attestations to the
+/// execution once it has been found
+fn get_executed_stage(
+    batch: &SeenBatch,
+    still_pending: bool,
+    ethereum_nonce: u64,
+    votes: usize,
+    validators: usize,
+) -> Stage {
+    let txid = batch.execution.as_ref().and_then(|(_, txid)| txid.clone());
+    if batch.execution.is_none() && ethereum_nonce > batch.nonce {
+        Stage::BatchInvalidated { nonce: batch.nonce }
+    } else if !still_pending {
+        Stage::Complete(format!(
+            "Batch {} executed on Ethereum{} and observed by the Gravity Bridge chain",
+            batch.nonce,
+            txid.map(|t| format!(" in {t}")).unwrap_or_default()
+        ))
+    } else {
+        Stage::BatchExecuted {
+            nonce: batch.nonce,
+            txid,
+            votes,
+            validators,
+        }
+    }
+}
+
+struct Tracker {
+    grpc: GravityQueryClient<Channel>,
+    grpc_v2: GravityQueryClientV2<Channel>,
+    web3: Web3,
+    eth_rpc: EthQuorumClient,
+    gravity_contract_address: EthAddress,
+    batch: Option<SeenBatch>,
+}
+
+impl Tracker {
+    async fn get_validator_count(&mut self) -> Result<usize, GravityError> {
+        Ok(get_current_valset(&mut self.grpc).await?.members.len())
+    }
+
+    /// Gets the attestation of the claim of type `claim_type` for `event_nonce`, if validators
+    /// disagree on the claim the attestation with the most votes is returned
+    async fn get_attestation(
+        &mut self,
+        event_nonce: u64,
+        claim_type: &str,
+    ) -> Result<Option<Attestation>, GravityError> {
+        let attestations = self
+            .grpc
+            .get_attestations(QueryAttestationsRequest {
+                limit: 0,
+                order_by: String::new(),
+                claim_type: String::new(),
+                nonce: event_nonce,
+                height: 0,
+                use_v1_key: false,
+            })
+            .await?
+            .into_inner()
+            .attestations;
+        Ok(attestations
+            .into_iter()
+            .filter(|a| {
+                a.claim
+                    .as_ref()
+                    .map(|c| c.type_url.contains(claim_type))
+                    .unwrap_or(false)
+            })
+            .max_by_key(|a| a.votes.len()))
+    }
+
+    async fn get_send_to_eth_stage(&mut self, id: u64) -> Result<Stage, GravityError> {
+        let pending = get_pending_send_to_eth_v2(&mut self.grpc_v2).await?;
+        if pending.unbatched_transfers.iter().any(|t| t.id == id) {
+            self.batch = None;
+            return Ok(Stage::InPool);
+        }
+        let in_batch = pending
+            .transfers_in_batches
+            .into_iter()
+            .find(|t| t.transfer.as_ref().map(|t| t.id) == Some(id))
+            .and_then(|t| t.batch);
+        let still_pending = in_batch.is_some();
+        if let Some(batch) = in_batch {
+            if self.batch.as_ref().map(|b| b.nonce) != Some(batch.batch_nonce) {
+                self.batch = Some(SeenBatch::new(
+                    batch.batch_nonce,
+                    batch.token_contract.parse()?,
+                    batch.batch_timeout,
+                    self.web3.eth_block_number().await?,
+                ));
+            }
+        }
+        let mut batch = match self.batch.clone() {
+            Some(b) => b,
+            None => {
+                return Ok(Stage::Failed(format!(
+                    "Transfer {id} is not pending, it has already been executed on Ethereum, been cancelled, or does not exist"
+                )))
+            }
+        };
+
+        let latest_block = self.web3.eth_block_number().await?;
+        let ethereum_nonce = get_tx_batch_nonce(
+            self.gravity_contract_address,
+            batch.token,
+            zero_address(),
+            &self.web3,
+        )
+        .await?;
+        match get_batch_progress(&batch, still_pending, ethereum_nonce, latest_block) {
+            BatchProgress::Dropped => {
+                return Ok(Stage::Failed(format!(
+                    "Batch {} is no longer pending on the Gravity Bridge chain but was not executed on Ethereum",
+                    batch.nonce
+                )))
+            }
+            BatchProgress::TimedOut => {
+                return Ok(Stage::BatchTimedOut {
+                    nonce: batch.nonce,
+                    timeout: batch.timeout,
+                })
+            }
+            BatchProgress::Pending => {
+                let valset = get_current_valset(&mut self.grpc).await?;
+                let confirms =
+                    get_transaction_batch_signatures(&mut self.grpc, batch.nonce, batch.token)
+                        .await?;
+                let signers: Vec<_> = valset
+                    .members
+                    .iter()
+                    .filter(|m| confirms.iter().any(|c| c.ethereum_signer == m.eth_address))
+                    .collect();
+                return Ok(Stage::InBatch {
+                    nonce: batch.nonce,
+                    token: batch.token,
+                    timeout: batch.timeout,
+                    signed_power: signers.iter().map(|m| m.power).sum(),
+                    signers: signers.len(),
+                    validators: valset.members.len(),
+                });
+            }
+            BatchProgress::Executed => {}
+        }
+
+        self.find_batch_execution(&mut batch, latest_block).await?;
+        self.batch = Some(batch.clone());
+        let votes = match (&batch.execution, still_pending) {
+            (Some((event, _)), true) => self
+                .get_attestation(event.event_nonce, "MsgBatchSendToEthClaim")
+                .await?
+                .map(|a| a.votes.len())
+                .unwrap_or(0),
+            _ => 0,
+        };
+        let validators = if still_pending {
+            self.get_validator_count().await?
+        } else {
+            0
+        };
+        Ok(get_executed_stage(
+            &batch,
+            still_pending,
+            ethereum_nonce,
+            votes,
+            validators,
+        ))
+    }
+
+    /// Searches Ethereum for the execution of `batch`, continuing from the last block searched by
+    /// the previous poll. Ranges the node refuses to search are split by get_gravity_logs_adaptive
+    async fn find_batch_execution(
+        &self,
+        batch: &mut SeenBatch,
+        latest_block: Uint256,
+    ) -> Result<(), GravityError> {
+        while batch.execution.is_none() {
+            let (start, end) = match batch.next_search_range(latest_block) {
+                Some(r) => r,
+                None => break,
+            };
+            let (logs, searched_to) =
+                get_gravity_logs_adaptive(&self.eth_rpc, self.gravity_contract_address, start, end)
+                    .await?;
+            let executions = logs
+                .iter()
+                .filter_map(|log| match GravityEvent::from_log(log) {
+                    Ok(GravityEvent::TransactionBatchExecuted(event)) => {
+                        let txid = log
+                            .transaction_hash
+                            .as_ref()
+                            .map(|h| format!("0x{}", bytes_to_hex_str(h)));
+                        Some((event, txid))
+                    }
+                    _ => None,
+                })
+                .collect();
+            batch.record_search(executions, searched_to);
+        }
+        Ok(())
+    }
+
+    async fn get_deposit_stage(&mut self, txid: Uint256) -> Result<Stage, GravityError> {
+        let receipt = match self.web3.eth_get_transaction_receipt(txid).await? {
+            Some(r) => r,
+            None => return Ok(Stage::DepositPending),
+        };
+        if receipt.status == Some(0u8.into()) {
+            return Ok(Stage::Failed(
+                "The deposit transaction reverted on Ethereum, nothing was deposited".to_string(),
+            ));
+        }
+        let gravity_contract_address = self.gravity_contract_address;
+        let deposit = receipt
+            .logs
+            .iter()
+            .filter(|l| l.address == gravity_contract_address)
+            .find_map(|l| match GravityEvent::from_log(l) {
+                Ok(GravityEvent::SendToCosmos(e)) => Some(e),
+                _ => None,
+            });
+        let deposit = match deposit {
+            Some(d) => d,
+            None => {
+                return Ok(Stage::Failed(format!(
+                    "The transaction did not deposit to the Gravity contract {gravity_contract_address}"
+                )))
+            }
+        };
+
+        if get_last_observed_eth_nonce(&mut self.grpc).await? < deposit.event_nonce {
+            let votes = self
+                .get_attestation(deposit.event_nonce, "MsgSendToCosmosClaim")
+                .await?
+                .map(|a| a.votes.len())
+                .unwrap_or(0);
+            return Ok(Stage::DepositIncluded {
+                event_nonce: deposit.event_nonce,
+                block: deposit.block_height,
+                votes,
+                validators: self.get_validator_count().await?,
+            });
+        }
+
+        let forwards = get_all_pending_ibc_auto_forwards(&mut self.grpc).await;
+        if let Some(forward) = forwards
+            .iter()
+            .find(|f| f.event_nonce == deposit.event_nonce)
+        {
+            return Ok(Stage::IbcForwardPending {
+                receiver: forward.foreign_receiver.clone(),
+                channel: forward.ibc_channel.clone(),
+            });
+        }
+        Ok(Stage::Complete(match deposit.validated_destination {
+            Some(_) => format!(
+                "Deposit of {} of token {} credited to {}",
+                deposit.amount, deposit.erc20, deposit.destination
+            ),
+            None => format!(
+                "Deposit executed, but the destination {:?} is not a valid address so the tokens were sent to the community pool",
+                deposit.destination
+            ),
+        }))
+    }
+}
+
+/// Finds the id of the SendToEth transfer made by the Cosmos transaction `txhash`
+async fn get_send_to_eth_id(contact: &Contact, txhash: String) -> Result<u64, String> {
+    let tx = contact
+        .get_tx_by_hash(txhash.clone())
+        .await
+        .map_err(|e| format!("Could not find Cosmos transaction {txhash} {e:?}"))?;
+    let events = tx.tx_response.map(|r| r.events).unwrap_or_default();
+    for event in events
+        .iter()
+        .filter(|e| e.r#type == "gravity.v1.EventOutgoingTxId")
+    {
+        for attribute in event.attributes.iter() {
+            if String::from_utf8_lossy(attribute.key.as_ref()) != "tx_id" {
+                continue;
+            }
+            // typed event attributes are json encoded
+            let value = String::from_utf8_lossy(attribute.value.as_ref());
+            if let Ok(id) = value.trim_matches('"').parse() {
+                return Ok(id);
+            }
+        }
+    }
+    Err(format!(
+        "Cosmos transaction {txhash} did not send a transfer to Ethereum"
+    ))
+}
+
+pub async fn track(args: TrackOpts, address_prefix: String) {
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let web3 = connections.web3.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let grpc_v2 = connections.grpc_v2.unwrap();
+
+    let gravity_contract_address = if let Some(c) = args.gravity_contract_address {
+        c
+    } else {
+        let params = get_gravity_params(&mut grpc)
+            .await
+            .expect("Failed to get Gravity Bridge module parameters!");
+        match params.bridge_ethereum_address.parse() {
+            Ok(v) => v,
+            Err(_) => {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+        }
+    };
+
+    let target = match args.transfer {
+        TrackTarget::CosmosTx(txhash) => match get_send_to_eth_id(&contact, txhash).await {
+            Ok(id) => {
+                info!("Tracking SendToEth transfer {id}");
+                TrackTarget::SendToEth(id)
+            }
+            Err(e) => {
+                error!("{e}");
+                exit(1);
+            }
+        },
+        t => t,
+    };
+
+    let eth_rpc =
+        EthQuorumClient::new(web3.clone(), &[], 1).expect("A quorum of one is always reachable");
+    let mut tracker = Tracker {
+        grpc,
+        grpc_v2,
+        web3,
+        eth_rpc,
+        gravity_contract_address,
+        batch: None,
+    };
+    let start = Instant::now();
+    let timeout = Duration::from_secs(args.timeout);
+    let mut last_stage: Option<Stage> = None;
+    loop {
+        let stage = match &target {
+            TrackTarget::SendToEth(id) => tracker.get_send_to_eth_stage(*id).await,
+            TrackTarget::EthDeposit(txid) => tracker.get_deposit_stage(*txid).await,
+            TrackTarget::CosmosTx(_) => unreachable!(),
+        };
+        match stage {
+            Ok(stage) => {
+                match stage {
+                    Stage::Complete(_) => {
+                        info!("{stage}");
+                        return;
+                    }
+                    Stage::Failed(_) => {
+                        error!("{stage}");
+                        exit(1);
+                    }
+                    _ if last_stage.as_ref() != Some(&stage) => info!("{stage}"),
+                    _ => {}
+                }
+                last_stage = Some(stage);
+            }
+            Err(e) => warn!("Failed to check the transfer, retrying {e:?}"),
+        }
+        if start.elapsed() > timeout {
+            error!(
+                "The transfer did not complete within {} seconds",
+                args.timeout
+            );
+            exit(1);
+        }
+        delay_for(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_track_target() {
+        assert_eq!("42".parse(), Ok(TrackTarget::SendToEth(42)));
+        let hash = "a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f90";
+        assert_eq!(hash.parse(), Ok(TrackTarget::CosmosTx(hash.to_uppercase())));
+        assert!(matches!(
+            format!("0x{hash}").parse(),
+            Ok(TrackTarget::EthDeposit(_))
+        ));
+        assert!("0x1234".parse::<TrackTarget>().is_err());
+        assert!("not a transfer".parse::<TrackTarget>().is_err());
+    }
+
+    #[test]
+    fn test_batch_search_state() {
+        let token = EthAddress::from_slice(&[7u8; 20]).unwrap();
+        let mut batch = SeenBatch::new(7, token, 20_000, 15_000u64.into());
+        let latest: Uint256 = 16_000u64.into();
+
+        // the first search starts before the batch was seen and is split into bounded ranges
+        let (start, end) = batch.next_search_range(latest).unwrap();
+        assert_eq!(start, 5_000u64.into());
+        assert_eq!(end, start + BLOCKS_TO_SEARCH.into());
+        // a node that refused the full range only searched part of it
+        batch.record_search(Vec::new(), 7_000u64.into());
+        assert_eq!(batch.next_search_range(latest).unwrap().0, 7_001u64.into());
+        batch.record_search(Vec::new(), latest);
+        assert_eq!(batch.next_search_range(latest), None);
+        // later polls only search the new blocks
+        assert_eq!(
+            batch.next_search_range(16_010u64.into()),
+            Some((16_001u64.into(), 16_010u64.into()))
+        );
+
+        // executions of other batches are ignored
+        let execution = |batch_nonce, erc20| TransactionBatchExecutedEvent {
+            batch_nonce,
+            erc20,
+            ..Default::default()
+        };
+        batch.record_search(
+            vec![
+                (execution(8, token), None),
+                (execution(7, zero_address()), None),
+            ],
+            16_010u64.into(),
+        );
+        assert!(batch.execution.is_none());
+        batch.record_search(
+            vec![(execution(7, token), Some("0x01".to_string()))],
+            16_020u64.into(),
+        );
+        assert_eq!(batch.execution.as_ref().unwrap().0.batch_nonce, 7);
+
+        let executed = get_executed_stage(&batch, true, 7, 3, 4);
+        assert_eq!(
+            executed,
+            Stage::BatchExecuted {
+                nonce: 7,
+                txid: Some("0x01".to_string()),
+                votes: 3,
+                validators: 4
+            }
+        );
+        assert!(matches!(
+            get_executed_stage(&batch, false, 7, 0, 0),
+            Stage::Complete(_)
+        ));
+    }
+
+    #[test]
+    fn test_batch_progress() {
+        let token = EthAddress::from_slice(&[7u8; 20]).unwrap();
+        let batch = SeenBatch::new(7, token, 20_000, 15_000u64.into());
+        let before_timeout: Uint256 = 19_000u64.into();
+        let after_timeout: Uint256 = 21_000u64.into();
+
+        assert_eq!(
+            get_batch_progress(&batch, true, 6, before_timeout),
+            BatchProgress::Pending
+        );
+        assert_eq!(
+            get_batch_progress(&batch, true, 6, after_timeout),
+            BatchProgress::TimedOut
+        );
+        assert_eq!(
+            get_batch_progress(&batch, false, 6, before_timeout),
+            BatchProgress::Dropped
+        );
+        // executed batches are reported as such even past their timeout
+        assert_eq!(
+            get_batch_progress(&batch, true, 7, after_timeout),
+            BatchProgress::Executed
+        );
+        assert_eq!(
+            get_batch_progress(&batch, false, 8, before_timeout),
+            BatchProgress::Executed
+        );
+
+        // a later batch executed without this one invalidates it
+        assert_eq!(
+            get_executed_stage(&batch, true, 8, 0, 4),
+            Stage::BatchInvalidated { nonce: 7 }
+        );
+        // the execution may not have been found if it predates the search
+        assert!(matches!(
+            get_executed_stage(&batch, true, 7, 0, 4),
+            Stage::BatchExecuted { txid: None, .. }
+        ));
+    }
+}
@@ -1,12 +1,19 @@
 //! Handles configuration structs + saving and loading for Gravity bridge tools
 
 use crate::args::InitOpts;
+use crate::keystore::EncryptedKey;
 use clarity::PrivateKey as EthPrivateKey;
-use gravity_utils::types::{GravityBridgeToolsConfig, TomlGravityBridgeToolsConfig};
+use gravity_utils::types::{GravityBridgeToolsConfig, RelayerConfig, TomlGravityBridgeToolsConfig};
+use lazy_static::lazy_static;
 use std::{
-    fs::{self, create_dir},
+    convert::{TryFrom, TryInto},
+    env,
+    fs::{self, create_dir, OpenOptions},
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
     path::{Path, PathBuf},
     process::exit,
+    sync::RwLock,
 };
 
 /// The name of the config file, this file is copied
@@ -16,17 +23,61 @@ pub const CONFIG_NAME: &str = "config.toml";
 /// The name of the keys file, this file is not expected
 /// to be hand edited.
 pub const KEYS_NAME: &str = "keys.json";
+/// The name of the oracle checkpoint file, used by the orchestrator
+/// to resume without resyncing the full Ethereum history
+pub const ORACLE_CHECKPOINT_NAME: &str = "oracle_checkpoints.json";
+/// The name of the signing journal, a record of every checkpoint the orchestrator has
+/// signed, used to refuse conflicting signatures. Do not delete this file
+pub const SIGNING_JOURNAL_NAME: &str = "signing_journal.json";
+/// The name of the remote signer's signing journal, kept separately from the orchestrator's
+/// since both may run with the same home directory. Do not delete this file
+pub const REMOTE_SIGNER_JOURNAL_NAME: &str = "remote_signer_journal.json";
+/// The name of the file the relayer persists its pending Ethereum transactions to, used
+/// to replace or cancel transactions left pending across restarts
+pub const PENDING_TRANSACTIONS_NAME: &str = "relayer_pending_transactions.json";
+/// The name of the relayer's profit and loss ledger, a record of every transaction the relayer
+/// has had included, read by `gbt relayer report`
+pub const RELAYER_LEDGER_NAME: &str = "relayer_ledger.json";
+/// The name of the file the relayer persists its gas price samples to, so that altruistic
+/// relaying does not have to collect them again after a restart
+pub const GAS_HISTORY_NAME: &str = "relayer_gas_history.json";
 /// The folder name for the config
 pub const CONFIG_FOLDER: &str = ".gbt";
+/// The environment variable that may be used to provide the keystore passphrase
+pub const KEYSTORE_PASSPHRASE_ENV: &str = "GBT_KEYSTORE_PASSPHRASE";
 
-/// The keys storage struct, including encrypted and un-encrypted local keys
-/// un-encrypted keys provide for orchestrator start and relayer start functions
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+lazy_static! {
+    /// The keystore passphrase, once provided it is retained so that the user is only prompted once
+    static ref KEYSTORE_PASSPHRASE: RwLock<Option<String>> = RwLock::new(None);
+}
+
+/// The decrypted keys, as used by the orchestrator and relayer start functions
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default, Clone)]
 pub struct KeyStorage {
     pub orchestrator_phrase: Option<String>,
     pub ethereum_key: Option<EthPrivateKey>,
 }
 
+/// The on disk format of the keys file. Keys are saved encrypted in the Ethereum v3 keystore format,
+/// plaintext keys written by older versions are still read and are encrypted when next saved
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+struct StoredKeys {
+    orchestrator_phrase: Option<String>,
+    ethereum_key: Option<EthPrivateKey>,
+    encrypted_orchestrator_phrase: Option<EncryptedKey>,
+    encrypted_ethereum_key: Option<EncryptedKey>,
+}
+
+impl StoredKeys {
+    fn has_plaintext(&self) -> bool {
+        self.orchestrator_phrase.is_some() || self.ethereum_key.is_some()
+    }
+
+    fn has_encrypted(&self) -> bool {
+        self.encrypted_orchestrator_phrase.is_some() || self.encrypted_ethereum_key.is_some()
+    }
+}
+
 /// Checks if the user has setup their config environment
 pub fn config_exists(home_dir: &Path) -> bool {
     let config_file = home_dir.join(CONFIG_FOLDER).with_file_name(CONFIG_NAME);
@@ -48,9 +99,9 @@ pub fn init_config(_init_ops: InitOpts, home_dir: PathBuf) {
 
         fs::write(home_dir.join(CONFIG_NAME), get_default_config())
             .expect("Unable to write config file");
-        fs::write(
-            home_dir.join(KEYS_NAME),
-            toml::to_string(&KeyStorage::default()).unwrap(),
+        write_keys_file(
+            &home_dir.join(KEYS_NAME),
+            &toml::to_string(&KeyStorage::default()).unwrap(),
         )
         .expect("Unable to write config file");
     }
@@ -86,7 +137,13 @@ pub fn load_config(home_dir: &Path) -> GravityBridgeToolsConfig {
         fs::read_to_string(config_file).expect("Could not find config file! Run `gbt init`");
     let val: Result<TomlGravityBridgeToolsConfig, _> = toml::from_str(&config);
     match val {
-        Ok(v) => v.into(),
+        Ok(v) => match GravityBridgeToolsConfig::try_from(v) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Invalid relayer config! {e}");
+                exit(1);
+            }
+        },
         Err(e) => {
             error!("Invalid config! {e:?}");
             exit(1);
@@ -94,8 +151,84 @@ pub fn load_config(home_dir: &Path) -> GravityBridgeToolsConfig {
     }
 }
 
-/// Load the keys file, this operates at runtime
-pub fn load_keys(home_dir: &Path) -> KeyStorage {
+/// Parses and validates the relayer section of a config file, unlike load_config
+/// this never exits so that it can be used to reload the config of a running relayer
+pub fn parse_relayer_config(config: &str) -> Result<RelayerConfig, String> {
+    let val: TomlGravityBridgeToolsConfig =
+        toml::from_str(config).map_err(|e| format!("Invalid config {e}"))?;
+    RelayerConfig::try_from(val.relayer)
+}
+
+/// Reads the keystore passphrase from the given file, it will be used instead of prompting the user
+pub fn set_keystore_passphrase_file(passphrase_file: &Path) {
+    match fs::read_to_string(passphrase_file) {
+        Ok(v) => {
+            let passphrase = v.trim_end_matches(&['\r', '\n'][..]).to_string();
+            if passphrase.is_empty() {
+                error!(
+                    "Keystore passphrase file {} is empty, the keystore passphrase can not be empty",
+                    passphrase_file.to_str().unwrap()
+                );
+                exit(1);
+            }
+            *KEYSTORE_PASSPHRASE.write().unwrap() = Some(passphrase)
+        }
+        Err(e) => {
+            error!(
+                "Could not read keystore passphrase file {} {e:?}",
+                passphrase_file.to_str().unwrap()
+            );
+            exit(1);
+        }
+    }
+}
+
+/// Returns the keystore passphrase if it can be obtained without prompting the user
+fn get_provided_passphrase() -> Option<String> {
+    if let Some(v) = KEYSTORE_PASSPHRASE.read().unwrap().clone() {
+        return Some(v);
+    }
+    if let Ok(v) = env::var(KEYSTORE_PASSPHRASE_ENV) {
+        if v.is_empty() {
+            error!("{KEYSTORE_PASSPHRASE_ENV} is set but empty, the keystore passphrase can not be empty");
+            exit(1);
+        }
+        *KEYSTORE_PASSPHRASE.write().unwrap() = Some(v.clone());
+        return Some(v);
+    }
+    None
+}
+
+/// Gets the keystore passphrase, from the passphrase file, the environment, or by prompting the user.
+/// When `new` is set the user is asked to confirm the passphrase since it is being used to encrypt keys
+fn get_keystore_passphrase(new: bool) -> String {
+    if let Some(v) = get_provided_passphrase() {
+        return v;
+    }
+    let prompt_failed = |e: std::io::Error| -> String {
+        error!("Could not read the keystore passphrase {e:?}");
+        error!("When running non-interactively provide the passphrase with --keystore-passphrase-file or the {KEYSTORE_PASSPHRASE_ENV} environment variable");
+        exit(1);
+    };
+    let passphrase =
+        rpassword::prompt_password("Enter keystore passphrase: ").unwrap_or_else(prompt_failed);
+    if passphrase.is_empty() {
+        error!("The keystore passphrase can not be empty");
+        exit(1);
+    }
+    if new {
+        let confirmation = rpassword::prompt_password("Confirm keystore passphrase: ")
+            .unwrap_or_else(prompt_failed);
+        if passphrase != confirmation {
+            error!("Passphrases do not match!");
+            exit(1);
+        }
+    }
+    *KEYSTORE_PASSPHRASE.write().unwrap() = Some(passphrase.clone());
+    passphrase
+}
+
+fn read_stored_keys(home_dir: &Path) -> StoredKeys {
     let keys_file = home_dir.join(CONFIG_FOLDER).with_file_name(KEYS_NAME);
     if !keys_file.exists() {
         error!(
@@ -115,7 +248,54 @@ pub fn load_keys(home_dir: &Path) -> KeyStorage {
     }
 }
 
-/// Saves the keys file, overwriting the existing one
+/// Load the keys file, decrypting the keys. The user is prompted for the passphrase
+/// if it was not otherwise provided. Plaintext keys from older versions are migrated
+/// to the encrypted format if a passphrase was provided, otherwise a warning is printed
+pub fn load_keys(home_dir: &Path) -> KeyStorage {
+    let stored = read_stored_keys(home_dir);
+    let mut keys = KeyStorage {
+        orchestrator_phrase: stored.orchestrator_phrase.clone(),
+        ethereum_key: stored.ethereum_key,
+    };
+
+    if stored.has_encrypted() {
+        let passphrase = get_keystore_passphrase(false);
+        let decrypt = |key: &EncryptedKey| match key.decrypt(&passphrase) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Failed to decrypt keys! {e}");
+                exit(1);
+            }
+        };
+        if let Some(phrase) = &stored.encrypted_orchestrator_phrase {
+            keys.orchestrator_phrase = Some(
+                String::from_utf8(decrypt(phrase))
+                    .expect("Failed to decode key in keyfile. Did you edit it manually?"),
+            );
+        }
+        if let Some(key) = &stored.encrypted_ethereum_key {
+            let bytes: [u8; 32] = decrypt(key)
+                .try_into()
+                .expect("Failed to decode key in keyfile. Did you edit it manually?");
+            keys.ethereum_key = Some(
+                EthPrivateKey::from_bytes(bytes)
+                    .expect("Failed to decode key in keyfile. Did you edit it manually?"),
+            );
+        }
+    }
+
+    if stored.has_plaintext() {
+        if get_provided_passphrase().is_some() {
+            info!("Migrating plaintext keys to the encrypted keystore format");
+            save_keys(home_dir, keys.clone());
+        } else {
+            warn!("Your keys are stored unencrypted! Run `gbt keys encrypt` to encrypt them");
+        }
+    }
+    keys
+}
+
+/// Saves the keys file encrypted with the keystore passphrase, overwriting the existing one
 pub fn save_keys(home_dir: &Path, updated_keys: KeyStorage) {
     let config_file = home_dir.join(CONFIG_FOLDER).with_file_name(KEYS_NAME);
     if !config_file.exists() {
@@ -125,13 +305,43 @@ pub fn save_keys(home_dir: &Path, updated_keys: KeyStorage) {
         );
     }
 
-    fs::write(
-        home_dir.join(KEYS_NAME),
-        toml::to_string(&updated_keys).unwrap(),
+    let passphrase = get_keystore_passphrase(true);
+    let stored = StoredKeys {
+        orchestrator_phrase: None,
+        ethereum_key: None,
+        encrypted_orchestrator_phrase: updated_keys
+            .orchestrator_phrase
+            .map(|v| EncryptedKey::encrypt(v.as_bytes(), &passphrase)),
+        encrypted_ethereum_key: updated_keys
+            .ethereum_key
+            .map(|v| EncryptedKey::encrypt(&v.to_bytes(), &passphrase)),
+    };
+
+    write_keys_file(
+        &home_dir.join(KEYS_NAME),
+        &toml::to_string(&stored).unwrap(),
     )
     .expect("Unable to write config file");
 }
 
+/// Writes the keys file readable only by the current user. The contents are written to a new temporary
+/// file which is then renamed over the keys file, so it is never readable by others or left half written
+fn write_keys_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    // a leftover temporary file would keep its old permissions
+    if tmp_path.exists() {
+        fs::remove_file(&tmp_path)?;
+    }
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&tmp_path)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(tmp_path, path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,7 +352,27 @@ mod tests {
     fn test_default_config() {
         // make sure the default config default-config.toml is the same as the default config struct
         let res: TomlGravityBridgeToolsConfig = toml::from_str(&get_default_config()).unwrap();
-        let res: GravityBridgeToolsConfig = res.into();
+        let res = GravityBridgeToolsConfig::try_from(res).unwrap();
         assert_eq!(res, GravityBridgeToolsConfig::default());
     }
+
+    #[test]
+    fn test_parse_relayer_config() {
+        let res = parse_relayer_config(&get_default_config()).unwrap();
+        assert_eq!(res, RelayerConfig::default());
+
+        let bad_mode = "[relayer]\nbatch_relaying_mode = { mode = \"Sometimes\" }\n";
+        assert!(parse_relayer_config(bad_mode).is_err());
+        let missing_margin = "[relayer]\nbatch_relaying_mode = { mode = \"ProfitableOnly\" }\n";
+        assert!(parse_relayer_config(missing_margin).is_err());
+        let bad_speed = "[relayer]\ngas_tracker_loop_speed = 120\n";
+        assert!(parse_relayer_config(bad_speed).is_err());
+        let bad_bump = "[relayer]\ntx_bump_percentage = 11.0\n";
+        assert!(parse_relayer_config(bad_bump)
+            .unwrap_err()
+            .contains("between 0.1 and 10.0"));
+        let bad_source = "[relayer]\nprice_source = { mode = \"Median\", sources = [{ mode = \"HttpFeed\" }] }\n";
+        assert!(parse_relayer_config(bad_source).is_err());
+        assert!(parse_relayer_config("[relayer\n").is_err());
+    }
 }
@@ -36,6 +36,17 @@ pub fn show_keys(home_dir: &Path, prefix: &str) {
     }
 }
 
+/// Encrypts the keys file, migrating plaintext keys stored by older versions
+pub fn encrypt_keys(home_dir: &Path) {
+    if !config_exists(home_dir) {
+        error!("Please run `gbt init` before running this command!");
+        exit(1);
+    }
+    let keys = load_keys(home_dir);
+    save_keys(home_dir, keys);
+    info!("Successfully encrypted keys")
+}
+
 pub fn set_eth_key(home_dir: &Path, opts: SetEthereumKeyOpts) {
     if !config_exists(home_dir) {
         error!("Please run `gbt init` before running this command!");
@@ -0,0 +1,245 @@
+//! Encrypted key storage using the Ethereum v3 keystore format (scrypt key derivation and
+//! aes-128-ctr encryption), the same format produced by geth and most Ethereum wallets.
+//! Both the orchestrator mnemonic and the Ethereum private key are stored this way in keys.json
+
+use aes::Aes128;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use rand::{thread_rng, Rng};
+use scrypt::{scrypt, Params};
+use sha3::{Digest, Keccak256};
+use std::fmt;
+use subtle::ConstantTimeEq;
+
+/// Standard scrypt parameters used by geth, log2(n) = 18
+pub const SCRYPT_LOG_N: u8 = 18;
+pub const SCRYPT_R: u32 = 8;
+pub const SCRYPT_P: u32 = 1;
+const DKLEN: usize = 32;
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+#[derive(Debug)]
+pub enum KeystoreError {
+    /// The passphrase is wrong or the keystore has been modified
+    InvalidMac,
+    UnsupportedFormat(String),
+    InvalidHex(String),
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeystoreError::InvalidMac => {
+                write!(
+                    f,
+                    "Incorrect keystore passphrase, or the keystore is corrupt"
+                )
+            }
+            KeystoreError::UnsupportedFormat(v) => write!(f, "Unsupported keystore {v}"),
+            KeystoreError::InvalidHex(v) => write!(f, "Invalid hex in keystore {v}"),
+        }
+    }
+}
+
+impl std::error::Error for KeystoreError {}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScryptParams {
+    pub dklen: usize,
+    pub n: u64,
+    pub r: u32,
+    pub p: u32,
+    pub salt: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeystoreCrypto {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    pub kdf: String,
+    pub kdfparams: ScryptParams,
+    pub mac: String,
+}
+
+/// A secret encrypted in the Ethereum v3 keystore format
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedKey {
+    pub version: u8,
+    pub id: String,
+    pub crypto: KeystoreCrypto,
+}
+
+impl EncryptedKey {
+    /// Encrypts the secret with the given passphrase using the standard scrypt parameters
+    pub fn encrypt(secret: &[u8], passphrase: &str) -> EncryptedKey {
+        EncryptedKey::encrypt_with_params(secret, passphrase, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)
+    }
+
+    pub fn encrypt_with_params(
+        secret: &[u8],
+        passphrase: &str,
+        log_n: u8,
+        r: u32,
+        p: u32,
+    ) -> EncryptedKey {
+        let mut rng = thread_rng();
+        let salt: [u8; 32] = rng.gen();
+        let iv: [u8; 16] = rng.gen();
+        let id: [u8; 16] = rng.gen();
+
+        let derived_key =
+            derive_key(passphrase, &salt, log_n, r, p).expect("Invalid scrypt parameters");
+        let mut ciphertext = secret.to_vec();
+        let mut cipher = Aes128Ctr::new(derived_key[..16].into(), &iv.into());
+        cipher.apply_keystream(&mut ciphertext);
+        let mac = compute_mac(&derived_key, &ciphertext);
+
+        EncryptedKey {
+            version: 3,
+            id: format_uuid(id),
+            crypto: KeystoreCrypto {
+                cipher: "aes-128-ctr".to_string(),
+                cipherparams: CipherParams {
+                    iv: hex::encode(iv),
+                },
+                ciphertext: hex::encode(ciphertext),
+                kdf: "scrypt".to_string(),
+                kdfparams: ScryptParams {
+                    dklen: DKLEN,
+                    n: 1 << log_n,
+                    r,
+                    p,
+                    salt: hex::encode(salt),
+                },
+                mac: hex::encode(mac),
+            },
+        }
+    }
+
+    /// Decrypts the secret, returning KeystoreError::InvalidMac if the passphrase is incorrect
+    pub fn decrypt(&self, passphrase: &str) -> Result<Vec<u8>, KeystoreError> {
+        let crypto = &self.crypto;
+        if self.version != 3 {
+            return Err(KeystoreError::UnsupportedFormat(format!(
+                "version {}",
+                self.version
+            )));
+        }
+        if crypto.cipher != "aes-128-ctr" || crypto.kdf != "scrypt" {
+            return Err(KeystoreError::UnsupportedFormat(format!(
+                "cipher {} kdf {}",
+                crypto.cipher, crypto.kdf
+            )));
+        }
+        let params = &crypto.kdfparams;
+        if params.dklen != DKLEN || !params.n.is_power_of_two() {
+            return Err(KeystoreError::UnsupportedFormat(format!(
+                "scrypt dklen {} n {}",
+                params.dklen, params.n
+            )));
+        }
+        let log_n = params.n.trailing_zeros() as u8;
+
+        let salt = decode_hex(&params.salt)?;
+        let iv = decode_hex(&crypto.cipherparams.iv)?;
+        let mut plaintext = decode_hex(&crypto.ciphertext)?;
+        let mac = decode_hex(&crypto.mac)?;
+        if iv.len() != 16 {
+            return Err(KeystoreError::UnsupportedFormat(format!(
+                "iv length {}",
+                iv.len()
+            )));
+        }
+
+        let derived_key = derive_key(passphrase, &salt, log_n, params.r, params.p)?;
+        // compared in constant time so a mismatch does not reveal how much of the mac was correct
+        if !bool::from(compute_mac(&derived_key, &plaintext).ct_eq(&mac)) {
+            return Err(KeystoreError::InvalidMac);
+        }
+        let mut cipher = Aes128Ctr::new(derived_key[..16].into(), iv.as_slice().into());
+        cipher.apply_keystream(&mut plaintext);
+        Ok(plaintext)
+    }
+}
+
+/// Derives the encryption key, the parameters may come from a keystore file so invalid ones are an error
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    log_n: u8,
+    r: u32,
+    p: u32,
+) -> Result<[u8; DKLEN], KeystoreError> {
+    let params = Params::new(log_n, r, p, DKLEN).map_err(|e| {
+        KeystoreError::UnsupportedFormat(format!("scrypt n 2^{log_n} r {r} p {p} {e}"))
+    })?;
+    let mut derived_key = [0u8; DKLEN];
+    scrypt(passphrase.as_bytes(), salt, &params, &mut derived_key)
+        .map_err(|e| KeystoreError::UnsupportedFormat(format!("scrypt output {e}")))?;
+    Ok(derived_key)
+}
+
+/// The v3 keystore mac, keccak256 of the second half of the derived key and the ciphertext
+fn compute_mac(derived_key: &[u8; DKLEN], ciphertext: &[u8]) -> Vec<u8> {
+    let mut hasher = Keccak256::new();
+    hasher.update(&derived_key[16..32]);
+    hasher.update(ciphertext);
+    hasher.finalize().to_vec()
+}
+
+fn decode_hex(input: &str) -> Result<Vec<u8>, KeystoreError> {
+    hex::decode(input.trim_start_matches("0x"))
+        .map_err(|e| KeystoreError::InvalidHex(e.to_string()))
+}
+
+/// Formats random bytes as a version 4 uuid
+fn format_uuid(mut bytes: [u8; 16]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let h = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &h[0..8],
+        &h[8..12],
+        &h[12..16],
+        &h[16..20],
+        &h[20..32]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let secret = b"some orchestrator mnemonic phrase";
+        // low scrypt cost so the test runs quickly
+        let encrypted = EncryptedKey::encrypt_with_params(secret, "hunter2", 4, 8, 1);
+        assert_eq!(encrypted.crypto.kdfparams.n, 16);
+        assert_eq!(encrypted.decrypt("hunter2").unwrap(), secret.to_vec());
+        assert!(matches!(
+            encrypted.decrypt("wrong"),
+            Err(KeystoreError::InvalidMac)
+        ));
+
+        // the stored format survives a round trip through toml
+        let serialized = toml::to_string(&encrypted).unwrap();
+        let deserialized: EncryptedKey = toml::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, encrypted);
+
+        // scrypt parameters read from the file are not trusted
+        let mut invalid = encrypted;
+        invalid.crypto.kdfparams.r = 0;
+        assert!(matches!(
+            invalid.decrypt("hunter2"),
+            Err(KeystoreError::UnsupportedFormat(_))
+        ));
+    }
+}
@@ -3,21 +3,32 @@ extern crate log;
 #[macro_use]
 extern crate serde_derive;
 
-use crate::args::{ClientSubcommand, KeysSubcommand, SubCommand};
+use crate::args::{ClientSubcommand, KeysSubcommand, RelayerSubcommand, SubCommand};
 use crate::config::init_config;
 use crate::gov::proposals::submit_delete_cosmos_bridgeable_tokens;
 use crate::gov::proposals::submit_set_cosmos_bridgeable_tokens;
-use crate::keys::{recover_funds, show_keys};
-use crate::{jsonrpc_server::jsonrpc_server, orchestrator::orchestrator, relayer::relayer};
-use args::{GovQuerySubcommand, GovSubcommand, GovSubmitSubcommand, Opts};
+use crate::keys::{encrypt_keys, recover_funds, show_keys};
+use crate::{
+    jsonrpc_server::jsonrpc_server, orchestrator::orchestrator, relayer::relayer,
+    relayer::relayer_gas_history, relayer::relayer_report, remote_signer::remote_signer,
+};
+use args::{AuctionSubcommand, GovQuerySubcommand, GovSubcommand, GovSubmitSubcommand, Opts};
+use auction::bid::bid;
+use auction::queries::{auction_pool, bidder_auctions, list_auctions};
 use clap::Parser;
+use client::cancel_send_to_eth::cancel_send_to_eth_cmd;
 use client::cosmos_to_eth::cosmos_to_eth_cmd;
 use client::deploy_erc20_representation::deploy_erc20_representation;
+use client::erc721_holdings::erc721_holdings;
+use client::erc721_to_cosmos::erc721_to_cosmos;
 use client::eth_to_cosmos::eth_to_cosmos;
 use client::ibc_auto_forward::ibc_auto_forward;
+use client::pending_sends::pending_sends;
 use client::relay_valset::relay_valset;
 use client::request_all_batches::request_all_batches;
 use client::spot_relay::spot_relay;
+use client::status::status;
+use client::track::track;
 use config::{get_home_dir, load_config};
 use env_logger::Env;
 use gov::proposals::{submit_airdrop, submit_emergency_bridge_halt, submit_oracle_unhalt};
@@ -29,13 +40,16 @@ use rustls::crypto::aws_lc_rs;
 use rustls::crypto::CryptoProvider;
 
 mod args;
+mod auction;
 mod client;
 mod config;
 mod gov;
 mod jsonrpc_server;
 mod keys;
+mod keystore;
 mod orchestrator;
 mod relayer;
+mod remote_signer;
 mod utils;
 
 #[actix_rt::main]
@@ -83,6 +97,20 @@ async fn main() {
             ClientSubcommand::IbcAutoForward(ibc_auto_forward_opts) => {
                 ibc_auto_forward(ibc_auto_forward_opts, address_prefix).await
             }
+            ClientSubcommand::PendingSends(pending_sends_opts) => {
+                pending_sends(pending_sends_opts, address_prefix).await
+            }
+            ClientSubcommand::CancelSendToEth(cancel_send_to_eth_opts) => {
+                cancel_send_to_eth_cmd(cancel_send_to_eth_opts, address_prefix).await
+            }
+            ClientSubcommand::Track(track_opts) => track(track_opts, address_prefix).await,
+            ClientSubcommand::Status(status_opts) => status(status_opts, address_prefix).await,
+            ClientSubcommand::Erc721ToCosmos(erc721_to_cosmos_opts) => {
+                erc721_to_cosmos(erc721_to_cosmos_opts, address_prefix).await
+            }
+            ClientSubcommand::Erc721Holdings(erc721_holdings_opts) => {
+                erc721_holdings(erc721_holdings_opts, address_prefix).await
+            }
         },
         SubCommand::Keys(key_opts) => match key_opts.subcmd {
             KeysSubcommand::RegisterOrchestratorAddress(set_orchestrator_address_opts) => {
@@ -94,6 +122,7 @@ async fn main() {
                 .await
             }
             KeysSubcommand::Show => show_keys(&home_dir, &address_prefix),
+            KeysSubcommand::Encrypt => encrypt_keys(&home_dir),
             KeysSubcommand::SetEthereumKey(set_eth_key_opts) => {
                 set_eth_key(&home_dir, set_eth_key_opts)
             }
@@ -107,8 +136,24 @@ async fn main() {
         SubCommand::Orchestrator(orchestrator_opts) => {
             orchestrator(orchestrator_opts, address_prefix, &home_dir, config).await
         }
-        SubCommand::Relayer(relayer_opts) => {
-            relayer(relayer_opts, address_prefix, &home_dir, config.relayer).await
+        SubCommand::Relayer(mut relayer_opts) => match relayer_opts.subcmd.take() {
+            Some(RelayerSubcommand::Report(report_opts)) => relayer_report(report_opts, &home_dir),
+            Some(RelayerSubcommand::GasHistory(gas_history_opts)) => {
+                relayer_gas_history(gas_history_opts, &home_dir, &config.relayer)
+            }
+            None => {
+                relayer(
+                    relayer_opts,
+                    address_prefix,
+                    &home_dir,
+                    config.relayer,
+                    config.metrics,
+                )
+                .await
+            }
+        },
+        SubCommand::RemoteSigner(remote_signer_opts) => {
+            remote_signer(remote_signer_opts, &home_dir).await
         }
         SubCommand::JsonrpcServer(server_opts) => jsonrpc_server(server_opts).await,
         SubCommand::Init(init_opts) => init_config(init_opts, home_dir),
@@ -132,5 +177,11 @@ async fn main() {
                 GovQuerySubcommand::Airdrop(opts) => query_airdrops(opts, address_prefix).await,
             },
         },
+        SubCommand::Auction(auction_opts) => match auction_opts.subcmd {
+            AuctionSubcommand::List(opts) => list_auctions(opts, address_prefix).await,
+            AuctionSubcommand::Pool(opts) => auction_pool(opts, address_prefix).await,
+            AuctionSubcommand::Bidder(opts) => bidder_auctions(opts, address_prefix).await,
+            AuctionSubcommand::Bid(opts) => bid(opts, address_prefix).await,
+        },
     }
 }
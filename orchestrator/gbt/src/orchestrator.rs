@@ -1,19 +1,25 @@
 use crate::args::OrchestratorOpts;
 use crate::config::config_exists;
 use crate::config::load_keys;
+use crate::config::set_keystore_passphrase_file;
+use crate::config::{ORACLE_CHECKPOINT_NAME, SIGNING_JOURNAL_NAME};
+use crate::relayer::load_relayer_state;
 use crate::utils::print_relaying_explanation;
 use clarity::constants::zero_address;
 use cosmos_gravity::query::get_gravity_params;
 use deep_space::{CosmosPrivateKey, PrivateKey};
+use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::connection_prep::{
     check_delegate_addresses, check_for_eth, wait_for_cosmos_node_ready,
 };
 use gravity_utils::connection_prep::{check_for_fee, create_rpc_connections};
+use gravity_utils::eth_signer::{EthSigner, RemoteEthSigner};
 use gravity_utils::types::BatchRequestMode;
 use gravity_utils::types::GravityBridgeToolsConfig;
 use metrics_exporter::metrics_server;
 use orchestrator::main_loop::orchestrator_main_loop;
 use orchestrator::main_loop::{ETH_ORACLE_LOOP_SPEED, ETH_SIGNER_LOOP_SPEED};
+use orchestrator::signing_journal::SigningJournal;
 use std::cmp::min;
 use std::path::Path;
 use std::process::exit;
@@ -30,6 +36,9 @@ pub async fn orchestrator(
     let ethereum_rpc = args.ethereum_rpc;
     let ethereum_key = args.ethereum_key;
     let cosmos_key = args.cosmos_phrase;
+    if let Some(passphrase_file) = &args.keystore_passphrase_file {
+        set_keystore_passphrase_file(passphrase_file);
+    }
 
     let cosmos_key = if let Some(k) = cosmos_key {
         k
@@ -88,11 +97,44 @@ pub async fn orchestrator(
     let mut grpc = connections.grpc.clone().unwrap();
     let contact = connections.contact.clone().unwrap();
     let web3 = connections.web3.clone().unwrap();
+    let cosmos_pool = connections
+        .cosmos_pool
+        .clone()
+        .unwrap()
+        .with_fallbacks(&args.cosmos_grpc_fallback, &contact.get_prefix(), timeout)
+        .await;
+    let cosmos_pool = match cosmos_pool {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("{e}");
+            exit(1);
+        }
+    };
+
+    // confirms are signed by the remote signer if one is configured, otherwise by the Ethereum key
+    let eth_signer = if let Some(socket) = args.remote_signer {
+        match RemoteEthSigner::connect(socket.clone(), timeout).await {
+            Ok(signer) => EthSigner::Remote(signer),
+            Err(e) => {
+                error!(
+                    "Could not connect to the remote signer at {} {e}",
+                    socket.display()
+                );
+                exit(1);
+            }
+        }
+    } else {
+        EthSigner::Local(ethereum_key)
+    };
 
     let public_eth_key = ethereum_key.to_address();
+    let public_delegate_key = eth_signer.get_address();
     let public_cosmos_key = cosmos_key.to_address(&contact.get_prefix()).unwrap();
     info!("Starting Gravity Validator companion binary Relayer + Oracle + Eth Signer");
     info!("Ethereum Address: {public_eth_key} Cosmos Address {public_cosmos_key}");
+    if public_delegate_key != public_eth_key {
+        info!("Confirms will be signed by the remote signer with Ethereum Address {public_delegate_key}");
+    }
 
     // check if the cosmos node is syncing, if so wait for it
     // we can't move any steps above this because they may fail on an incorrect
@@ -102,7 +144,7 @@ pub async fn orchestrator(
     // check if the delegate addresses are correctly configured
     check_delegate_addresses(
         &mut grpc,
-        public_eth_key,
+        public_delegate_key,
         public_cosmos_key,
         &contact.get_prefix(),
     )
@@ -146,6 +188,7 @@ pub async fn orchestrator(
         } else {
             print_relaying_explanation(&config.relayer, false)
         }
+        load_relayer_state(home_dir, &config.relayer);
     }
 
     // Start monitiring if enabled on config.toml
@@ -153,16 +196,54 @@ pub async fn orchestrator(
         metrics_server(&config.metrics);
     };
 
-    orchestrator_main_loop(
+    // persist oracle progress only if the user has a home directory to store it in
+    let oracle_checkpoint_path = if home_dir.exists() {
+        Some(home_dir.join(ORACLE_CHECKPOINT_NAME))
+    } else {
+        None
+    };
+
+    let signing_journal_path = if home_dir.exists() {
+        Some(home_dir.join(SIGNING_JOURNAL_NAME))
+    } else {
+        warn!("No home directory, the signing journal will not persist across restarts");
+        None
+    };
+    let signing_journal = match SigningJournal::load(signing_journal_path) {
+        Ok(j) => j,
+        Err(e) => {
+            error!("Failed to load the signing journal, refusing to start {e}");
+            exit(1);
+        }
+    };
+    let signer_cross_check = match args.signer_cross_check_grpc {
+        Some(url) => match GravityQueryClient::connect(url.clone()).await {
+            Ok(client) => Some(client),
+            Err(e) => {
+                error!("Failed to connect to the signer cross check Cosmos gRPC {url} {e:?}");
+                exit(1);
+            }
+        },
+        None => None,
+    };
+
+    if let Err(e) = orchestrator_main_loop(
         cosmos_key,
         ethereum_key,
+        eth_signer,
         connections.web3.unwrap(),
-        connections.contact.unwrap(),
-        connections.grpc.unwrap(),
+        cosmos_pool,
         contract_address,
         params.gravity_id,
         fee,
         config,
+        oracle_checkpoint_path,
+        signing_journal,
+        signer_cross_check,
     )
-    .await;
+    .await
+    {
+        error!("Orchestrator halted with {e}");
+        exit(1);
+    }
 }
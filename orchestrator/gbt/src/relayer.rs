@@ -1,31 +1,53 @@
-use crate::args::RelayerOpts;
+use crate::args::{RelayerGasHistoryOpts, RelayerOpts, RelayerReportOpts};
 use crate::config::config_exists;
 use crate::config::load_keys;
+use crate::config::parse_relayer_config;
+use crate::config::set_keystore_passphrase_file;
+use crate::config::{
+    CONFIG_FOLDER, CONFIG_NAME, GAS_HISTORY_NAME, PENDING_TRANSACTIONS_NAME, RELAYER_LEDGER_NAME,
+};
 use crate::utils::print_relaying_explanation;
 use clarity::constants::zero_address;
 use cosmos_gravity::query::get_gravity_params;
 use deep_space::{CosmosPrivateKey, PrivateKey};
+use futures::future::{join, select, Either};
 use gravity_utils::connection_prep::check_for_fee;
 use gravity_utils::connection_prep::{
     check_for_eth, create_rpc_connections, wait_for_cosmos_node_ready,
 };
+use gravity_utils::num_conversion::{print_eth, print_gwei};
 use gravity_utils::types::BatchRequestMode;
-use gravity_utils::types::RelayerConfig;
+use gravity_utils::types::{MetricsConfig, RelayerConfig, SharedRelayerConfig};
+use metrics_exporter::metrics_server;
+use relayer::altruistic::{get_gas_history_window, load_gas_history, read_gas_history};
+use relayer::ledger::{load_ledger, set_ledger_path, summarize, LedgerSummary, Profit};
 use relayer::main_loop::all_relayer_loops;
 use relayer::main_loop::TIMEOUT;
-use std::path::Path;
+use relayer::tx_manager::load_pending_transactions;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::time::sleep as delay_for;
+
+/// How often the config file is checked for modifications
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(10);
 
 pub async fn relayer(
     args: RelayerOpts,
     address_prefix: String,
     home_dir: &Path,
     config: RelayerConfig,
+    metrics_config: MetricsConfig,
 ) {
     let cosmos_grpc = args.cosmos_grpc;
     let ethereum_rpc = args.ethereum_rpc;
     let ethereum_key = args.ethereum_key;
     let cosmos_key = args.cosmos_phrase;
+    if let Some(passphrase_file) = &args.keystore_passphrase_file {
+        set_keystore_passphrase_file(passphrase_file);
+    }
     let connections = create_rpc_connections(
         address_prefix,
         Some(cosmos_grpc),
@@ -70,6 +92,18 @@ pub async fn relayer(
     let contact = connections.contact.clone().unwrap();
     let web3 = connections.web3.unwrap();
     let mut grpc = connections.grpc.unwrap();
+    let cosmos_pool = connections
+        .cosmos_pool
+        .unwrap()
+        .with_fallbacks(&args.cosmos_grpc_fallback, &contact.get_prefix(), TIMEOUT)
+        .await;
+    let cosmos_pool = match cosmos_pool {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("{e}");
+            exit(1);
+        }
+    };
 
     // check if the cosmos node is syncing, if so wait for it
     // we can't move any steps above this because they may fail on an incorrect
@@ -118,16 +152,251 @@ pub async fn relayer(
         print_relaying_explanation(&config, false)
     }
 
-    all_relayer_loops(
+    load_relayer_state(home_dir, &config);
+
+    // Start monitiring if enabled on config.toml
+    if metrics_config.metrics_enabled {
+        metrics_server(&metrics_config);
+    };
+
+    let batch_requests = args.fees.is_some() && cosmos_key.is_some();
+    let shared_config = SharedRelayerConfig::new(config);
+    let relayer_loops = all_relayer_loops(
         cosmos_key,
         ethereum_key,
         web3,
-        contact,
-        grpc,
+        cosmos_pool,
         contract_address,
         params.gravity_id,
         args.fees,
-        config,
-    )
-    .await;
+        shared_config.clone(),
+    );
+    let config_file = home_dir.join(CONFIG_FOLDER).with_file_name(CONFIG_NAME);
+    if config_file.exists() {
+        join(
+            relayer_loops,
+            watch_relayer_config(config_file, shared_config, batch_requests),
+        )
+        .await;
+    } else {
+        relayer_loops.await;
+    }
+}
+
+/// Loads the relayer's pending Ethereum transactions and gas price history from the home directory so
+/// that transactions left pending by a previous run are managed and altruistic relaying does not start
+/// from nothing, without a home directory they are kept only in memory. Settled transactions are
+/// recorded in the relayer ledger in the same directory
+pub fn load_relayer_state(home_dir: &Path, config: &RelayerConfig) {
+    if !home_dir.exists() {
+        warn!("No home directory, pending relayer transactions and gas price history will not persist across restarts and no ledger will be kept");
+        return;
+    }
+    if let Err(e) = load_pending_transactions(home_dir.join(PENDING_TRANSACTIONS_NAME)) {
+        warn!("Failed to load pending relayer transactions {e}");
+    }
+    if let Err(e) = load_gas_history(home_dir.join(GAS_HISTORY_NAME), config) {
+        warn!("Failed to load the gas price history {e}");
+    }
+    set_ledger_path(home_dir.join(RELAYER_LEDGER_NAME));
+}
+
+/// The percentiles printed by `gbt relayer gas-history`
+const GAS_HISTORY_PERCENTILES: [f32; 7] = [0.05, 0.1, 0.25, 0.5, 0.75, 0.9, 0.95];
+
+/// Prints the distribution of the gas price samples saved by a running relayer, and the price below
+/// which altruistic relaying currently happens
+pub fn relayer_gas_history(args: RelayerGasHistoryOpts, home_dir: &Path, config: &RelayerConfig) {
+    let history = match read_gas_history(&home_dir.join(GAS_HISTORY_NAME), config) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to load the gas price history {e}");
+            exit(1);
+        }
+    };
+    let samples = history.get_samples();
+    if samples.is_empty() {
+        info!(
+            "No gas price samples from the last {} seconds, is the relayer running?",
+            get_gas_history_window(config)
+        );
+        return;
+    }
+    let span = history.get_time_span();
+    info!(
+        "{} gas price samples over the last {:.1} hours, {} are required before altruistic relaying starts",
+        samples.len(),
+        span as f64 / 3600.0,
+        config.altruistic_batch_relaying_samples_delay
+    );
+    for percentile in GAS_HISTORY_PERCENTILES {
+        if let Some(price) = history.get_acceptable_gas_price(percentile) {
+            info!("{:>3}%: {} gwei", percentile * 100.0, print_gwei(price));
+        }
+    }
+    let acceptable =
+        history.get_acceptable_gas_price(config.altruistic_acceptable_gas_price_percentage);
+    if let (Some(latest), Some(acceptable)) = (history.latest_gas_price(), acceptable) {
+        info!(
+            "The latest sample is {} gwei, altruistic relaying happens at or below the {}% price of {} gwei",
+            print_gwei(latest),
+            config.altruistic_acceptable_gas_price_percentage * 100.0,
+            print_gwei(acceptable)
+        );
+    }
+    if args.all {
+        for sample in samples {
+            info!("{} {} gwei", sample.time, print_gwei(sample.price));
+        }
+    }
+}
+
+/// Prints the relayer's profit and loss per reward token and per period from its ledger
+pub fn relayer_report(args: RelayerReportOpts, home_dir: &Path) {
+    let ledger = match load_ledger(&home_dir.join(RELAYER_LEDGER_NAME)) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to load the relayer ledger {e}");
+            exit(1);
+        }
+    };
+    let period = match args.period.as_str() {
+        "week" => 7 * 86400,
+        "month" => 30 * 86400,
+        _ => 86400,
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let since = now.saturating_sub(args.days * 86400);
+    let (by_token, by_period) = summarize(&ledger, period, since);
+    if by_token.is_empty() {
+        info!(
+            "No relayed transactions settled in the last {} days",
+            args.days
+        );
+        return;
+    }
+
+    info!("Relayer profit and loss over the last {} days", args.days);
+    info!("By reward token:");
+    for (token, summary) in by_token.iter() {
+        let token = token
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| "No reward".to_string());
+        print_summary(&token, summary);
+    }
+    info!("By {}:", args.period);
+    for (start, summary) in by_period.iter() {
+        print_summary(&format_date(*start), summary);
+    }
+    let mut total = LedgerSummary::default();
+    for summary in by_token.values() {
+        total.submissions += summary.submissions;
+        total.successes += summary.successes;
+        total.reverts += summary.reverts;
+        total.beaten += summary.beaten;
+        total.cost += summary.cost;
+        total.revenue += summary.revenue;
+    }
+    print_summary("Total", &total);
+}
+
+fn print_summary(label: &str, summary: &LedgerSummary) {
+    let (sign, profit) = match summary.get_profit() {
+        Profit::Gain(v) => ("", v),
+        Profit::Loss(v) => ("-", v),
+    };
+    info!(
+        "{label}: {} submitted, {} succeeded, {} reverted, {} beaten. Gas spent {} ETH, rewards {} ETH, profit {sign}{} ETH",
+        summary.submissions,
+        summary.successes,
+        summary.reverts,
+        summary.beaten,
+        print_eth(summary.cost),
+        print_eth(summary.revenue),
+        print_eth(profit)
+    );
+}
+
+/// Formats a unix timestamp as a UTC date, see http://howardhinnant.github.io/date_algorithms.html
+fn format_date(unix_secs: u64) -> String {
+    let z = (unix_secs / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+fn get_modified(config_file: &Path) -> Option<SystemTime> {
+    fs::metadata(config_file).and_then(|m| m.modified()).ok()
+}
+
+/// Reloads the relayer config whenever the config file is modified or SIGHUP is received, the new
+/// config is only swapped in if it is valid, otherwise the running config is kept
+async fn watch_relayer_config(
+    config_file: PathBuf,
+    shared_config: SharedRelayerConfig,
+    batch_requests: bool,
+) {
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            warn!("Could not listen for SIGHUP, the config will only be reloaded on modification {e:?}");
+            None
+        }
+    };
+    let mut last_modified = get_modified(&config_file);
+    info!(
+        "Watching {} for relayer config changes",
+        config_file.display()
+    );
+    loop {
+        let forced = match hangup.as_mut() {
+            Some(hangup) => matches!(
+                select(
+                    Box::pin(hangup.recv()),
+                    Box::pin(delay_for(CONFIG_POLL_INTERVAL))
+                )
+                .await,
+                Either::Left(_)
+            ),
+            None => {
+                delay_for(CONFIG_POLL_INTERVAL).await;
+                false
+            }
+        };
+        let modified = get_modified(&config_file);
+        if !forced && modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        let new_config = match fs::read_to_string(&config_file) {
+            Ok(v) => parse_relayer_config(&v),
+            Err(e) => Err(format!("Could not read {} {e:?}", config_file.display())),
+        };
+        match new_config {
+            Ok(new_config) => {
+                if new_config == shared_config.get() {
+                    debug!("Relayer config unchanged");
+                    continue;
+                }
+                let old_config = shared_config.replace(new_config.clone());
+                if new_config.altruistic_gas_price_samples < old_config.altruistic_gas_price_samples
+                {
+                    warn!("Reducing altruistic_gas_price_samples requires a relayer restart, the gas tracker will keep {} samples", old_config.altruistic_gas_price_samples);
+                }
+                info!("Reloaded relayer config");
+                print_relaying_explanation(&new_config, batch_requests);
+            }
+            Err(e) => error!("Rejected relayer config reload, keeping the running config! {e}"),
+        }
+    }
 }
@@ -0,0 +1,55 @@
+use crate::args::RemoteSignerOpts;
+use crate::config::{
+    config_exists, load_keys, set_keystore_passphrase_file, REMOTE_SIGNER_JOURNAL_NAME,
+};
+use gravity_utils::eth_signer::run_remote_signer;
+use gravity_utils::signing_journal::SigningJournal;
+use std::path::Path;
+use std::process::exit;
+
+pub async fn remote_signer(args: RemoteSignerOpts, home_dir: &Path) {
+    if let Some(passphrase_file) = &args.keystore_passphrase_file {
+        set_keystore_passphrase_file(passphrase_file);
+    }
+    let ethereum_key = if let Some(k) = args.ethereum_key {
+        k
+    } else {
+        let mut k = None;
+        if config_exists(home_dir) {
+            let keys = load_keys(home_dir);
+            k = keys.ethereum_key;
+        }
+        match k {
+            Some(k) => k,
+            None => {
+                error!("You must specify an Ethereum key!");
+                error!("Store an already registered key using 'gbt keys set-ethereum-key`");
+                error!("To run from the command line, with no key storage use 'gbt remote-signer --ethereum-key your key' ");
+                exit(1);
+            }
+        }
+    };
+
+    let journal_path = if home_dir.exists() {
+        Some(home_dir.join(REMOTE_SIGNER_JOURNAL_NAME))
+    } else {
+        warn!("No home directory, the signing journal will not persist across restarts");
+        None
+    };
+    let journal = match SigningJournal::load(journal_path) {
+        Ok(j) => j,
+        Err(e) => {
+            error!("Failed to load the signing journal, refusing to start {e}");
+            exit(1);
+        }
+    };
+
+    info!(
+        "Starting remote signer for gravity ids {:?}",
+        args.gravity_id
+    );
+    if let Err(e) = run_remote_signer(&args.socket, ethereum_key, args.gravity_id, journal).await {
+        error!("Remote signer halted with {e}");
+        exit(1);
+    }
+}
@@ -1,4 +1,8 @@
-use gravity_utils::types::{BatchRequestMode, RelayerConfig, ValsetRelayingMode};
+use gravity_utils::num_conversion::{print_eth, print_gwei};
+use gravity_utils::types::{
+    BatchRequestMode, FeeStrategy, PriceSource, RelayerConfig, SubmissionMode, ValsetRelayingMode,
+};
+use relayer::private_relay::MAX_BUNDLE_BLOCKS;
 use std::time::Duration;
 
 pub const TIMEOUT: Duration = Duration::from_secs(60);
@@ -42,4 +46,51 @@ pub fn print_relaying_explanation(input: &RelayerConfig, batch_requests: bool) {
         gravity_utils::types::BatchRelayingMode::ProfitableWithWhitelist { margin, whitelist } =>
             info!("This relayer will relay profitable matches with {margin} margin, and the following tokens with the provided amounts {whitelist:?}")
     }
+    if let Some(urgency) = &input.altruistic_urgency {
+        info!(
+            "Altruistic relaying will accept up to the lowest {}% of gas prices as batches come within {} blocks of timing out or wait up to {} seconds",
+            urgency.max_percentage * 100.0,
+            urgency.timeout_blocks,
+            urgency.max_wait
+        );
+    }
+    match &input.fee_strategy {
+        FeeStrategy::NodeSuggested => info!("This relayer will pay a max fee of the node suggested gas price plus 20%"),
+        FeeStrategy::Fixed { max_fee_per_gas, max_priority_fee_per_gas } => info!("This relayer will pay a max fee of {} gwei with a {} gwei priority fee", print_gwei(*max_fee_per_gas), print_gwei(*max_priority_fee_per_gas)),
+        FeeStrategy::FeeHistory { blocks, percentile } => info!("This relayer will pay the {percentile} percentile priority fee of the last {blocks} blocks, with a max fee of twice the base fee"),
+        FeeStrategy::GasTracker { multiplier } => info!("This relayer will pay a max fee of {multiplier} times the latest gas price sample"),
+    }
+    if let Some(cap) = input.max_fee_per_gas_cap {
+        info!(
+            "This relayer will never pay a max fee above {} gwei",
+            print_gwei(cap)
+        );
+    }
+    if let Some(min) = input.min_eth_balance {
+        info!(
+            "This relayer will pause relaying while a submission could leave less than {} ETH in its wallet",
+            print_eth(min)
+        );
+    }
+    if let Some(limit) = input.max_spend_per_hour {
+        info!(
+            "This relayer will spend at most {} ETH on gas per hour",
+            print_eth(limit)
+        );
+    }
+    if let Some(limit) = input.max_spend_per_day {
+        info!(
+            "This relayer will spend at most {} ETH on gas per day",
+            print_eth(limit)
+        );
+    }
+    match &input.price_source {
+        PriceSource::Uniswap => {}
+        source => info!("This relayer will value rewards using the price source {source:?}"),
+    }
+    match &input.submission_mode {
+        SubmissionMode::Public => {}
+        SubmissionMode::PrivateTransaction { endpoint, fallback_blocks } => info!("This relayer will send transactions privately to {endpoint}, and publicly if they are not included within {fallback_blocks} blocks"),
+        SubmissionMode::Bundle { endpoint, fallback_blocks } => info!("This relayer will send transactions as bundles to {endpoint}, and publicly if they are not included within {} blocks", fallback_blocks.min(&MAX_BUNDLE_BLOCKS)),
+    }
 }
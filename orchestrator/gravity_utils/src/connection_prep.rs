@@ -7,23 +7,211 @@ use deep_space::error::CosmosGrpcError;
 use deep_space::Address as CosmosAddress;
 use deep_space::Contact;
 use deep_space::{client::ChainStatus, Coin};
+use futures::future::join_all;
+use gravity_proto::cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::service_client::ServiceClient as TendermintServiceClient;
 use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
 use gravity_proto::gravity::v1::QueryDelegateKeysByEthAddress;
 use gravity_proto::gravity::v1::QueryDelegateKeysByOrchestratorAddress;
+use gravity_proto::gravity::v2::query_client::QueryClient as GravityQueryClientV2;
 use std::process::exit;
-use std::time::Duration;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
-use tonic::transport::Channel;
+use tonic::transport::{Channel, Endpoint};
 use url::Url;
 use web30::client::Web3;
 
+use crate::error::GravityError;
 use crate::get_with_retry::get_balances_with_retry;
 use crate::get_with_retry::get_eth_balances_with_retry;
 
+/// How many blocks a Cosmos gRPC endpoint may be behind the highest endpoint in its pool before
+/// it is considered unhealthy
+pub const MAX_COSMOS_GRPC_BLOCK_LAG: u64 = 10;
+/// How often every endpoint in a CosmosGrpcPool is checked, so that a lagging active endpoint is
+/// detected and a recovered preferred endpoint is returned to. Between these checks only the active
+/// endpoint is queried
+pub const COSMOS_GRPC_POOL_CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
 pub struct Connections {
     pub web3: Option<Web3>,
     pub grpc: Option<GravityQueryClient<Channel>>,
+    /// A client for the v2 Gravity queries on the same endpoint as `grpc`
+    pub grpc_v2: Option<GravityQueryClientV2<Channel>>,
     pub contact: Option<Contact>,
+    /// A failover pool containing the same connection as `grpc` and `contact`, fallback
+    /// endpoints may be added with CosmosGrpcPool::with_fallbacks
+    pub cosmos_pool: Option<CosmosGrpcPool>,
+}
+
+/// A single Cosmos gRPC endpoint in a CosmosGrpcPool
+#[derive(Clone)]
+struct CosmosGrpcEndpoint {
+    url: String,
+    contact: Contact,
+    grpc: GravityQueryClient<Channel>,
+    /// A client for the Tendermint queries on the same endpoint, it connects lazily
+    tendermint: TendermintServiceClient<Channel>,
+}
+
+/// An ordered list of Cosmos gRPC endpoints, the first endpoint is preferred and the others are
+/// used in order if it becomes unhealthy, either because its chain is not moving or because it is
+/// more than MAX_COSMOS_GRPC_BLOCK_LAG blocks behind another endpoint. Clones of the pool share the
+/// active endpoint so that a failover in one loop applies to all of them
+#[derive(Clone)]
+pub struct CosmosGrpcPool {
+    endpoints: Arc<Vec<CosmosGrpcEndpoint>>,
+    active: Arc<RwLock<usize>>,
+    /// When every endpoint was last checked, see COSMOS_GRPC_POOL_CHECK_INTERVAL
+    last_full_check: Arc<RwLock<Instant>>,
+}
+
+impl CosmosGrpcPool {
+    pub fn new(url: String, contact: Contact, grpc: GravityQueryClient<Channel>) -> CosmosGrpcPool {
+        let tendermint = TendermintServiceClient::new(
+            Endpoint::from_shared(url.clone()).unwrap().connect_lazy(),
+        );
+        CosmosGrpcPool {
+            endpoints: Arc::new(vec![CosmosGrpcEndpoint {
+                url,
+                contact,
+                grpc,
+                tendermint,
+            }]),
+            active: Arc::new(RwLock::new(0)),
+            last_full_check: Arc::new(RwLock::new(Instant::now())),
+        }
+    }
+
+    /// Adds the fallback endpoints, in order, after the existing endpoints. Fallbacks connect lazily
+    /// so a fallback that is unreachable now is kept and used once it becomes healthy, an invalid
+    /// fallback url is an error
+    pub async fn with_fallbacks(
+        self,
+        fallback_urls: &[String],
+        address_prefix: &str,
+        timeout: Duration,
+    ) -> Result<CosmosGrpcPool, GravityError> {
+        let mut endpoints = (*self.endpoints).clone();
+        for url in fallback_urls {
+            let url = url.trim_end_matches('/').to_string();
+            let invalid = |e: String| {
+                GravityError::InvalidOptionsError(format!("Invalid Cosmos gRPC url {url} {e}"))
+            };
+            let parsed = Url::parse(&url).map_err(|e| invalid(e.to_string()))?;
+            if !(parsed.scheme() == "http" || parsed.scheme() == "https") {
+                return Err(invalid("please chose http or https".to_string()));
+            }
+            let channel = Endpoint::from_shared(url.clone())
+                .map_err(|e| invalid(e.to_string()))?
+                .connect_lazy();
+            let contact = Contact::new(&url, timeout, address_prefix)?;
+            if let Err(e) = contact.get_chain_status().await {
+                warn!("Failed to connect to fallback Cosmos gRPC {url} with {e:?}, it will be retried when a failover is needed");
+            }
+            endpoints.push(CosmosGrpcEndpoint {
+                contact,
+                url,
+                grpc: GravityQueryClient::new(channel.clone()),
+                tendermint: TendermintServiceClient::new(channel),
+            });
+        }
+        Ok(CosmosGrpcPool {
+            endpoints: Arc::new(endpoints),
+            active: self.active,
+            last_full_check: self.last_full_check,
+        })
+    }
+
+    fn get_active_index(&self) -> usize {
+        *self.active.read().unwrap()
+    }
+
+    pub fn get_active_url(&self) -> String {
+        self.endpoints[self.get_active_index()].url.clone()
+    }
+
+    pub fn get_urls(&self) -> Vec<String> {
+        self.endpoints.iter().map(|e| e.url.clone()).collect()
+    }
+
+    pub fn get_contact(&self) -> Contact {
+        self.endpoints[self.get_active_index()].contact.clone()
+    }
+
+    pub fn get_grpc(&self) -> GravityQueryClient<Channel> {
+        self.endpoints[self.get_active_index()].grpc.clone()
+    }
+
+    /// Returns the Tendermint client of the active endpoint, call this after get_healthy_connection
+    /// so that it is on the same endpoint as the Contact and gRPC client in use
+    pub fn get_tendermint(&self) -> TendermintServiceClient<Channel> {
+        self.endpoints[self.get_active_index()].tendermint.clone()
+    }
+
+    /// Checks the active endpoint using get_chain_status, it is used for as long as its chain is moving.
+    /// If it is not, or every COSMOS_GRPC_POOL_CHECK_INTERVAL, every endpoint is checked and we switch to
+    /// the most preferred endpoint that is healthy, so that we return to a preferred endpoint once it
+    /// recovers. If no endpoint is healthy the active endpoint is left unchanged. Returns the Contact
+    /// and gRPC client to use
+    pub async fn get_healthy_connection(&self) -> (Contact, GravityQueryClient<Channel>) {
+        if self.endpoints.len() == 1 {
+            return (self.get_contact(), self.get_grpc());
+        }
+        let active = self.get_active_index();
+        let full_check_due =
+            self.last_full_check.read().unwrap().elapsed() >= COSMOS_GRPC_POOL_CHECK_INTERVAL;
+        if !full_check_due {
+            if let Ok(ChainStatus::Moving { .. }) =
+                self.endpoints[active].contact.get_chain_status().await
+            {
+                return (
+                    self.endpoints[active].contact.clone(),
+                    self.endpoints[active].grpc.clone(),
+                );
+            }
+        }
+        *self.last_full_check.write().unwrap() = Instant::now();
+        let statuses = join_all(self.endpoints.iter().map(|e| e.contact.get_chain_status())).await;
+        let heights: Vec<Option<u64>> = statuses
+            .into_iter()
+            .map(|s| match s {
+                Ok(ChainStatus::Moving { block_height }) => Some(block_height),
+                _ => None,
+            })
+            .collect();
+        match choose_endpoint(&heights, MAX_COSMOS_GRPC_BLOCK_LAG) {
+            Some(i) => {
+                if i != active {
+                    warn!(
+                        "Cosmos gRPC {} is unhealthy or behind at height {:?}, failover to {} at height {:?}",
+                        self.endpoints[active].url, heights[active], self.endpoints[i].url, heights[i]
+                    );
+                    *self.active.write().unwrap() = i;
+                }
+                (
+                    self.endpoints[i].contact.clone(),
+                    self.endpoints[i].grpc.clone(),
+                )
+            }
+            None => {
+                warn!(
+                    "No Cosmos gRPC endpoint is healthy, continuing with {}",
+                    self.endpoints[active].url
+                );
+                (self.get_contact(), self.get_grpc())
+            }
+        }
+    }
+}
+
+/// Returns the index of the first endpoint with a moving chain whose height is no more than `max_lag`
+/// blocks behind the highest endpoint, given the height of each endpoint or None if its chain is not moving
+fn choose_endpoint(heights: &[Option<u64>], max_lag: u64) -> Option<usize> {
+    let highest = heights.iter().flatten().max()?;
+    heights
+        .iter()
+        .position(|h| matches!(h, Some(h) if h + max_lag >= *highest))
 }
 
 /// Returns the three major RPC connections required for Gravity
@@ -38,6 +226,7 @@ pub async fn create_rpc_connections(
     let mut web3 = None;
     let mut grpc = None;
     let mut contact = None;
+    let mut cosmos_url = None;
     if let Some(grpc_url) = grpc_url {
         let url = Url::parse(&grpc_url)
             .unwrap_or_else(|_| panic!("Invalid Cosmos gRPC url {}", grpc_url));
@@ -50,6 +239,7 @@ pub async fn create_rpc_connections(
             Ok(val) => {
                 grpc = Some(val);
                 contact = Some(Contact::new(&cosmos_grpc_url, timeout, &address_prefix).unwrap());
+                cosmos_url = Some(cosmos_grpc_url);
             }
             // did not work, now we check if it's localhost
             Err(e) => {
@@ -67,11 +257,13 @@ pub async fn create_rpc_connections(
                         (Ok(v), Err(_)) => {
                             info!("Url fallback succeeded, your cosmos gRPC url {grpc_url} has been corrected to {ipv4_url}");
                             contact = Some(Contact::new(&ipv4_url, timeout, &address_prefix).unwrap());
+                            cosmos_url = Some(ipv4_url);
                             grpc = Some(v)
                         },
                         (Err(_), Ok(v)) => {
                             info!("Url fallback succeeded, your cosmos gRPC url {grpc_url} has been corrected to {ipv6_url}");
                             contact = Some(Contact::new(&ipv6_url, timeout, &address_prefix).unwrap());
+                            cosmos_url = Some(ipv6_url);
                             grpc = Some(v)
                         },
                         (Ok(_), Ok(_)) => panic!("This should never happen? Why didn't things work the first time?"),
@@ -91,11 +283,13 @@ pub async fn create_rpc_connections(
                         (Ok(v), Err(_)) => {
                             info!("Https upgrade succeeded, your cosmos gRPC url {grpc_url} has been corrected to {https_on_80_url}");
                             contact = Some(Contact::new(&https_on_80_url, timeout, &address_prefix).unwrap());
+                            cosmos_url = Some(https_on_80_url);
                             grpc = Some(v)
                         },
                         (Err(_), Ok(v)) => {
                             info!("Https upgrade succeeded, your cosmos gRPC url {grpc_url} has been corrected to {https_on_443_url}");
                             contact = Some(Contact::new(&https_on_443_url, timeout, &address_prefix).unwrap());
+                            cosmos_url = Some(https_on_443_url);
                             grpc = Some(v)
                         },
                         (Ok(_), Ok(_)) => panic!("This should never happen? Why didn't things work the first time?"),
@@ -174,10 +368,22 @@ pub async fn create_rpc_connections(
         }
     }
 
+    let grpc_v2 = cosmos_url.as_ref().map(|url| {
+        GravityQueryClientV2::new(Endpoint::from_shared(url.clone()).unwrap().connect_lazy())
+    });
+    let cosmos_pool = match (cosmos_url, &contact, &grpc) {
+        (Some(url), Some(contact), Some(grpc)) => {
+            Some(CosmosGrpcPool::new(url, contact.clone(), grpc.clone()))
+        }
+        _ => None,
+    };
+
     Connections {
         web3,
         grpc,
+        grpc_v2,
         contact,
+        cosmos_pool,
     }
 }
 
@@ -348,3 +554,25 @@ pub async fn check_for_eth(address: EthAddress, web3: &Web3) {
         exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_endpoint() {
+        // the preferred endpoint is used while it is healthy
+        assert_eq!(choose_endpoint(&[Some(100), Some(100)], 10), Some(0));
+        assert_eq!(choose_endpoint(&[Some(95), Some(100)], 10), Some(0));
+        // not moving or too far behind
+        assert_eq!(choose_endpoint(&[None, Some(100)], 10), Some(1));
+        assert_eq!(choose_endpoint(&[Some(80), Some(100)], 10), Some(1));
+        assert_eq!(choose_endpoint(&[Some(80), None, Some(100)], 10), Some(2));
+        assert_eq!(
+            choose_endpoint(&[Some(80), Some(95), Some(100)], 10),
+            Some(1)
+        );
+        // nothing is healthy
+        assert_eq!(choose_endpoint(&[None, None], 10), None);
+    }
+}
@@ -28,6 +28,10 @@ pub enum GravityError {
     InsufficientVotingPowerToPass(String),
     ParseBigIntError(ParseError),
     ValsetUpToDate,
+    IoError(std::io::Error),
+    EthereumReorgError(String),
+    EthereumQuorumError(String),
+    RemoteSignerError(String),
 }
 
 impl fmt::Display for GravityError {
@@ -63,6 +67,14 @@ impl fmt::Display for GravityError {
                     "latest validator set is synced between Ethereum and Cosmos"
                 )
             }
+            GravityError::IoError(val) => write!(f, "IO error {val}"),
+            GravityError::EthereumReorgError(val) => {
+                write!(f, "Ethereum block hash conflict detected! {val}")
+            }
+            GravityError::EthereumQuorumError(val) => {
+                write!(f, "Ethereum RPC endpoints disagree! {val}")
+            }
+            GravityError::RemoteSignerError(val) => write!(f, "Remote signer error {val}"),
         }
     }
 }
@@ -107,3 +119,8 @@ impl From<ParseError> for GravityError {
         GravityError::InvalidBigInt(error)
     }
 }
+impl From<std::io::Error> for GravityError {
+    fn from(error: std::io::Error) -> Self {
+        GravityError::IoError(error)
+    }
+}
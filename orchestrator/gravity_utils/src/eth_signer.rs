@@ -0,0 +1,392 @@
+//! Signers for the Ethereum checkpoint signatures submitted by the orchestrator. The delegate Ethereum key
+//! may either be held in process or by a separate signer process reached over a Unix socket. The remote
+//! protocol is newline delimited json, each request line receives exactly one response line.
+//!
+//! The remote signer is sent the full, unhashed checkpoint message rather than a hash so that it can
+//! check the gravity_id and method name embedded in the message before signing anything, and keep its own
+//! signing journal so that it never signs two different checkpoints for the same nonce.
+
+use crate::error::GravityError;
+use crate::signing_journal::{SignedCheckpoint, SigningJournal};
+use clarity::{Address as EthAddress, PrivateKey as EthPrivateKey, Signature as EthSignature};
+use std::fs::Permissions;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::time::timeout;
+
+/// The method names encoded into the second word of every checkpoint the orchestrator signs
+pub const CHECKPOINT_METHODS: [&str; 3] = ["checkpoint", "transactionBatch", "logicCall"];
+
+/// A request to the remote signer
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum SignerRequest {
+    GetAddress,
+    /// `checkpoint` is the hex encoded output of one of the encode_*_confirm functions
+    SignCheckpoint {
+        checkpoint: String,
+    },
+}
+
+/// A response from the remote signer, exactly one of the fields is set
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct SignerResponse {
+    pub address: Option<EthAddress>,
+    pub signature: Option<EthSignature>,
+    pub error: Option<String>,
+}
+
+/// Produces the Ethereum signatures for valset, batch, and logic call confirms
+#[derive(Debug, Clone)]
+pub enum EthSigner {
+    /// Signs in process using the delegate Ethereum key
+    Local(EthPrivateKey),
+    /// Forwards signing requests to a separate signer process
+    Remote(RemoteEthSigner),
+}
+
+impl EthSigner {
+    /// The delegate Ethereum address signatures are produced with
+    pub fn get_address(&self) -> EthAddress {
+        match self {
+            EthSigner::Local(key) => key.to_address(),
+            EthSigner::Remote(signer) => signer.address,
+        }
+    }
+
+    /// Signs the given checkpoint message, as produced by encode_valset_confirm, encode_tx_batch_confirm
+    /// or encode_logic_call_confirm, the signature is over the hashed message (see the *_hashed variants)
+    pub async fn sign_checkpoint(&self, checkpoint: &[u8]) -> Result<EthSignature, GravityError> {
+        match self {
+            EthSigner::Local(key) => Ok(key.sign_ethereum_msg(checkpoint)),
+            EthSigner::Remote(signer) => signer.sign_checkpoint(checkpoint).await,
+        }
+    }
+}
+
+/// A client for a remote signer process listening on a Unix socket
+#[derive(Debug, Clone)]
+pub struct RemoteEthSigner {
+    socket_path: PathBuf,
+    address: EthAddress,
+    timeout: Duration,
+}
+
+impl RemoteEthSigner {
+    /// Connects to the remote signer and queries the address it signs for
+    pub async fn connect(
+        socket_path: PathBuf,
+        timeout: Duration,
+    ) -> Result<RemoteEthSigner, GravityError> {
+        let response =
+            send_signer_request(&socket_path, &SignerRequest::GetAddress, timeout).await?;
+        match response.address {
+            Some(address) => Ok(RemoteEthSigner {
+                socket_path,
+                address,
+                timeout,
+            }),
+            None => Err(GravityError::RemoteSignerError(format!(
+                "Remote signer did not return an address {:?}",
+                response.error
+            ))),
+        }
+    }
+
+    pub async fn sign_checkpoint(&self, checkpoint: &[u8]) -> Result<EthSignature, GravityError> {
+        let request = SignerRequest::SignCheckpoint {
+            checkpoint: clarity::utils::bytes_to_hex_str(checkpoint),
+        };
+        let response = send_signer_request(&self.socket_path, &request, self.timeout).await?;
+        match (response.signature, response.error) {
+            (Some(signature), _) => {
+                // a misconfigured signer could return a signature from a different key, which
+                // would fail on chain in a much less obvious way
+                let hash = clarity::utils::get_ethereum_msg_hash(checkpoint);
+                match signature.recover(&hash) {
+                    Ok(signer) if signer == self.address => Ok(signature),
+                    _ => Err(GravityError::RemoteSignerError(format!(
+                        "Remote signer returned a signature not produced by {}",
+                        self.address
+                    ))),
+                }
+            }
+            (None, e) => Err(GravityError::RemoteSignerError(format!(
+                "Remote signer refused to sign {e:?}"
+            ))),
+        }
+    }
+}
+
+async fn send_signer_request(
+    socket_path: &Path,
+    request: &SignerRequest,
+    request_timeout: Duration,
+) -> Result<SignerResponse, GravityError> {
+    let exchange = async {
+        let stream = UnixStream::connect(socket_path).await?;
+        let (read, mut write) = stream.into_split();
+        let mut line = serde_json::to_string(request).map_err(std::io::Error::from)?;
+        line.push('\n');
+        write.write_all(line.as_bytes()).await?;
+
+        let mut response = String::new();
+        BufReader::new(read).read_line(&mut response).await?;
+        let response: SignerResponse =
+            serde_json::from_str(&response).map_err(std::io::Error::from)?;
+        Ok::<SignerResponse, std::io::Error>(response)
+    };
+    match timeout(request_timeout, exchange).await {
+        Ok(Ok(response)) => Ok(response),
+        Ok(Err(e)) => Err(GravityError::RemoteSignerError(format!(
+            "Failed to communicate with remote signer at {} {e:?}",
+            socket_path.display()
+        ))),
+        Err(_) => Err(GravityError::TimeoutError),
+    }
+}
+
+/// Checks that the checkpoint message is one the orchestrator would produce for one of the allowed
+/// gravity ids, the first word is the gravity id and the second word is the method name, both encoded
+/// as left aligned, zero padded bytes32 values
+pub fn check_checkpoint(checkpoint: &[u8], allowed_gravity_ids: &[String]) -> Result<(), String> {
+    if checkpoint.len() < 64 {
+        return Err(format!("Checkpoint too short, {} bytes", checkpoint.len()));
+    }
+    let (gravity_id, method) = (&checkpoint[0..32], &checkpoint[32..64]);
+    if !allowed_gravity_ids
+        .iter()
+        .any(|id| encode_fixed_string(id).map_or(false, |v| v[..] == *gravity_id))
+    {
+        return Err("Gravity id is not in the allowlist".to_string());
+    }
+    if !CHECKPOINT_METHODS
+        .iter()
+        .any(|m| encode_fixed_string(m).map_or(false, |v| v[..] == *method))
+    {
+        return Err("Not a valset, batch, or logic call checkpoint".to_string());
+    }
+    Ok(())
+}
+
+pub(crate) fn encode_fixed_string(input: &str) -> Option<[u8; 32]> {
+    let bytes = input.as_bytes();
+    if bytes.len() > 32 {
+        return None;
+    }
+    let mut out = [0u8; 32];
+    out[..bytes.len()].copy_from_slice(bytes);
+    Some(out)
+}
+
+/// Handles a single signer request, refusing any checkpoint not matching the allowlist or conflicting with
+/// one already recorded in the journal. Checkpoints are recorded in the journal before they are signed
+pub fn handle_signer_request(
+    request: SignerRequest,
+    key: EthPrivateKey,
+    allowed_gravity_ids: &[String],
+    journal: &Mutex<SigningJournal>,
+) -> SignerResponse {
+    match request {
+        SignerRequest::GetAddress => SignerResponse {
+            address: Some(key.to_address()),
+            ..Default::default()
+        },
+        SignerRequest::SignCheckpoint { checkpoint } => {
+            let checkpoint = match clarity::utils::hex_str_to_bytes(&checkpoint) {
+                Ok(v) => v,
+                Err(e) => {
+                    return SignerResponse {
+                        error: Some(format!("Invalid checkpoint hex {e:?}")),
+                        ..Default::default()
+                    }
+                }
+            };
+            let recorded = check_checkpoint(&checkpoint, allowed_gravity_ids)
+                .and_then(|_| SignedCheckpoint::from_checkpoint(&checkpoint))
+                .and_then(|entry| {
+                    journal
+                        .lock()
+                        .unwrap()
+                        .record(entry)
+                        .map_err(|e| e.to_string())
+                });
+            match recorded {
+                Ok(()) => SignerResponse {
+                    signature: Some(key.sign_ethereum_msg(&checkpoint)),
+                    ..Default::default()
+                },
+                Err(e) => {
+                    warn!("Refusing to sign checkpoint {e}");
+                    SignerResponse {
+                        error: Some(e),
+                        ..Default::default()
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Runs a remote signer on the given Unix socket, holding `key` and signing checkpoints only for
+/// `allowed_gravity_ids` and only if they do not conflict with `journal`. The socket is only accessible
+/// to the user running the signer. Each connection is served by its own task so a stuck client can not
+/// block signing, this never returns unless the socket can not be created
+pub async fn run_remote_signer(
+    socket_path: &Path,
+    key: EthPrivateKey,
+    allowed_gravity_ids: Vec<String>,
+    journal: SigningJournal,
+) -> Result<(), GravityError> {
+    // a stale socket from a previous run would prevent binding
+    if socket_path.exists() {
+        std::fs::remove_file(socket_path)?;
+    }
+    let listener = UnixListener::bind(socket_path)?;
+    // anyone who can connect can get signatures, nothing is accepted until the socket is restricted
+    std::fs::set_permissions(socket_path, Permissions::from_mode(0o600))?;
+    let allowed_gravity_ids = Arc::new(allowed_gravity_ids);
+    let journal = Arc::new(Mutex::new(journal));
+    info!(
+        "Remote signer for {} listening on {}",
+        key.to_address(),
+        socket_path.display()
+    );
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                warn!("Remote signer failed to accept connection {e:?}");
+                continue;
+            }
+        };
+        tokio::spawn(serve_signer_connection(
+            stream,
+            key,
+            allowed_gravity_ids.clone(),
+            journal.clone(),
+        ));
+    }
+}
+
+async fn serve_signer_connection(
+    stream: UnixStream,
+    key: EthPrivateKey,
+    allowed_gravity_ids: Arc<Vec<String>>,
+    journal: Arc<Mutex<SigningJournal>>,
+) {
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let response = match serde_json::from_str(&line) {
+            Ok(request) => handle_signer_request(request, key, &allowed_gravity_ids, &journal),
+            Err(e) => SignerResponse {
+                error: Some(format!("Invalid request {e:?}")),
+                ..Default::default()
+            },
+        };
+        let mut response = serde_json::to_string(&response).unwrap();
+        response.push('\n');
+        if write.write_all(response.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix::System;
+    use futures::future::{select, Either};
+
+    /// A checkpoint with every nonce and scope zero, `timeout` makes otherwise identical checkpoints differ
+    fn test_checkpoint(gravity_id: &str, method: &str, timeout: u8) -> Vec<u8> {
+        let mut checkpoint = encode_fixed_string(gravity_id).unwrap().to_vec();
+        checkpoint.extend_from_slice(&encode_fixed_string(method).unwrap());
+        checkpoint.extend_from_slice(&[0u8; 32 * 9]);
+        checkpoint[8 * 32 - 1] = timeout;
+        checkpoint
+    }
+
+    #[test]
+    fn test_check_checkpoint() {
+        let allowed = vec!["gravity-bridge-mainnet".to_string()];
+        assert!(check_checkpoint(
+            &test_checkpoint("gravity-bridge-mainnet", "checkpoint", 1),
+            &allowed
+        )
+        .is_ok());
+        assert!(check_checkpoint(
+            &test_checkpoint("gravity-bridge-mainnet", "logicCall", 1),
+            &allowed
+        )
+        .is_ok());
+        assert!(
+            check_checkpoint(&test_checkpoint("other-bridge", "checkpoint", 1), &allowed).is_err()
+        );
+        assert!(check_checkpoint(
+            &test_checkpoint("gravity-bridge-mainnet", "transfer", 1),
+            &allowed
+        )
+        .is_err());
+        assert!(check_checkpoint(&[0u8; 40], &allowed).is_err());
+    }
+
+    #[test]
+    fn test_remote_signer() {
+        let key = EthPrivateKey::from_bytes([3u8; 32]).unwrap();
+        let socket_path = std::env::temp_dir().join(format!(
+            "gravity_remote_signer_test_{}.sock",
+            std::process::id()
+        ));
+        let allowed = vec!["test-gravity-id".to_string()];
+        let runner = System::new();
+        runner.block_on(async move {
+            let server = run_remote_signer(&socket_path, key, allowed, SigningJournal::default());
+            let client = async {
+                // wait for the signer to bind
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                let signer = EthSigner::Remote(
+                    RemoteEthSigner::connect(socket_path.clone(), Duration::from_secs(5))
+                        .await
+                        .unwrap(),
+                );
+                assert_eq!(signer.get_address(), key.to_address());
+                let mode = std::fs::metadata(&socket_path)
+                    .unwrap()
+                    .permissions()
+                    .mode();
+                assert_eq!(mode & 0o777, 0o600);
+
+                let checkpoint = test_checkpoint("test-gravity-id", "transactionBatch", 1);
+                let signature = signer.sign_checkpoint(&checkpoint).await.unwrap();
+                let local = EthSigner::Local(key)
+                    .sign_checkpoint(&checkpoint)
+                    .await
+                    .unwrap();
+                assert_eq!(signature, local);
+
+                let refused = test_checkpoint("other-gravity-id", "transactionBatch", 1);
+                assert!(signer.sign_checkpoint(&refused).await.is_err());
+                // the same checkpoint may be signed again but a different one for the same nonce may not
+                signer.sign_checkpoint(&checkpoint).await.unwrap();
+                let conflicting = test_checkpoint("test-gravity-id", "transactionBatch", 2);
+                assert!(signer.sign_checkpoint(&conflicting).await.is_err());
+
+                // a client that never sends its request does not block others
+                let _stuck = UnixStream::connect(&socket_path).await.unwrap();
+                let checkpoint = test_checkpoint("test-gravity-id", "checkpoint", 1);
+                signer.sign_checkpoint(&checkpoint).await.unwrap();
+            };
+            match select(Box::pin(server), Box::pin(client)).await {
+                Either::Left((res, _)) => panic!("Remote signer exited {res:?}"),
+                Either::Right(_) => {}
+            }
+            std::fs::remove_file(&socket_path).unwrap();
+        });
+    }
+}
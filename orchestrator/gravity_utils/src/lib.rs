@@ -9,7 +9,9 @@ extern crate log;
 
 pub mod connection_prep;
 pub mod error;
+pub mod eth_signer;
 pub mod get_with_retry;
 pub mod num_conversion;
 pub mod prices;
+pub mod signing_journal;
 pub mod types;
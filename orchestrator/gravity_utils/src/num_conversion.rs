@@ -27,6 +27,12 @@ pub fn downcast_to_u128(input: Uint256) -> Option<u128> {
     input.to_u128()
 }
 
+/// converts wei to gwei as a float, for metrics which can not hold wei amounts
+pub fn wei_to_gwei(input: Uint256) -> f64 {
+    let float: f64 = input.to_string().parse().unwrap();
+    float / ONE_GWEI_FLOAT
+}
+
 /// TODO revisit this for higher precision while
 /// still representing the number to the user as a float
 /// this takes a number like 0.37 eth and turns it into wei
@@ -1,6 +1,14 @@
+use crate::types::{PriceSource, RelayerConfig, WhitelistToken};
 use clarity::address::Address as EthAddress;
 use clarity::Uint256;
+use futures::future::{join_all, BoxFuture};
 use futures::join;
+use futures::FutureExt;
+use lazy_static::lazy_static;
+use num_traits::CheckedMul;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
 use web30::amm::DAI_CONTRACT_ADDRESS;
 use web30::amm::USDC_CONTRACT_ADDRESS;
 use web30::amm::USDT_CONTRACT_ADDRESS;
@@ -10,6 +18,189 @@ use web30::jsonrpc::error::Web3Error;
 
 const FIVE_PERCENT: f64 = 0.05f64; // used as an acceptable amount of slippage
 
+/// The timeout for price feed requests
+const PRICE_FEED_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The most decimals a priced token may have, 10^78 does not fit in a Uint256
+pub const MAX_DECIMALS: u8 = 77;
+
+lazy_static! {
+    // Recently obtained WETH values by price source, token and amount, so that a single relayer
+    // iteration does not query the same price repeatedly. Keyed by source so that a reloaded
+    // config never uses prices from the source it replaced
+    static ref PRICE_CACHE: RwLock<HashMap<(PriceSource, EthAddress, Uint256), (Instant, Uint256)>> =
+        RwLock::new(HashMap::new());
+}
+
+/// The response expected from a PriceSource::HttpFeed url
+#[derive(Deserialize, Debug)]
+struct PriceFeedResponse {
+    /// wei per coin
+    price: Uint256,
+    decimals: u8,
+}
+
+/// The WETH value of `amount` base units of a token priced at `price` wei per coin. Prices come
+/// from the config or an untrusted feed, so an out of range price or decimals is an error
+pub fn get_value_in_weth(
+    price: Uint256,
+    decimals: u8,
+    amount: Uint256,
+) -> Result<Uint256, Web3Error> {
+    if decimals > MAX_DECIMALS {
+        return Err(Web3Error::BadResponse(format!(
+            "Token decimals {decimals} is greater than the maximum of {MAX_DECIMALS}"
+        )));
+    }
+    let one_coin: Uint256 = Uint256::from(10u8).pow(decimals as u32);
+    match amount.checked_mul(&price) {
+        Some(value) => Ok(value / one_coin),
+        None => Err(Web3Error::BadResponse(format!(
+            "The value of {amount} at a price of {price} overflows"
+        ))),
+    }
+}
+
+/// The median of the given values, the lower of the middle two for an even number of values
+pub fn get_median(mut values: Vec<Uint256>) -> Option<Uint256> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort();
+    Some(values[(values.len() - 1) / 2])
+}
+
+/// Gets the WETH value of `amount` of `token` from the relayer's configured price source
+pub async fn get_relayer_weth_price(
+    config: &RelayerConfig,
+    pubkey: EthAddress,
+    token: EthAddress,
+    amount: Uint256,
+    web3: &Web3,
+) -> Result<Uint256, Web3Error> {
+    config
+        .price_source
+        .get_weth_price(
+            pubkey,
+            token,
+            amount,
+            web3,
+            Duration::from_secs(config.price_cache_ttl),
+        )
+        .await
+}
+
+impl PriceSource {
+    /// Gets the WETH value of `amount` of `token` from this source, reusing a value obtained
+    /// less than `cache_ttl` ago from the same source for the same token and amount
+    pub async fn get_weth_price(
+        &self,
+        pubkey: EthAddress,
+        token: EthAddress,
+        amount: Uint256,
+        web3: &Web3,
+        cache_ttl: Duration,
+    ) -> Result<Uint256, Web3Error> {
+        let key = (self.clone(), token, amount);
+        if let Some((time, price)) = PRICE_CACHE.read().unwrap().get(&key) {
+            if time.elapsed() < cache_ttl {
+                return Ok(*price);
+            }
+        }
+        let price = self
+            .get_weth_price_uncached(pubkey, token, amount, web3)
+            .await?;
+        let mut cache = PRICE_CACHE.write().unwrap();
+        cache.retain(|_, (time, _)| time.elapsed() < cache_ttl);
+        cache.insert(key, (Instant::now(), price));
+        Ok(price)
+    }
+
+    fn get_weth_price_uncached<'a>(
+        &'a self,
+        pubkey: EthAddress,
+        token: EthAddress,
+        amount: Uint256,
+        web3: &'a Web3,
+    ) -> BoxFuture<'a, Result<Uint256, Web3Error>> {
+        async move {
+            if token == *WETH_CONTRACT_ADDRESS {
+                return Ok(amount);
+            } else if amount == 0u8.into() {
+                return Ok(0u8.into());
+            }
+            match self {
+                PriceSource::Uniswap => {
+                    get_weth_price_with_retries(pubkey, token, amount, web3).await
+                }
+                PriceSource::Static { prices } => get_static_price(prices, token, amount),
+                PriceSource::HttpFeed { url } => get_feed_price(url, token, amount).await,
+                PriceSource::Median { sources } | PriceSource::Min { sources } => {
+                    let prices: Vec<Uint256> = join_all(
+                        sources
+                            .iter()
+                            .map(|s| s.get_weth_price_uncached(pubkey, token, amount, web3)),
+                    )
+                    .await
+                    .into_iter()
+                    .filter_map(|v| match v {
+                        Ok(v) => Some(v),
+                        Err(e) => {
+                            info!("Price source failed for {token} {e:?}");
+                            None
+                        }
+                    })
+                    .collect();
+                    let price = if let PriceSource::Min { .. } = self {
+                        prices.into_iter().min()
+                    } else {
+                        get_median(prices)
+                    };
+                    price.ok_or_else(|| {
+                        Web3Error::BadResponse(format!("No price source has a price for {token}"))
+                    })
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+fn get_static_price(
+    prices: &[WhitelistToken],
+    token: EthAddress,
+    amount: Uint256,
+) -> Result<Uint256, Web3Error> {
+    match prices.iter().find(|p| p.token == token) {
+        Some(p) => get_value_in_weth(p.price, p.decimals, amount),
+        None => Err(Web3Error::BadResponse(format!(
+            "No static price configured for {token}"
+        ))),
+    }
+}
+
+async fn get_feed_price(
+    url: &str,
+    token: EthAddress,
+    amount: Uint256,
+) -> Result<Uint256, Web3Error> {
+    let url = url.replace("{token}", &token.to_string());
+    let client = reqwest::Client::builder()
+        .timeout(PRICE_FEED_TIMEOUT)
+        .build()
+        .map_err(|e| Web3Error::BadResponse(format!("Failed to build price feed client {e}")))?;
+    let response: PriceFeedResponse = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Web3Error::BadResponse(format!("Price feed request failed {e}")))?
+        .json()
+        .await
+        .map_err(|e| Web3Error::BadResponse(format!("Invalid price feed response {e}")))?;
+    get_value_in_weth(response.price, response.decimals, amount)
+}
+
 /// First fetches the amount of WETH obtainable for `amount` of `token` from all the Uniswap v2 and v3 token/WETH pools,
 /// potentially fetching the stablecoin intermediary (token -> USDC/USDT/DAI -> WETH) paths if no suitable WETH pairing exists.
 /// Stablecoin prices are preferred in USDC, USDT, DAI order
@@ -196,7 +387,33 @@ mod tests {
     use std::time::Duration;
     use web30::client::Web3;
 
-    use super::get_weth_price_with_retries;
+    use super::{get_median, get_value_in_weth, get_weth_price_with_retries};
+
+    #[test]
+    fn test_price_math() {
+        // 2000 USDC at 0.0005 WETH per USDC
+        let price: Uint256 = 500_000_000_000_000u64.into();
+        let amount: Uint256 = 2_000_000_000u64.into();
+        assert_eq!(
+            get_value_in_weth(price, 6, amount).unwrap(),
+            1_000_000_000_000_000_000u64.into()
+        );
+        // a hostile feed can not panic the relayer with huge decimals or prices
+        assert!(get_value_in_weth(price, 77, amount).is_ok());
+        assert!(get_value_in_weth(price, 78, amount).is_err());
+        assert!(get_value_in_weth(price, 255, amount).is_err());
+        let huge = Uint256::from(2u8).pow(255);
+        assert!(get_value_in_weth(huge, 18, amount).is_err());
+        assert_eq!(
+            get_value_in_weth(huge, 18, 1u8.into()).unwrap(),
+            huge / Uint256::from(10u8).pow(18)
+        );
+
+        let values = |v: &[u64]| v.iter().map(|v| Uint256::from(*v)).collect::<Vec<_>>();
+        assert_eq!(get_median(values(&[])), None);
+        assert_eq!(get_median(values(&[3, 1, 2])), Some(2u8.into()));
+        assert_eq!(get_median(values(&[4, 1, 3, 2])), Some(2u8.into()));
+    }
 
     #[test]
     #[ignore]
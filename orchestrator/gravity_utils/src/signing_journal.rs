@@ -0,0 +1,232 @@
+//! A local journal of every checkpoint the Eth signer has signed. Signing two different checkpoints for the
+//! same valset, batch, or logic call nonce is slashable (see submit_bad_signature_evidence) so the journal is
+//! checked before signing anything the Cosmos node asks for, and any conflicting request is refused. This
+//! protects against a compromised or malfunctioning Cosmos node, which the signer otherwise trusts completely.
+//!
+//! The journal is an append only file of json lines, entries are written and synced to disk before the
+//! signature is produced, so a crash can not leave a signature without a journal entry. It is kept by both
+//! the orchestrator and the remote signer, which can not rely on the orchestrator to have checked it
+
+use crate::error::GravityError;
+use crate::eth_signer::encode_fixed_string;
+use clarity::utils::{bytes_to_hex_str, get_ethereum_msg_hash};
+use clarity::Address as EthAddress;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointType {
+    Valset,
+    Batch,
+    LogicCall,
+}
+
+/// A single signed checkpoint, `scope` is the token contract for batches and the invalidation id
+/// for logic calls, both of which have independent nonces, and is empty for valsets
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SignedCheckpoint {
+    pub checkpoint_type: CheckpointType,
+    pub nonce: u64,
+    pub scope: String,
+    pub checkpoint_hash: String,
+}
+
+impl SignedCheckpoint {
+    /// Reads the journal entry for a checkpoint message, as produced by encode_valset_confirm,
+    /// encode_tx_batch_confirm or encode_logic_call_confirm, from the abi encoded message itself. This is
+    /// how the remote signer, which only ever sees the message, journals what it signs. The logic call
+    /// scope is the zero padded bytes32 invalidation id as it appears in the message
+    pub fn from_checkpoint(checkpoint: &[u8]) -> Result<SignedCheckpoint, String> {
+        let word = |i: usize| {
+            checkpoint
+                .get(i * 32..(i + 1) * 32)
+                .ok_or_else(|| format!("Checkpoint too short, {} bytes", checkpoint.len()))
+        };
+        let nonce = |i: usize| {
+            let word = word(i)?;
+            if word[..24].iter().any(|b| *b != 0) {
+                return Err("Checkpoint nonce does not fit in a u64".to_string());
+            }
+            Ok(u64::from_be_bytes(word[24..].try_into().unwrap()))
+        };
+        let method = word(1)?;
+        let is_method = |m: &str| encode_fixed_string(m).map_or(false, |v| v[..] == *method);
+        let (checkpoint_type, nonce, scope) = if is_method("checkpoint") {
+            (CheckpointType::Valset, nonce(2)?, String::new())
+        } else if is_method("transactionBatch") {
+            let token_contract = EthAddress::from_slice(&word(6)?[12..])
+                .map_err(|e| format!("Invalid batch token contract {e:?}"))?;
+            (CheckpointType::Batch, nonce(5)?, token_contract.to_string())
+        } else if is_method("logicCall") {
+            (
+                CheckpointType::LogicCall,
+                nonce(10)?,
+                bytes_to_hex_str(word(9)?),
+            )
+        } else {
+            return Err("Not a valset, batch, or logic call checkpoint".to_string());
+        };
+        Ok(SignedCheckpoint {
+            checkpoint_type,
+            nonce,
+            scope,
+            checkpoint_hash: bytes_to_hex_str(&get_ethereum_msg_hash(checkpoint)),
+        })
+    }
+
+    fn key(&self) -> (CheckpointType, u64, String) {
+        (self.checkpoint_type, self.nonce, self.scope.clone())
+    }
+}
+
+/// A request to sign a checkpoint that differs from the one already signed for the same nonce
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningConflict {
+    pub requested: SignedCheckpoint,
+    pub signed_hash: String,
+}
+
+impl fmt::Display for SigningConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Refusing to sign {:?} nonce {} {} with checkpoint {}, checkpoint {} was already signed",
+            self.requested.checkpoint_type,
+            self.requested.nonce,
+            self.requested.scope,
+            self.requested.checkpoint_hash,
+            self.signed_hash
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SigningJournal {
+    /// The journal file, if None the journal is kept only in memory
+    path: Option<PathBuf>,
+    signed: HashMap<(CheckpointType, u64, String), String>,
+}
+
+impl SigningJournal {
+    /// Loads the journal at the given path, a missing file is treated as an empty journal. A journal
+    /// that can not be parsed is an error since signing without it would defeat its purpose
+    pub fn load(path: Option<PathBuf>) -> Result<SigningJournal, GravityError> {
+        let mut journal = SigningJournal {
+            path: path.clone(),
+            signed: HashMap::new(),
+        };
+        let path = match path {
+            Some(p) if p.exists() => p,
+            _ => return Ok(journal),
+        };
+
+        let contents = fs::read_to_string(&path)?;
+        let lines: Vec<&str> = contents.lines().filter(|l| !l.trim().is_empty()).collect();
+        for (i, line) in lines.iter().enumerate() {
+            match serde_json::from_str::<SignedCheckpoint>(line) {
+                Ok(entry) => {
+                    journal.signed.insert(entry.key(), entry.checkpoint_hash);
+                }
+                // a crash mid append can only truncate the final entry, and that entry was
+                // never signed since entries are synced before signing. It is removed so that
+                // new entries are not appended to it
+                Err(e) if i == lines.len() - 1 && !contents.ends_with('\n') => {
+                    warn!("Removing incomplete final signing journal entry {e}");
+                    let complete = contents.rfind('\n').map(|i| i + 1).unwrap_or(0);
+                    fs::write(&path, &contents[..complete])?;
+                }
+                Err(e) => {
+                    return Err(GravityError::IoError(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "Corrupt signing journal {} line {} {e}",
+                            path.display(),
+                            i + 1
+                        ),
+                    )))
+                }
+            }
+        }
+        Ok(journal)
+    }
+
+    /// Checks that signing this checkpoint would not conflict with any previous signature
+    pub fn check(&self, checkpoint: &SignedCheckpoint) -> Result<(), SigningConflict> {
+        match self.signed.get(&checkpoint.key()) {
+            Some(signed_hash) if *signed_hash != checkpoint.checkpoint_hash => {
+                Err(SigningConflict {
+                    requested: checkpoint.clone(),
+                    signed_hash: signed_hash.clone(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the checkpoint and records it in the journal, this must succeed before the checkpoint is signed
+    pub fn record(&mut self, checkpoint: SignedCheckpoint) -> Result<(), GravityError> {
+        if let Err(conflict) = self.check(&checkpoint) {
+            return Err(GravityError::InvalidBridgeStateError(conflict.to_string()));
+        }
+        if self.signed.contains_key(&checkpoint.key()) {
+            return Ok(());
+        }
+        if let Some(path) = &self.path {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            let mut line = serde_json::to_string(&checkpoint).map_err(std::io::Error::from)?;
+            line.push('\n');
+            file.write_all(line.as_bytes())?;
+            file.sync_all()?;
+        }
+        self.signed
+            .insert(checkpoint.key(), checkpoint.checkpoint_hash);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkpoint(nonce: u64, scope: &str, hash: &str) -> SignedCheckpoint {
+        SignedCheckpoint {
+            checkpoint_type: CheckpointType::Batch,
+            nonce,
+            scope: scope.to_string(),
+            checkpoint_hash: hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_signing_journal() {
+        let path = std::env::temp_dir().join(format!(
+            "gravity_test_signing_journal_{}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        let mut journal = SigningJournal::load(Some(path.clone())).unwrap();
+        journal.record(checkpoint(1, "a", "0x01")).unwrap();
+        journal.record(checkpoint(1, "b", "0x02")).unwrap();
+        // signing the same checkpoint again is fine
+        journal.record(checkpoint(1, "a", "0x01")).unwrap();
+        assert!(journal.check(&checkpoint(1, "a", "0x03")).is_err());
+        assert!(journal.record(checkpoint(1, "a", "0x03")).is_err());
+
+        // the journal survives a restart, including a partially written final entry
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"checkpoint_type\":\"ba").unwrap();
+        let mut journal = SigningJournal::load(Some(path.clone())).unwrap();
+        journal.record(checkpoint(2, "a", "0x05")).unwrap();
+        let journal = SigningJournal::load(Some(path.clone())).unwrap();
+        assert!(journal.check(&checkpoint(2, "a", "0x06")).is_err());
+        let conflict = journal.check(&checkpoint(1, "b", "0x04")).unwrap_err();
+        assert_eq!(conflict.signed_hash, "0x02");
+        assert!(journal.check(&checkpoint(2, "b", "0x04")).is_ok());
+        fs::remove_file(&path).unwrap();
+    }
+}
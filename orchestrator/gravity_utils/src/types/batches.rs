@@ -43,6 +43,9 @@ pub struct TransactionBatch {
     /// the ERC20 token contract shared by all transactions
     /// and fees in this batch
     pub token_contract: EthAddress,
+    /// the Cosmos block height at which this batch was created
+    #[serde(default)]
+    pub cosmos_block_created: u64,
 }
 
 impl TransactionBatch {
@@ -179,6 +182,7 @@ impl TryFrom<gravity_proto::gravity::v1::OutgoingTxBatch> for TransactionBatch {
                 transactions,
                 token_contract: total_fee.token_contract_address,
                 total_fee,
+                cosmos_block_created: input.cosmos_block_created,
             })
         } else {
             Err(GravityError::InvalidBridgeStateError(
@@ -256,7 +260,7 @@ impl Into<gravity_proto::gravity::v1::OutgoingTxBatch> for &TransactionBatch {
             batch_timeout: self.batch_timeout,
             transactions: self.transactions.iter().map(|v| v.into()).collect(),
             token_contract: self.token_contract.to_string(),
-            cosmos_block_created: 0,
+            cosmos_block_created: self.cosmos_block_created,
         }
     }
 }
@@ -1,6 +1,9 @@
 //! contains configuration structs that need to be accessed across crates.
 
+use crate::num_conversion::fraction_to_exponent;
 use clarity::{Address as EthAddress, Uint256};
+use std::convert::{TryFrom, TryInto};
+use std::sync::{Arc, RwLock};
 
 /// Global configuration struct for Gravity bridge tools
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default, Clone)]
@@ -21,13 +24,15 @@ pub struct TomlGravityBridgeToolsConfig {
     pub metrics: MetricsConfig,
 }
 
-impl From<TomlGravityBridgeToolsConfig> for GravityBridgeToolsConfig {
-    fn from(input: TomlGravityBridgeToolsConfig) -> Self {
-        GravityBridgeToolsConfig {
-            relayer: input.relayer.into(),
+impl TryFrom<TomlGravityBridgeToolsConfig> for GravityBridgeToolsConfig {
+    type Error = String;
+
+    fn try_from(input: TomlGravityBridgeToolsConfig) -> Result<Self, String> {
+        Ok(GravityBridgeToolsConfig {
+            relayer: input.relayer.try_into()?,
             orchestrator: input.orchestrator,
             metrics: input.metrics,
-        }
+        })
     }
 }
 
@@ -62,10 +67,44 @@ pub struct RelayerConfig {
     /// acceptable gas prices are determined by the samples in the gas tracker, so both
     /// gas_tracker_loop_speed and altruistic_gas_price_samples will play a role in this decision
     pub altruistic_acceptable_gas_price_percentage: f32,
+    /// fills the gas price history from recent blocks with eth_feeHistory when the relayer starts
+    /// without enough saved samples, so that altruistic relaying does not have to wait for samples
+    pub gas_history_backfill: bool,
+    /// raises the percentage of gas prices altruistic relaying accepts as batches approach their
+    /// timeout and as valsets and batches wait longer, if not set urgency is not considered
+    pub altruistic_urgency: Option<AltruisticUrgency>,
     /// the speed at which the relayer checks for pending ibc auto forwards, in seconds
     pub ibc_auto_forward_loop_speed: u64,
     /// the number of pending ibc auto forwards to attempt to execute per loop
     pub ibc_auto_forwards_to_execute: u64,
+    /// how the EIP-1559 max fee and priority fee of relayed transactions are determined
+    pub fee_strategy: FeeStrategy,
+    /// a hard ceiling on the max fee per gas, in wei, of relayed transactions. Nothing is
+    /// relayed while the fee strategy calls for more than this
+    pub max_fee_per_gas_cap: Option<Uint256>,
+    /// the time in seconds a relayed transaction may wait for inclusion before it is replaced
+    /// with the same transaction at higher fees
+    pub tx_bump_interval: u64,
+    /// the fraction both fees are increased by when replacing a transaction, between 0.1 and
+    /// 10.0 as Ethereum clients require at least 0.1
+    pub tx_bump_percentage: f32,
+    /// where signed relay transactions are sent, the public mempool of the Ethereum node
+    /// or a private relay that protects them from front running
+    pub submission_mode: SubmissionMode,
+    /// where the WETH value of relaying rewards is obtained when judging profitability
+    pub price_source: PriceSource,
+    /// the time in seconds a price is reused for before it is queried again
+    pub price_cache_ttl: u64,
+    /// the most gas the batches submitted in a single relayer iteration may use in total, the
+    /// most profitable batches that fit are submitted
+    pub batch_gas_budget: Option<u64>,
+    /// the relayer pauses all submissions while a submission would leave less than this many wei
+    /// in its wallet, and resumes once it is topped up
+    pub min_eth_balance: Option<Uint256>,
+    /// the most wei the relayer may spend on gas in any hour, submissions pause once it is reached
+    pub max_spend_per_hour: Option<Uint256>,
+    /// the most wei the relayer may spend on gas in any day, submissions pause once it is reached
+    pub max_spend_per_day: Option<Uint256>,
 }
 
 /// Relayer configuration that's is more easily parsable with toml
@@ -91,17 +130,99 @@ pub struct TomlRelayerConfig {
     pub altruistic_gas_price_samples: u64,
     #[serde(default = "default_altruistic_acceptable_gas_price_percentage")]
     pub altruistic_acceptable_gas_price_percentage: f32,
+    #[serde(default)]
+    pub gas_history_backfill: bool,
+    #[serde(default)]
+    pub altruistic_urgency: Option<AltruisticUrgency>,
     #[serde(default = "default_ibc_auto_forward_loop_speed")]
     pub ibc_auto_forward_loop_speed: u64,
     #[serde(default = "default_ibc_auto_forwards_to_execute")]
     pub ibc_auto_forwards_to_execute: u64,
-}
-
-impl From<TomlRelayerConfig> for RelayerConfig {
-    fn from(input: TomlRelayerConfig) -> Self {
-        RelayerConfig {
-            valset_relaying_mode: input.valset_relaying_mode.into(),
-            batch_relaying_mode: input.batch_relaying_mode.into(),
+    #[serde(default = "default_fee_strategy")]
+    pub fee_strategy: TomlFeeStrategy,
+    /// in gwei
+    #[serde(default = "default_max_fee_per_gas_cap")]
+    pub max_fee_per_gas_cap: Option<f64>,
+    #[serde(default = "default_tx_bump_interval")]
+    pub tx_bump_interval: u64,
+    #[serde(default = "default_tx_bump_percentage")]
+    pub tx_bump_percentage: f32,
+    #[serde(default = "default_submission_mode")]
+    pub submission_mode: TomlSubmissionMode,
+    #[serde(default = "default_price_source")]
+    pub price_source: TomlPriceSource,
+    #[serde(default = "default_price_cache_ttl")]
+    pub price_cache_ttl: u64,
+    #[serde(default = "default_batch_gas_budget")]
+    pub batch_gas_budget: Option<u64>,
+    /// in ETH
+    #[serde(default)]
+    pub min_eth_balance: Option<f64>,
+    /// in ETH
+    #[serde(default)]
+    pub max_spend_per_hour: Option<f64>,
+    /// in ETH
+    #[serde(default)]
+    pub max_spend_per_day: Option<f64>,
+}
+
+impl TryFrom<TomlRelayerConfig> for RelayerConfig {
+    type Error = String;
+
+    /// Converts the config, checking that its values are usable so that an invalid reloaded
+    /// config can be rejected without affecting the running relayer
+    fn try_from(input: TomlRelayerConfig) -> Result<Self, String> {
+        if let Some(urgency) = &input.altruistic_urgency {
+            urgency.validate()?;
+        }
+        if let Some(cap) = input.max_fee_per_gas_cap {
+            if cap <= 0.0 {
+                return Err(format!(
+                    "Invalid max_fee_per_gas_cap ({cap}): must be positive"
+                ));
+            }
+        }
+        for (name, limit) in [
+            ("min_eth_balance", input.min_eth_balance),
+            ("max_spend_per_hour", input.max_spend_per_hour),
+            ("max_spend_per_day", input.max_spend_per_day),
+        ] {
+            if let Some(limit) = limit {
+                if limit < 0.0 {
+                    return Err(format!("Invalid {name} ({limit}): must not be negative"));
+                }
+            }
+        }
+        if input.batch_gas_budget == Some(0) {
+            return Err("batch_gas_budget must be greater than zero".to_string());
+        }
+        if input.tx_bump_interval == 0 {
+            return Err("tx_bump_interval must be greater than zero".to_string());
+        }
+        if !(0.1..=10.0).contains(&input.tx_bump_percentage) {
+            return Err(format!(
+                "Invalid tx_bump_percentage ({}): must be between 0.1 and 10.0",
+                input.tx_bump_percentage
+            ));
+        }
+        if input.relayer_loop_speed == 0 || input.ibc_auto_forward_loop_speed == 0 {
+            return Err("Relayer loop speeds must be greater than zero".to_string());
+        }
+        if input.gas_tracker_loop_speed == 0 || input.gas_tracker_loop_speed > 60 {
+            return Err(format!(
+                "Invalid gas_tracker_loop_speed ({}): must be between 1 and 60 seconds",
+                input.gas_tracker_loop_speed
+            ));
+        }
+        if !(0.0..=1.0).contains(&input.altruistic_acceptable_gas_price_percentage) {
+            return Err(format!(
+                "Invalid altruistic_acceptable_gas_price_percentage ({}): must be between 0 and 1",
+                input.altruistic_acceptable_gas_price_percentage
+            ));
+        }
+        Ok(RelayerConfig {
+            valset_relaying_mode: input.valset_relaying_mode.try_into()?,
+            batch_relaying_mode: input.batch_relaying_mode.try_into()?,
             batch_request_mode: input.batch_request_mode,
             logic_call_market_enabled: input.logic_call_market_enabled,
             relayer_loop_speed: input.relayer_loop_speed,
@@ -111,12 +232,249 @@ impl From<TomlRelayerConfig> for RelayerConfig {
             altruistic_gas_price_samples: input.altruistic_gas_price_samples,
             altruistic_acceptable_gas_price_percentage: input
                 .altruistic_acceptable_gas_price_percentage,
+            gas_history_backfill: input.gas_history_backfill,
+            altruistic_urgency: input.altruistic_urgency,
             ibc_auto_forward_loop_speed: input.ibc_auto_forward_loop_speed,
             ibc_auto_forwards_to_execute: input.ibc_auto_forwards_to_execute,
+            fee_strategy: input.fee_strategy.try_into()?,
+            max_fee_per_gas_cap: input.max_fee_per_gas_cap.map(gwei_to_wei),
+            tx_bump_interval: input.tx_bump_interval,
+            tx_bump_percentage: input.tx_bump_percentage,
+            submission_mode: input.submission_mode.try_into()?,
+            price_source: input.price_source.try_into()?,
+            price_cache_ttl: input.price_cache_ttl,
+            batch_gas_budget: input.batch_gas_budget,
+            min_eth_balance: input.min_eth_balance.map(eth_to_wei),
+            max_spend_per_hour: input.max_spend_per_hour.map(eth_to_wei),
+            max_spend_per_day: input.max_spend_per_day.map(eth_to_wei),
+        })
+    }
+}
+
+/// How the relayer determines the EIP-1559 fees of the transactions it submits
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum FeeStrategy {
+    /// The max fee is the node's suggested gas price plus 20% and the priority fee is
+    /// left to the node
+    NodeSuggested,
+    /// Fixed fees, in wei
+    Fixed {
+        max_fee_per_gas: Uint256,
+        max_priority_fee_per_gas: Uint256,
+    },
+    /// The priority fee is the given percentile of the priority fees paid over the last
+    /// `blocks` blocks, the max fee is twice the next base fee plus the priority fee, enough
+    /// to remain includable through six consecutive full blocks
+    FeeHistory { blocks: u64, percentile: f32 },
+    /// The max fee is the latest gas tracker sample times `multiplier`, the priority fee
+    /// is left to the node
+    GasTracker { multiplier: f32 },
+}
+
+/// A version of FeeStrategy that is easy to serialize as toml, fees are in gwei
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TomlFeeStrategy {
+    mode: String,
+    max_fee_per_gas: Option<f64>,
+    max_priority_fee_per_gas: Option<f64>,
+    blocks: Option<u64>,
+    percentile: Option<f32>,
+    multiplier: Option<f32>,
+}
+
+impl TryFrom<TomlFeeStrategy> for FeeStrategy {
+    type Error = String;
+
+    fn try_from(input: TomlFeeStrategy) -> Result<Self, String> {
+        match input.mode.as_str() {
+            "NodeSuggested" | "nodesuggested" | "NODESUGGESTED" => Ok(FeeStrategy::NodeSuggested),
+            "Fixed" | "fixed" | "FIXED" => match (input.max_fee_per_gas, input.max_priority_fee_per_gas) {
+                (Some(max), Some(priority)) if priority >= 0.0 && max >= priority && max > 0.0 => {
+                    Ok(FeeStrategy::Fixed {
+                        max_fee_per_gas: gwei_to_wei(max),
+                        max_priority_fee_per_gas: gwei_to_wei(priority),
+                    })
+                }
+                _ => Err("fee_strategy Fixed requires a max_fee_per_gas greater than its max_priority_fee_per_gas".to_string()),
+            },
+            "FeeHistory" | "feehistory" | "FEEHISTORY" => match (input.blocks, input.percentile) {
+                (Some(blocks), Some(percentile))
+                    if (1..=1024).contains(&blocks) && (0.0..=100.0).contains(&percentile) =>
+                {
+                    Ok(FeeStrategy::FeeHistory { blocks, percentile })
+                }
+                _ => Err("fee_strategy FeeHistory requires blocks between 1 and 1024 and a percentile between 0 and 100".to_string()),
+            },
+            "GasTracker" | "gastracker" | "GASTRACKER" => match input.multiplier {
+                Some(multiplier) if multiplier >= 1.0 => Ok(FeeStrategy::GasTracker { multiplier }),
+                _ => Err("fee_strategy GasTracker requires a multiplier of at least 1".to_string()),
+            },
+            v => Err(format!("Invalid fee_strategy {v}")),
+        }
+    }
+}
+
+/// Where the relayer sends its signed transactions
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum SubmissionMode {
+    /// Transactions are sent to the configured Ethereum node and enter the public mempool
+    Public,
+    /// Transactions are sent to a private relay with eth_sendPrivateTransaction, if one is
+    /// not included within `fallback_blocks` blocks it is sent to the public mempool
+    PrivateTransaction {
+        endpoint: String,
+        fallback_blocks: u64,
+    },
+    /// Transactions are sent to a private relay as single transaction bundles with
+    /// eth_sendBundle, one for each of the next `fallback_blocks` blocks (at most 25), after
+    /// which a transaction that is still not included is sent to the public mempool
+    Bundle {
+        endpoint: String,
+        fallback_blocks: u64,
+    },
+}
+
+/// A version of SubmissionMode that is easy to serialize as toml
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct TomlSubmissionMode {
+    mode: String,
+    endpoint: Option<String>,
+    fallback_blocks: Option<u64>,
+}
+
+impl TryFrom<TomlSubmissionMode> for SubmissionMode {
+    type Error = String;
+
+    fn try_from(input: TomlSubmissionMode) -> Result<Self, String> {
+        let private = match input.mode.as_str() {
+            "Public" | "public" | "PUBLIC" => return Ok(SubmissionMode::Public),
+            "PrivateTransaction" | "privatetransaction" | "PRIVATETRANSACTION" => true,
+            "Bundle" | "bundle" | "BUNDLE" => false,
+            v => return Err(format!("Invalid submission_mode {v}")),
+        };
+        match (input.endpoint, input.fallback_blocks) {
+            (Some(endpoint), Some(fallback_blocks))
+                if endpoint.starts_with("http") && (1..=100).contains(&fallback_blocks) =>
+            {
+                if private {
+                    Ok(SubmissionMode::PrivateTransaction {
+                        endpoint,
+                        fallback_blocks,
+                    })
+                } else {
+                    Ok(SubmissionMode::Bundle {
+                        endpoint,
+                        fallback_blocks,
+                    })
+                }
+            }
+            _ => Err(format!(
+                "submission_mode {} requires an http(s) endpoint and fallback_blocks between 1 and 100",
+                input.mode
+            )),
+        }
+    }
+}
+
+/// Where the relayer obtains the WETH value of a token amount
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub enum PriceSource {
+    /// Swap prices from Uniswap v2 and v3 on Ethereum mainnet, through a stablecoin if the token
+    /// has no WETH pool
+    Uniswap,
+    /// Fixed prices from the config, tokens not listed have no price
+    Static { prices: Vec<WhitelistToken> },
+    /// An HTTP price feed, `url` is queried with `{token}` replaced by the token address and must
+    /// respond with json of the form {"price": "<wei per coin>", "decimals": <token decimals>}
+    HttpFeed { url: String },
+    /// The median of the prices from the sources that return one
+    Median { sources: Vec<PriceSource> },
+    /// The lowest of the prices from the sources that return one
+    Min { sources: Vec<PriceSource> },
+}
+
+/// A version of PriceSource that is easy to serialize as toml
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct TomlPriceSource {
+    mode: String,
+    prices: Option<Vec<WhitelistToken>>,
+    url: Option<String>,
+    sources: Option<Vec<TomlPriceSource>>,
+}
+
+impl TryFrom<TomlPriceSource> for PriceSource {
+    type Error = String;
+
+    fn try_from(input: TomlPriceSource) -> Result<Self, String> {
+        match input.mode.as_str() {
+            "Uniswap" | "uniswap" | "UNISWAP" => Ok(PriceSource::Uniswap),
+            "Static" | "static" | "STATIC" => match input.prices {
+                Some(prices) => Ok(PriceSource::Static { prices }),
+                None => Err("price_source Static requires prices".to_string()),
+            },
+            "HttpFeed" | "httpfeed" | "HTTPFEED" => match input.url {
+                Some(url) if url.starts_with("http") && url.contains("{token}") => {
+                    Ok(PriceSource::HttpFeed { url })
+                }
+                _ => Err(
+                    "price_source HttpFeed requires an http(s) url containing {token}".to_string(),
+                ),
+            },
+            "Median" | "median" | "MEDIAN" | "Min" | "min" | "MIN" => {
+                let sources = match input.sources {
+                    Some(sources) if !sources.is_empty() => sources
+                        .into_iter()
+                        .map(PriceSource::try_from)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    _ => return Err(format!("price_source {} requires sources", input.mode)),
+                };
+                if input.mode.eq_ignore_ascii_case("median") {
+                    Ok(PriceSource::Median { sources })
+                } else {
+                    Ok(PriceSource::Min { sources })
+                }
+            }
+            v => Err(format!("Invalid price_source {v}")),
         }
     }
 }
 
+fn gwei_to_wei(gwei: f64) -> Uint256 {
+    ((gwei * 1_000_000_000f64) as u128).into()
+}
+
+fn eth_to_wei(eth: f64) -> Uint256 {
+    fraction_to_exponent(eth, 18)
+}
+
+/// A RelayerConfig shared between the relayer loops that may be replaced while they run, each loop
+/// takes a snapshot with get() at the start of every iteration so that a replacement is picked up
+/// on the next iteration without restarting the relayer or losing its state
+#[derive(Debug, Clone)]
+pub struct SharedRelayerConfig(Arc<RwLock<RelayerConfig>>);
+
+impl SharedRelayerConfig {
+    pub fn new(config: RelayerConfig) -> SharedRelayerConfig {
+        SharedRelayerConfig(Arc::new(RwLock::new(config)))
+    }
+
+    /// A snapshot of the current config
+    pub fn get(&self) -> RelayerConfig {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Atomically replaces the config, returning the previous one
+    pub fn replace(&self, config: RelayerConfig) -> RelayerConfig {
+        std::mem::replace(&mut *self.0.write().unwrap(), config)
+    }
+}
+
+impl From<RelayerConfig> for SharedRelayerConfig {
+    fn from(input: RelayerConfig) -> Self {
+        SharedRelayerConfig::new(input)
+    }
+}
+
 /// The various possible modes for relaying validator set updates
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
 pub enum ValsetRelayingMode {
@@ -139,17 +497,18 @@ pub struct TomlValsetRelayingMode {
     margin: Option<f32>,
 }
 
-impl From<TomlValsetRelayingMode> for ValsetRelayingMode {
-    fn from(input: TomlValsetRelayingMode) -> Self {
+impl TryFrom<TomlValsetRelayingMode> for ValsetRelayingMode {
+    type Error = String;
+
+    fn try_from(input: TomlValsetRelayingMode) -> Result<Self, String> {
         match input.mode.as_str() {
-            "ProfitableOnly" | "profitableonly" | "PROFITABLEONLY" => {
-                ValsetRelayingMode::ProfitableOnly {
-                    margin: input.margin.unwrap(),
-                }
-            }
-            "Altruistic" | "altruistic" | "ALTRUISTIC" => ValsetRelayingMode::Altruistic,
-            "EveryValset" | "everyvalset" | "EVERYVALSET" => ValsetRelayingMode::EveryValset,
-            _ => panic!("Invalid TomlValsetRelayingMode"),
+            "ProfitableOnly" | "profitableonly" | "PROFITABLEONLY" => match input.margin {
+                Some(margin) => Ok(ValsetRelayingMode::ProfitableOnly { margin }),
+                None => Err("valset_relaying_mode ProfitableOnly requires a margin".to_string()),
+            },
+            "Altruistic" | "altruistic" | "ALTRUISTIC" => Ok(ValsetRelayingMode::Altruistic),
+            "EveryValset" | "everyvalset" | "EVERYVALSET" => Ok(ValsetRelayingMode::EveryValset),
+            v => Err(format!("Invalid valset_relaying_mode {v}")),
         }
     }
 }
@@ -169,9 +528,51 @@ pub enum BatchRequestMode {
     None,
 }
 
+/// How altruistic relaying weighs urgency. An item's urgency rises from 0 to 1 as a batch comes within
+/// `timeout_blocks` blocks of its timeout, or as a valset or batch waits up to `max_wait` seconds to be
+/// relayed, counted from the time of the Cosmos block it was created in, whichever is higher. The percentage of gas prices accepted rises from
+/// altruistic_acceptable_gas_price_percentage to `max_percentage` with urgency raised to `exponent`,
+/// an exponent above 1 only pays higher prices once an item is close to timing out
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct AltruisticUrgency {
+    pub timeout_blocks: u64,
+    pub max_wait: u64,
+    pub max_percentage: f32,
+    #[serde(default = "default_urgency_exponent")]
+    pub exponent: f32,
+}
+
+fn default_urgency_exponent() -> f32 {
+    1.0
+}
+
+impl AltruisticUrgency {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.timeout_blocks == 0 || self.max_wait == 0 {
+            return Err(
+                "altruistic_urgency timeout_blocks and max_wait must be greater than zero"
+                    .to_string(),
+            );
+        }
+        if !(0.0..=1.0).contains(&self.max_percentage) {
+            return Err(format!(
+                "Invalid altruistic_urgency max_percentage ({}): must be between 0 and 1",
+                self.max_percentage
+            ));
+        }
+        if self.exponent <= 0.0 {
+            return Err(format!(
+                "Invalid altruistic_urgency exponent ({}): must be positive",
+                self.exponent
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// A whitelisted token that will be relayed given the batch
 /// provides at least amount of this specific token
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct WhitelistToken {
     /// the price of this token, denominated in weth per coin
     pub price: Uint256,
@@ -213,23 +614,29 @@ pub struct TomlBatchRelayingMode {
     whitelist: Option<Vec<WhitelistToken>>,
 }
 
-impl From<TomlBatchRelayingMode> for BatchRelayingMode {
-    fn from(input: TomlBatchRelayingMode) -> Self {
+impl TryFrom<TomlBatchRelayingMode> for BatchRelayingMode {
+    type Error = String;
+
+    fn try_from(input: TomlBatchRelayingMode) -> Result<Self, String> {
         match input.mode.as_str() {
-            "Altruistic" | "altruistic" | "ALTRUISTIC" => BatchRelayingMode::Altruistic,
-            "EveryBatch" | "everybatch" | "EVERYBATCH" => BatchRelayingMode::EveryBatch,
-            "ProfitableOnly" | "profitableonly" | "PROFITABLEONLY" => {
-                BatchRelayingMode::ProfitableOnly {
-                    margin: input.margin.unwrap(),
-                }
-            }
+            "Altruistic" | "altruistic" | "ALTRUISTIC" => Ok(BatchRelayingMode::Altruistic),
+            "EveryBatch" | "everybatch" | "EVERYBATCH" => Ok(BatchRelayingMode::EveryBatch),
+            "ProfitableOnly" | "profitableonly" | "PROFITABLEONLY" => match input.margin {
+                Some(margin) => Ok(BatchRelayingMode::ProfitableOnly { margin }),
+                None => Err("batch_relaying_mode ProfitableOnly requires a margin".to_string()),
+            },
             "ProfitableWithWhitelist" | "profitablewithwhitelist" | "PROFITABLEWITHWHITELIST" => {
-                BatchRelayingMode::ProfitableWithWhitelist {
-                    margin: input.margin.unwrap(),
-                    whitelist: input.whitelist.unwrap(),
+                match (input.margin, input.whitelist) {
+                    (Some(margin), Some(whitelist)) => {
+                        Ok(BatchRelayingMode::ProfitableWithWhitelist { margin, whitelist })
+                    }
+                    _ => Err(
+                        "batch_relaying_mode ProfitableWithWhitelist requires a margin and whitelist"
+                            .to_string(),
+                    ),
                 }
             }
-            _ => panic!("Bad TomlBatchRelayingMode"),
+            v => Err(format!("Invalid batch_relaying_mode {v}")),
         }
     }
 }
@@ -289,12 +696,60 @@ fn default_ibc_auto_forwards_to_execute() -> u64 {
     50
 }
 
+fn default_fee_strategy() -> TomlFeeStrategy {
+    TomlFeeStrategy {
+        mode: "NodeSuggested".to_string(),
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
+        blocks: None,
+        percentile: None,
+        multiplier: None,
+    }
+}
+
+fn default_max_fee_per_gas_cap() -> Option<f64> {
+    None
+}
+
+fn default_tx_bump_interval() -> u64 {
+    120
+}
+
+fn default_tx_bump_percentage() -> f32 {
+    0.15
+}
+
+fn default_price_source() -> TomlPriceSource {
+    TomlPriceSource {
+        mode: "Uniswap".to_string(),
+        prices: None,
+        url: None,
+        sources: None,
+    }
+}
+
+fn default_price_cache_ttl() -> u64 {
+    30
+}
+
+fn default_batch_gas_budget() -> Option<u64> {
+    None
+}
+
+fn default_submission_mode() -> TomlSubmissionMode {
+    TomlSubmissionMode {
+        mode: "Public".to_string(),
+        endpoint: None,
+        fallback_blocks: None,
+    }
+}
+
 impl Default for RelayerConfig {
     fn default() -> Self {
         RelayerConfig {
-            valset_relaying_mode: default_valset_relaying_mode().into(),
+            valset_relaying_mode: ValsetRelayingMode::Altruistic,
             batch_request_mode: default_batch_request_mode(),
-            batch_relaying_mode: default_batch_relaying_mode().into(),
+            batch_relaying_mode: BatchRelayingMode::ProfitableOnly { margin: 1.1 },
             logic_call_market_enabled: default_logic_call_market_enabled(),
             relayer_loop_speed: default_relayer_loop_speed(),
             gas_tracker_loop_speed: default_gas_tracker_loop_speed(),
@@ -304,8 +759,21 @@ impl Default for RelayerConfig {
             altruistic_gas_price_samples: default_altruistic_gas_price_samples(),
             altruistic_acceptable_gas_price_percentage:
                 default_altruistic_acceptable_gas_price_percentage(),
+            gas_history_backfill: false,
+            altruistic_urgency: None,
             ibc_auto_forward_loop_speed: default_ibc_auto_forward_loop_speed(),
             ibc_auto_forwards_to_execute: default_ibc_auto_forwards_to_execute(),
+            fee_strategy: FeeStrategy::NodeSuggested,
+            max_fee_per_gas_cap: default_max_fee_per_gas_cap().map(gwei_to_wei),
+            tx_bump_interval: default_tx_bump_interval(),
+            tx_bump_percentage: default_tx_bump_percentage(),
+            submission_mode: SubmissionMode::Public,
+            price_source: PriceSource::Uniswap,
+            price_cache_ttl: default_price_cache_ttl(),
+            batch_gas_budget: default_batch_gas_budget(),
+            min_eth_balance: None,
+            max_spend_per_hour: None,
+            max_spend_per_day: None,
         }
     }
 }
@@ -325,14 +793,27 @@ impl Default for TomlRelayerConfig {
             altruistic_gas_price_samples: default_altruistic_gas_price_samples(),
             altruistic_acceptable_gas_price_percentage:
                 default_altruistic_acceptable_gas_price_percentage(),
+            gas_history_backfill: false,
+            altruistic_urgency: None,
             ibc_auto_forward_loop_speed: default_ibc_auto_forward_loop_speed(),
             ibc_auto_forwards_to_execute: default_ibc_auto_forwards_to_execute(),
+            fee_strategy: default_fee_strategy(),
+            max_fee_per_gas_cap: default_max_fee_per_gas_cap(),
+            tx_bump_interval: default_tx_bump_interval(),
+            tx_bump_percentage: default_tx_bump_percentage(),
+            submission_mode: default_submission_mode(),
+            price_source: default_price_source(),
+            price_cache_ttl: default_price_cache_ttl(),
+            batch_gas_budget: default_batch_gas_budget(),
+            min_eth_balance: None,
+            max_spend_per_hour: None,
+            max_spend_per_day: None,
         }
     }
 }
 
 /// Orchestrator configuration options
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct OrchestratorConfig {
     /// If this Orchestrator should run an integrated relayer or not
     #[serde(default = "default_relayer_enabled")]
@@ -340,6 +821,15 @@ pub struct OrchestratorConfig {
     /// Whether to check that the ethereum node supports "finalized" blocks
     #[serde(default = "default_check_eth_rpc")]
     pub check_eth_rpc: bool,
+    /// Additional Ethereum RPC endpoints used by the Oracle to cross check the primary
+    /// Ethereum RPC, event logs and safe blocks are only acted upon if `eth_rpc_quorum`
+    /// endpoints agree on them
+    #[serde(default = "default_eth_rpc_quorum_endpoints")]
+    pub eth_rpc_quorum_endpoints: Vec<String>,
+    /// The number of Ethereum RPC endpoints, including the primary, that must agree,
+    /// zero indicates a simple majority. It may not be larger than the number of endpoints
+    #[serde(default = "default_eth_rpc_quorum")]
+    pub eth_rpc_quorum: usize,
 }
 
 fn default_relayer_enabled() -> bool {
@@ -350,11 +840,21 @@ fn default_check_eth_rpc() -> bool {
     true
 }
 
+fn default_eth_rpc_quorum_endpoints() -> Vec<String> {
+    Vec::new()
+}
+
+fn default_eth_rpc_quorum() -> usize {
+    0
+}
+
 impl Default for OrchestratorConfig {
     fn default() -> Self {
         OrchestratorConfig {
             relayer_enabled: default_relayer_enabled(),
             check_eth_rpc: default_check_eth_rpc(),
+            eth_rpc_quorum_endpoints: default_eth_rpc_quorum_endpoints(),
+            eth_rpc_quorum: default_eth_rpc_quorum(),
         }
     }
 }
@@ -7,9 +7,11 @@
 // TODO this file needs static assertions that prevent it from compiling on 16 bit systems.
 // we assume a system bit width of at least 32
 
+use super::event_signatures::*;
 use super::ValsetMember;
 use crate::error::GravityError;
 use crate::num_conversion::downcast_uint256;
+use clarity::abi::derive_signature;
 use clarity::constants::zero_address;
 use clarity::Address as EthAddress;
 use deep_space::utils::bytes_to_hex_str;
@@ -634,6 +636,83 @@ impl EthereumEvent for SendToCosmosEvent {
     }
 }
 
+/// A parsed struct representing the Ethereum event fired when someone deposits an ERC721 token
+/// on the GravityERC721 contract. These deposits are not observed by the Oracle, the token is
+/// simply held by the GravityERC721 contract
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SendErc721ToCosmosEvent {
+    /// The ERC721 contract of the deposited token
+    pub erc721: EthAddress,
+    /// The Ethereum Sender
+    pub sender: EthAddress,
+    /// The Cosmos destination, this is a raw value from the Ethereum contract
+    /// and therefore could be provided by an attacker
+    pub destination: String,
+    /// the destination parsed as a valid Bech32 Cosmos address, if this is not possible
+    /// the value is none
+    pub validated_destination: Option<CosmosAddress>,
+    /// The id of the deposited token
+    pub token_id: Uint256,
+    /// The GravityERC721 contract's own event nonce, unrelated to the Gravity contract event nonce
+    pub event_nonce: u64,
+    /// The block height this event occurred at
+    pub block_height: Uint256,
+}
+
+impl SendErc721ToCosmosEvent {
+    /// Parses an event out of an Ethereum Log, the event data is laid out like SendToCosmosEvent
+    /// with the token id in place of the amount
+    pub fn from_log(input: &Log) -> Result<SendErc721ToCosmosEvent, GravityError> {
+        let (erc721_data, sender_data) = match (input.topics.get(1), input.topics.get(2)) {
+            (Some(erc721), Some(sender)) => (erc721, sender),
+            _ => {
+                return Err(GravityError::InvalidEventLogError(
+                    "Too few topics".to_string(),
+                ))
+            }
+        };
+        let erc721 = EthAddress::from_slice(&erc721_data[12..32])?;
+        let sender = EthAddress::from_slice(&sender_data[12..32])?;
+        let block_height = match input.block_number {
+            Some(bn) => bn,
+            None => {
+                return Err(GravityError::InvalidEventLogError(
+                    "Log does not have block number, we only search logs already in blocks?"
+                        .to_string(),
+                ))
+            }
+        };
+
+        let data = SendToCosmosEvent::decode_data_bytes(&input.data)?;
+        let event_nonce = match downcast_uint256(data.event_nonce) {
+            Some(v) => v,
+            None => {
+                return Err(GravityError::InvalidEventLogError(
+                    "Event nonce overflow, probably incorrect parsing".to_string(),
+                ))
+            }
+        };
+        Ok(SendErc721ToCosmosEvent {
+            erc721,
+            sender,
+            validated_destination: data.destination.parse().ok(),
+            destination: data.destination,
+            token_id: data.amount,
+            event_nonce,
+            block_height,
+        })
+    }
+
+    /// Parses multiple events out of Ethereum Logs
+    pub fn from_logs(input: &[Log]) -> Result<Vec<SendErc721ToCosmosEvent>, GravityError> {
+        let mut res = Vec::new();
+        for item in input {
+            res.push(SendErc721ToCosmosEvent::from_log(item)?);
+        }
+        Ok(res)
+    }
+}
+
 /// A parsed struct representing the Ethereum event fired when someone uses the Gravity
 /// contract to deploy a new ERC20 contract representing a Cosmos asset
 #[derive(Serialize, Deserialize, Debug, Default, Clone, Eq, PartialEq, Hash)]
@@ -1047,6 +1126,104 @@ impl EthereumEvent for LogicCallExecutedEvent {
     }
 }
 
+/// Any one of the events emitted by the Gravity contract, used to dispatch logs
+/// fetched with a single query for all event signatures to the correct parser
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum GravityEvent {
+    ValsetUpdated(ValsetUpdatedEvent),
+    TransactionBatchExecuted(TransactionBatchExecutedEvent),
+    SendToCosmos(SendToCosmosEvent),
+    Erc20Deployed(Erc20DeployedEvent),
+    LogicCallExecuted(LogicCallExecutedEvent),
+}
+
+impl GravityEvent {
+    /// Parses a log into the event type matching its signature topic
+    pub fn from_log(input: &Log) -> Result<GravityEvent, GravityError> {
+        let topic: &[u8] = match input.topics.first() {
+            Some(t) => &t[..],
+            None => {
+                return Err(GravityError::InvalidEventLogError(
+                    "Log has no signature topic".to_string(),
+                ))
+            }
+        };
+        if topic == derive_signature(VALSET_UPDATED_EVENT_SIG)? {
+            Ok(GravityEvent::ValsetUpdated(ValsetUpdatedEvent::from_log(
+                input,
+            )?))
+        } else if topic == derive_signature(TRANSACTION_BATCH_EXECUTED_EVENT_SIG)? {
+            Ok(GravityEvent::TransactionBatchExecuted(
+                TransactionBatchExecutedEvent::from_log(input)?,
+            ))
+        } else if topic == derive_signature(SENT_TO_COSMOS_EVENT_SIG)? {
+            Ok(GravityEvent::SendToCosmos(SendToCosmosEvent::from_log(
+                input,
+            )?))
+        } else if topic == derive_signature(ERC20_DEPLOYED_EVENT_SIG)? {
+            Ok(GravityEvent::Erc20Deployed(Erc20DeployedEvent::from_log(
+                input,
+            )?))
+        } else if topic == derive_signature(LOGIC_CALL_EVENT_SIG)? {
+            Ok(GravityEvent::LogicCallExecuted(
+                LogicCallExecutedEvent::from_log(input)?,
+            ))
+        } else {
+            Err(GravityError::InvalidEventLogError(format!(
+                "Unknown event signature 0x{}",
+                bytes_to_hex_str(topic)
+            )))
+        }
+    }
+
+    pub fn get_event_nonce(&self) -> u64 {
+        match self {
+            GravityEvent::ValsetUpdated(e) => e.get_event_nonce(),
+            GravityEvent::TransactionBatchExecuted(e) => e.get_event_nonce(),
+            GravityEvent::SendToCosmos(e) => e.get_event_nonce(),
+            GravityEvent::Erc20Deployed(e) => e.get_event_nonce(),
+            GravityEvent::LogicCallExecuted(e) => e.get_event_nonce(),
+        }
+    }
+
+    pub fn get_block_height(&self) -> Uint256 {
+        match self {
+            GravityEvent::ValsetUpdated(e) => e.block_height,
+            GravityEvent::TransactionBatchExecuted(e) => e.block_height,
+            GravityEvent::SendToCosmos(e) => e.block_height,
+            GravityEvent::Erc20Deployed(e) => e.block_height,
+            GravityEvent::LogicCallExecuted(e) => e.block_height,
+        }
+    }
+}
+
+/// All events from a set of Gravity contract logs, sorted by type
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct GravityEvents {
+    pub valsets: Vec<ValsetUpdatedEvent>,
+    pub batches: Vec<TransactionBatchExecutedEvent>,
+    pub deposits: Vec<SendToCosmosEvent>,
+    pub erc20_deploys: Vec<Erc20DeployedEvent>,
+    pub logic_calls: Vec<LogicCallExecutedEvent>,
+}
+
+impl GravityEvents {
+    /// Parses and sorts all the provided logs, failing if any log can not be parsed
+    pub fn from_logs(input: &[Log]) -> Result<GravityEvents, GravityError> {
+        let mut res = GravityEvents::default();
+        for item in input {
+            match GravityEvent::from_log(item)? {
+                GravityEvent::ValsetUpdated(e) => res.valsets.push(e),
+                GravityEvent::TransactionBatchExecuted(e) => res.batches.push(e),
+                GravityEvent::SendToCosmos(e) => res.deposits.push(e),
+                GravityEvent::Erc20Deployed(e) => res.erc20_deploys.push(e),
+                GravityEvent::LogicCallExecuted(e) => res.logic_calls.push(e),
+            }
+        }
+        Ok(res)
+    }
+}
+
 /// Function used for debug printing hex dumps
 /// of ethereum events with each uint256 on a new
 /// line
@@ -1152,6 +1329,90 @@ mod tests {
         assert_eq!(correct, res);
     }
 
+    /// Builds a log as returned by eth_getLogs with the given topics and data at block 16
+    fn get_log(topics: &[String], data: &str) -> Log {
+        serde_json::from_value(serde_json::json!({
+            "address": "0x0000000000000000000000000000000000000001",
+            "topics": topics,
+            "data": data,
+            "blockNumber": "0x10",
+            "blockHash": format!("0x{}", "11".repeat(32)),
+            "transactionHash": format!("0x{}", "22".repeat(32)),
+            "transactionIndex": "0x0",
+            "logIndex": "0x0",
+            "removed": false
+        }))
+        .unwrap()
+    }
+
+    fn get_topic(sig: &str) -> String {
+        format!("0x{}", bytes_to_hex_str(&derive_signature(sig).unwrap()))
+    }
+
+    fn pad(hex: &str) -> String {
+        format!("0x{hex:0>64}")
+    }
+
+    #[test]
+    fn test_gravity_event_from_log() {
+        let erc20 = "7580bfe88dd3d07947908fae12d95872a260f2d8";
+        let sender = "1bb537aa56ffc7d608793baffc6c9c7de3c4f270";
+
+        let log = get_log(
+            &[
+                get_topic(TRANSACTION_BATCH_EXECUTED_EVENT_SIG),
+                pad("5"),
+                pad(erc20),
+            ],
+            &pad("7"),
+        );
+        match GravityEvent::from_log(&log).unwrap() {
+            GravityEvent::TransactionBatchExecuted(e) => {
+                assert_eq!(e.batch_nonce, 5);
+                assert_eq!(e.event_nonce, 7);
+                assert_eq!(e.erc20, format!("0x{erc20}").parse().unwrap());
+                assert_eq!(e.block_height, 16u8.into());
+            }
+            e => panic!("Decoded the wrong event {e:?}"),
+        }
+
+        let data = "0x0000000000000000000000000000000000000000000000000000000000000060\
+        0000000000000000000000000000000000000000000000000000000000000064\
+        0000000000000000000000000000000000000000000000000000000000000002\
+        000000000000000000000000000000000000000000000000000000000000002f\
+        67726176697479313139347a613679766737646a7a33633676716c63787a7877\
+        636a6b617a397264717332656739700000000000000000000000000000000000";
+        let log = get_log(
+            &[get_topic(SENT_TO_COSMOS_EVENT_SIG), pad(erc20), pad(sender)],
+            data,
+        );
+        match GravityEvent::from_log(&log).unwrap() {
+            GravityEvent::SendToCosmos(e) => {
+                assert_eq!(e.event_nonce, 2);
+                assert_eq!(e.amount, 100u8.into());
+                assert_eq!(e.sender, format!("0x{sender}").parse().unwrap());
+                assert_eq!(
+                    e.destination,
+                    "gravity1194za6yvg7djz3c6vqlcxzxwcjkaz9rdqs2eg9p"
+                );
+                assert_eq!(e.get_event_nonce(), 2);
+            }
+            e => panic!("Decoded the wrong event {e:?}"),
+        }
+
+        // unknown signatures and logs without a signature topic are rejected
+        let log = get_log(&[pad("1")], &pad("7"));
+        assert!(GravityEvent::from_log(&log).is_err());
+        let log = get_log(&[], &pad("7"));
+        assert!(GravityEvent::from_log(&log).is_err());
+        // a known signature with too few topics
+        let log = get_log(
+            &[get_topic(TRANSACTION_BATCH_EXECUTED_EVENT_SIG)],
+            &pad("7"),
+        );
+        assert!(GravityEvent::from_log(&log).is_err());
+    }
+
     #[test]
     fn fuzz_send_to_cosmos_decode() {
         let start = Instant::now();
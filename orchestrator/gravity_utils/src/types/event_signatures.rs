@@ -11,3 +11,16 @@ pub const LOGIC_CALL_EVENT_SIG: &str = "LogicCallEvent(bytes32,uint256,bytes,uin
 
 pub const VALSET_UPDATED_EVENT_SIG: &str =
     "ValsetUpdatedEvent(uint256,uint256,uint256,address,address[],uint256[])";
+
+/// Emitted by the GravityERC721 contract, not observed by the Oracle
+pub const SEND_ERC721_TO_COSMOS_EVENT_SIG: &str =
+    "SendERC721ToCosmosEvent(address,address,string,uint256,uint256)";
+
+/// All Gravity contract events observed by the Oracle
+pub const GRAVITY_EVENT_SIGS: [&str; 5] = [
+    VALSET_UPDATED_EVENT_SIG,
+    TRANSACTION_BATCH_EXECUTED_EVENT_SIG,
+    SENT_TO_COSMOS_EVENT_SIG,
+    ERC20_DEPLOYED_EVENT_SIG,
+    LOGIC_CALL_EVENT_SIG,
+];
@@ -3,7 +3,7 @@ const DEFAULT_PORT: u16 = 8545;
 const EVM_CHAIN_ID: u64 = 999999;
 
 use actix_cors::Cors;
-use actix_web::{post, web, App, HttpResponse, HttpServer};
+use actix_web::{post, web, App, HttpRequest, HttpResponse, HttpServer};
 use log::{debug, info};
 use rustls::pki_types::pem::PemObject;
 use rustls::pki_types::{CertificateDer, PrivateKeyDer};
@@ -22,6 +22,10 @@ lazy_static!(
     static ref BLOCK_NUMBER: Mutex<u64> = Mutex::new(64u64);
     // Used to track when the BLOCK_NUMBER last changed
     static ref BLOCK_TIME: Mutex<Instant> = Mutex::new(Instant::now());
+    // Every signed transaction received by the spoofed private relay methods, in order
+    static ref PRIVATE_TRANSACTIONS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    // The X-Flashbots-Signature header and body of every private relay request, in order
+    static ref PRIVATE_RELAY_REQUESTS: Mutex<Vec<(Option<String>, String)>> = Mutex::new(Vec::new());
 );
 
 #[derive(Deserialize, Debug)]
@@ -74,16 +78,34 @@ pub fn get_and_incr_block_number() -> u64 {
 /// See https://ethereum.org/en/developers/docs/apis/json-rpc/#json-rpc-methods for more Ethereum JSONRPC methods that
 /// may need to be implemented for MetaMask to function.
 #[post("/")]
-async fn request_dispatcher(req_body: web::Json<RequestBody>) -> HttpResponse {
+async fn request_dispatcher(req: HttpRequest, body: web::Bytes) -> HttpResponse {
+    let req_body: RequestBody = match serde_json::from_slice(&body) {
+        Ok(v) => v,
+        Err(_) => return HttpResponse::BadRequest().finish(),
+    };
     debug!("Got request: {req_body:?}");
+    if req_body.method == "eth_sendPrivateTransaction" || req_body.method == "eth_sendBundle" {
+        let signature = req
+            .headers()
+            .get("X-Flashbots-Signature")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        let body = String::from_utf8_lossy(&body).to_string();
+        PRIVATE_RELAY_REQUESTS
+            .lock()
+            .unwrap()
+            .push((signature, body));
+    }
     match req_body.method.as_str() {
-        "net_version" => net_version(req_body.into_inner()).await,
-        "eth_chainId" => eth_chainId(req_body.into_inner()).await,
-        "eth_blockNumber" => eth_blockNumber(req_body.into_inner()).await,
-        "eth_getBlockByNumber" => eth_getBlockByNumber(req_body.into_inner()).await,
-        "eth_getBalance" => eth_getBalance(req_body.into_inner()).await,
-        "eth_feeHistory" => eth_feeHistory(req_body.into_inner()).await,
-        "eth_gasPrice" => eth_gasPrice(req_body.into_inner()).await,
+        "net_version" => net_version(req_body).await,
+        "eth_chainId" => eth_chainId(req_body).await,
+        "eth_blockNumber" => eth_blockNumber(req_body).await,
+        "eth_getBlockByNumber" => eth_getBlockByNumber(req_body).await,
+        "eth_getBalance" => eth_getBalance(req_body).await,
+        "eth_feeHistory" => eth_feeHistory(req_body).await,
+        "eth_gasPrice" => eth_gasPrice(req_body).await,
+        "eth_sendPrivateTransaction" => eth_sendPrivateTransaction(req_body).await,
+        "eth_sendBundle" => eth_sendBundle(req_body).await,
         _ => default_response(),
     }
 }
@@ -163,6 +185,61 @@ async fn eth_getBalance(req_body: RequestBody) -> HttpResponse {
     HttpResponse::Ok().json(res)
 }
 
+/// Returns every signed transaction received through "eth_sendPrivateTransaction" and "eth_sendBundle"
+pub fn get_private_transactions() -> Vec<String> {
+    PRIVATE_TRANSACTIONS.lock().unwrap().clone()
+}
+
+/// Returns the X-Flashbots-Signature header, if any, and the body of every private relay request
+pub fn get_private_relay_requests() -> Vec<(Option<String>, String)> {
+    PRIVATE_RELAY_REQUESTS.lock().unwrap().clone()
+}
+
+// Records the signed transactions in a private relay request, returning a response id
+fn record_private_transactions(txs: Vec<String>) -> String {
+    let mut private_txs = PRIVATE_TRANSACTIONS.lock().unwrap();
+    private_txs.extend(txs);
+    format!("0x{:064x}", private_txs.len())
+}
+
+/// Spoofs the Flashbots style "eth_sendPrivateTransaction" private relay method, used to test relayer
+/// private submission. The transaction is only recorded, never included
+#[allow(non_snake_case)]
+async fn eth_sendPrivateTransaction(req_body: RequestBody) -> HttpResponse {
+    let params = req_body.params.unwrap_or_default();
+    let tx = params[0]["tx"].as_str().map(|v| v.to_string());
+    match tx {
+        Some(tx) => {
+            let res = jsonrpc_response(record_private_transactions(vec![tx]), req_body.id);
+            HttpResponse::Ok().json(res)
+        }
+        None => default_response(),
+    }
+}
+
+/// Spoofs the Flashbots style "eth_sendBundle" private relay method, used to test relayer private
+/// submission. The bundle transactions are only recorded, never included
+#[allow(non_snake_case)]
+async fn eth_sendBundle(req_body: RequestBody) -> HttpResponse {
+    let params = req_body.params.unwrap_or_default();
+    let txs: Option<Vec<String>> = params[0]["txs"].as_array().map(|txs| {
+        txs.iter()
+            .filter_map(|v| v.as_str().map(|v| v.to_string()))
+            .collect()
+    });
+    match txs {
+        Some(txs) => {
+            let bundle_hash = record_private_transactions(txs);
+            let res = jsonrpc_response(
+                serde_json::json!({ "bundleHash": bundle_hash }),
+                req_body.id,
+            );
+            HttpResponse::Ok().json(res)
+        }
+        None => default_response(),
+    }
+}
+
 // Returns a failure response to indicate that the request failed
 fn default_response() -> HttpResponse {
     HttpResponse::NotAcceptable().finish()
@@ -1,10 +1,15 @@
+use gravity_utils::connection_prep::CosmosGrpcPool;
 use gravity_utils::types::MetricsConfig;
 use lazy_static::lazy_static;
 use prometheus_exporter::prometheus::{
-    register_int_counter, register_int_counter_vec, register_int_gauge_vec,
+    register_gauge, register_gauge_vec, register_int_counter, register_int_counter_vec,
+    register_int_gauge, register_int_gauge_vec,
+};
+use prometheus_exporter::prometheus::{
+    Gauge, GaugeVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
-use prometheus_exporter::prometheus::{IntCounter, IntCounterVec, IntGaugeVec};
 use std::net::SocketAddr;
+use std::time::Duration;
 
 lazy_static! {
 
@@ -35,6 +40,60 @@ lazy_static! {
     // Information gauges
     pub static ref LATEST_INFO: IntGaugeVec =
         register_int_gauge_vec!("orchestrator_information", "Latest orchestrator information", &["gauge"]).unwrap();
+
+    // Ethereum block hash conflicts, the block and both hashes are in the orchestrator logs
+    pub static ref BLOCK_HASH_CONFLICTS: IntCounter =
+        register_int_counter!("orchestrator_eth_block_hash_conflicts", "Ethereum blocks observed with two different hashes, any increase means the oracle is halted").unwrap();
+
+    // Ethereum RPC quorum endpoints
+    pub static ref ETH_RPC_HEALTH: IntGaugeVec =
+        register_int_gauge_vec!("orchestrator_eth_rpc_healthy", "1 if the Ethereum RPC endpoint responded to the last request, 0 otherwise", &["endpoint"]).unwrap();
+    pub static ref ETH_RPC_DISAGREEMENTS: IntCounterVec =
+        register_int_counter_vec!("orchestrator_eth_rpc_disagreements", "Responses from the Ethereum RPC endpoint that disagreed with the quorum", &["endpoint"]).unwrap();
+
+    // Cosmos gRPC failover pool
+    pub static ref COSMOS_GRPC_ACTIVE: IntGaugeVec =
+        register_int_gauge_vec!("orchestrator_cosmos_grpc_active", "1 for the Cosmos gRPC endpoint currently in use, 0 for fallbacks", &["endpoint"]).unwrap();
+
+    // Eth signer refusals, any increase here means the Cosmos node asked us to sign something slashable
+    pub static ref SIGNING_CONFLICTS: IntCounterVec =
+        register_int_counter_vec!("orchestrator_signing_conflicts", "Checkpoints refused because they conflict with the signing journal or the cross check Cosmos node", &["checkpoint_type"]).unwrap();
+    pub static ref PRESIGN_REJECTIONS: IntCounterVec =
+        register_int_counter_vec!("orchestrator_presign_rejections", "Checkpoints refused because they failed pre-sign validation", &["checkpoint_type"]).unwrap();
+
+    // Relayer wallet funds guard
+    pub static ref RELAYER_FUNDS_PAUSED: IntGauge =
+        register_int_gauge!("relayer_paused_insufficient_funds", "1 while the relayer has paused submissions because of its balance or spend limits").unwrap();
+    pub static ref RELAYER_ETH_BALANCE: Gauge =
+        register_gauge!("relayer_eth_balance_gwei", "The relayer's ETH balance in gwei").unwrap();
+    pub static ref RELAYER_SPEND: GaugeVec =
+        register_gauge_vec!("relayer_gas_spend_gwei", "Gas spent by the relayer in gwei over the trailing window", &["window"]).unwrap();
+
+    // Relayer batches
+    pub static ref RELAYER_PENDING_BATCHES: IntGaugeVec =
+        register_int_gauge_vec!("relayer_pending_batches", "Batches on Cosmos not yet relayed to Ethereum", &["token"]).unwrap();
+    pub static ref RELAYER_BATCH_SIGNATURES: IntGaugeVec =
+        register_int_gauge_vec!("relayer_batches_by_signatures", "Pending batches that have enough signatures to submit, or are waiting for more", &["state"]).unwrap();
+    pub static ref RELAYER_CANDIDATE_COST: GaugeVec =
+        register_gauge_vec!("relayer_candidate_cost_gwei", "Worst case cost in gwei of submitting each batch the relayer considered in its last iteration", &["token", "nonce"]).unwrap();
+    pub static ref RELAYER_CANDIDATE_REWARD: GaugeVec =
+        register_gauge_vec!("relayer_candidate_reward_gwei", "Reward in gwei of each batch the relayer considered in its last iteration, if it could be priced", &["token", "nonce"]).unwrap();
+
+    // Relayer submissions
+    pub static ref RELAYER_SUBMISSIONS: IntCounterVec =
+        register_int_counter_vec!("relayer_submissions", "Relayer transactions settled on Ethereum", &["type", "outcome"]).unwrap();
+
+    // Relayer gas tracker, IBC auto forwarding and loops
+    pub static ref RELAYER_GAS_PRICE: GaugeVec =
+        register_gauge_vec!("relayer_gas_price_gwei", "The latest gas price sample and the price altruistic relaying accepts, in gwei", &["price"]).unwrap();
+    pub static ref RELAYER_IBC_AUTO_FORWARD_QUEUE: IntGauge =
+        register_int_gauge!("relayer_ibc_auto_forward_queue", "Pending IBC auto forwards waiting to be executed").unwrap();
+    pub static ref RELAYER_ITERATION_DURATION: IntGaugeVec =
+        register_int_gauge_vec!("relayer_iteration_duration_ms", "Duration of the last iteration of each relayer loop in milliseconds", &["loop"]).unwrap();
+}
+
+fn saturating_i64(v: u64) -> i64 {
+    i64::try_from(v).unwrap_or(i64::MAX)
 }
 
 pub fn metrics_errors_counter(s: i32, e: &str) {
@@ -63,6 +122,129 @@ pub fn metrics_latest(u: u64, e: &str) {
     }
 }
 
+pub fn metrics_block_hash_conflict() {
+    BLOCK_HASH_CONFLICTS.inc();
+}
+
+pub fn metrics_eth_rpc_health(endpoint: &str, healthy: bool) {
+    ETH_RPC_HEALTH
+        .with_label_values(&[endpoint])
+        .set(healthy as i64);
+}
+
+pub fn metrics_eth_rpc_disagreement(endpoint: &str) {
+    ETH_RPC_DISAGREEMENTS.with_label_values(&[endpoint]).inc();
+}
+
+pub fn metrics_cosmos_grpc_pool(pool: &CosmosGrpcPool) {
+    let active = pool.get_active_url();
+    for url in pool.get_urls() {
+        COSMOS_GRPC_ACTIVE
+            .with_label_values(&[&url])
+            .set((url == active) as i64);
+    }
+}
+
+pub fn metrics_signing_conflict(checkpoint_type: &str) {
+    SIGNING_CONFLICTS
+        .with_label_values(&[checkpoint_type])
+        .inc();
+}
+
+pub fn metrics_presign_rejection(checkpoint_type: &str) {
+    PRESIGN_REJECTIONS
+        .with_label_values(&[checkpoint_type])
+        .inc();
+}
+
+pub fn metrics_relayer_funds(
+    balance_gwei: f64,
+    spent_hour_gwei: f64,
+    spent_day_gwei: f64,
+    paused: bool,
+) {
+    metrics_relayer_eth_balance(balance_gwei);
+    RELAYER_SPEND
+        .with_label_values(&["hour"])
+        .set(spent_hour_gwei);
+    RELAYER_SPEND
+        .with_label_values(&["day"])
+        .set(spent_day_gwei);
+    RELAYER_FUNDS_PAUSED.set(paused as i64);
+}
+
+pub fn metrics_relayer_eth_balance(balance_gwei: f64) {
+    RELAYER_ETH_BALANCE.set(balance_gwei);
+}
+
+/// Sets the pending batch gauges, tokens with no pending batches are cleared
+pub fn metrics_relayer_batches(
+    pending_per_token: &[(String, u64)],
+    submittable: u64,
+    undersigned: u64,
+) {
+    RELAYER_PENDING_BATCHES.reset();
+    for (token, count) in pending_per_token {
+        RELAYER_PENDING_BATCHES
+            .with_label_values(&[token])
+            .set(saturating_i64(*count));
+    }
+    RELAYER_BATCH_SIGNATURES
+        .with_label_values(&["submittable"])
+        .set(saturating_i64(submittable));
+    RELAYER_BATCH_SIGNATURES
+        .with_label_values(&["undersigned"])
+        .set(saturating_i64(undersigned));
+}
+
+/// Clears the candidate gauges, called before the candidates of a new iteration are recorded
+pub fn metrics_relayer_reset_candidates() {
+    RELAYER_CANDIDATE_COST.reset();
+    RELAYER_CANDIDATE_REWARD.reset();
+}
+
+pub fn metrics_relayer_candidate(
+    token: &str,
+    nonce: u64,
+    cost_gwei: f64,
+    reward_gwei: Option<f64>,
+) {
+    let nonce = nonce.to_string();
+    RELAYER_CANDIDATE_COST
+        .with_label_values(&[token, &nonce])
+        .set(cost_gwei);
+    if let Some(reward) = reward_gwei {
+        RELAYER_CANDIDATE_REWARD
+            .with_label_values(&[token, &nonce])
+            .set(reward);
+    }
+}
+
+pub fn metrics_relayer_submission(submission_type: &str, outcome: &str) {
+    RELAYER_SUBMISSIONS
+        .with_label_values(&[submission_type, outcome])
+        .inc();
+}
+
+pub fn metrics_relayer_gas_price(current_gwei: Option<f64>, acceptable_gwei: Option<f64>) {
+    if let Some(v) = current_gwei {
+        RELAYER_GAS_PRICE.with_label_values(&["current"]).set(v);
+    }
+    if let Some(v) = acceptable_gwei {
+        RELAYER_GAS_PRICE.with_label_values(&["acceptable"]).set(v);
+    }
+}
+
+pub fn metrics_relayer_ibc_auto_forward_queue(len: usize) {
+    RELAYER_IBC_AUTO_FORWARD_QUEUE.set(saturating_i64(len as u64));
+}
+
+pub fn metrics_relayer_iteration(loop_name: &str, duration: Duration) {
+    RELAYER_ITERATION_DURATION
+        .with_label_values(&[loop_name])
+        .set(saturating_i64(duration.as_millis() as u64));
+}
+
 pub fn metrics_server(config: &MetricsConfig) {
     // Parse address used to bind exporter to.
     let addr_raw = &config.metrics_bind;
@@ -0,0 +1,274 @@
+//! A quorum client over multiple Ethereum RPC endpoints. The Oracle attests to events on the Cosmos
+//! chain, if its only Ethereum node lies or lags a validator could attest to deposits that never
+//! happened. Using this client the Oracle only acts on event logs and safe blocks that at least
+//! `quorum` endpoints agree on.
+
+use crate::ethereum_event_watcher::{get_gravity_logs, try_get_latest_safe_block};
+use crate::oracle_checkpoint::get_block_hash;
+use clarity::{Address as EthAddress, Uint256};
+use futures::future::join_all;
+use gravity_utils::error::GravityError;
+use metrics_exporter::{metrics_eth_rpc_disagreement, metrics_eth_rpc_health};
+use std::time::Duration;
+use web30::client::Web3;
+use web30::types::Log;
+
+/// Timeout for requests to the additional quorum endpoints
+pub const ETH_QUORUM_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub struct EthQuorumClient {
+    /// All endpoints, the first being the primary endpoint also used for
+    /// sending transactions and any queries not requiring a quorum
+    endpoints: Vec<Web3>,
+    /// The number of endpoints that must agree on a response
+    quorum: usize,
+}
+
+impl EthQuorumClient {
+    /// Creates a quorum client from the primary Web3 connection and a list of additional endpoint urls.
+    /// A `quorum` of zero is replaced with a simple majority, a `quorum` larger than the number of
+    /// endpoints could never be reached and is a configuration error
+    pub fn new(
+        primary: Web3,
+        additional_endpoints: &[String],
+        quorum: usize,
+    ) -> Result<EthQuorumClient, GravityError> {
+        let mut endpoints = vec![primary];
+        for url in additional_endpoints {
+            endpoints.push(Web3::new(url, ETH_QUORUM_TIMEOUT));
+        }
+        if quorum > endpoints.len() {
+            return Err(GravityError::InvalidOptionsError(format!(
+                "An Ethereum RPC quorum of {quorum} can never be reached with {} endpoints",
+                endpoints.len()
+            )));
+        }
+        let quorum = if quorum == 0 {
+            endpoints.len() / 2 + 1
+        } else {
+            quorum
+        };
+        Ok(EthQuorumClient { endpoints, quorum })
+    }
+
+    /// A copy of this client with every endpoint using the given request timeout, for queries
+    /// expected to take longer than usual such as the oracle resync
+    pub fn with_timeout(&self, timeout: Duration) -> EthQuorumClient {
+        EthQuorumClient {
+            endpoints: self
+                .endpoints
+                .iter()
+                .map(|web3| Web3::new(&web3.get_url(), timeout))
+                .collect(),
+            quorum: self.quorum,
+        }
+    }
+
+    pub fn get_primary(&self) -> &Web3 {
+        &self.endpoints[0]
+    }
+
+    pub fn get_quorum(&self) -> usize {
+        self.quorum
+    }
+
+    /// The latest safe block (see get_latest_safe_block) that at least `quorum` endpoints have reached
+    pub async fn get_latest_safe_block(&self) -> Result<Uint256, GravityError> {
+        let responses = join_all(self.endpoints.iter().map(try_get_latest_safe_block)).await;
+        let mut blocks = Vec::new();
+        for (web3, response) in self.endpoints.iter().zip(responses) {
+            match response {
+                Ok(block) => {
+                    metrics_eth_rpc_health(&web3.get_url(), true);
+                    blocks.push(block)
+                }
+                Err(e) => {
+                    warn!(
+                        "Ethereum RPC {} failed to get safe block {e}",
+                        web3.get_url()
+                    );
+                    metrics_eth_rpc_health(&web3.get_url(), false);
+                }
+            }
+        }
+        match get_quorum_safe_block(blocks.clone(), self.quorum) {
+            Some(block) => Ok(block),
+            None => Err(GravityError::EthereumQuorumError(format!(
+                "Only {} of the required {} Ethereum RPC endpoints returned a safe block {:?}",
+                blocks.len(),
+                self.quorum,
+                blocks
+            ))),
+        }
+    }
+
+    /// All Gravity contract event logs in the given range, only if at least `quorum` endpoints returned
+    /// exactly the same set of logs
+    pub async fn get_gravity_logs(
+        &self,
+        gravity_contract_address: EthAddress,
+        start_block: Uint256,
+        end_block: Uint256,
+    ) -> Result<Vec<Log>, GravityError> {
+        let responses =
+            join_all(self.endpoints.iter().map(|web3| {
+                get_gravity_logs(web3, gravity_contract_address, start_block, end_block)
+            }))
+            .await;
+        let mut keys = Vec::new();
+        let mut logs = Vec::new();
+        for (web3, response) in self.endpoints.iter().zip(responses) {
+            match response {
+                Ok(response) => {
+                    metrics_eth_rpc_health(&web3.get_url(), true);
+                    keys.push(serde_json::to_string(&response).ok());
+                    logs.push(Some(response));
+                }
+                Err(e) => {
+                    warn!("Ethereum RPC {} failed to get events {e}", web3.get_url());
+                    metrics_eth_rpc_health(&web3.get_url(), false);
+                    keys.push(None);
+                    logs.push(None);
+                }
+            }
+        }
+        match find_agreement(&keys, self.quorum) {
+            Some(index) => {
+                self.report_disagreements(&keys, index, "event logs");
+                Ok(logs[index].take().unwrap())
+            }
+            None => Err(self.quorum_error(&keys, "event logs")),
+        }
+    }
+
+    /// The hash of the given block, only if at least `quorum` endpoints agree on it
+    pub async fn get_block_hash(&self, block_number: Uint256) -> Result<Uint256, GravityError> {
+        let responses = join_all(
+            self.endpoints
+                .iter()
+                .map(|web3| get_block_hash(web3, block_number)),
+        )
+        .await;
+        let mut hashes = Vec::new();
+        for (web3, response) in self.endpoints.iter().zip(responses) {
+            match response {
+                Ok(hash) => {
+                    metrics_eth_rpc_health(&web3.get_url(), true);
+                    hashes.push(Some(hash));
+                }
+                Err(e) => {
+                    warn!(
+                        "Ethereum RPC {} failed to get block {block_number} {e}",
+                        web3.get_url()
+                    );
+                    metrics_eth_rpc_health(&web3.get_url(), false);
+                    hashes.push(None);
+                }
+            }
+        }
+        match find_agreement(&hashes, self.quorum) {
+            Some(index) => {
+                self.report_disagreements(&hashes, index, "block hash");
+                Ok(hashes[index].unwrap())
+            }
+            None => Err(self.quorum_error(&hashes, "block hash")),
+        }
+    }
+
+    /// Logs and counts every endpoint that responded with something other than the agreed upon value
+    fn report_disagreements<T: PartialEq>(
+        &self,
+        responses: &[Option<T>],
+        agreed: usize,
+        what: &str,
+    ) {
+        for (web3, response) in self.endpoints.iter().zip(responses) {
+            if response.is_some() && *response != responses[agreed] {
+                warn!(
+                    "Ethereum RPC {} disagrees with the quorum on {what}",
+                    web3.get_url()
+                );
+                metrics_eth_rpc_disagreement(&web3.get_url());
+            }
+        }
+    }
+
+    fn quorum_error<T: PartialEq>(&self, responses: &[Option<T>], what: &str) -> GravityError {
+        let mut report = Vec::new();
+        for (i, (web3, response)) in self.endpoints.iter().zip(responses).enumerate() {
+            let status = match response {
+                // number the distinct responses so operators can see which endpoints agree
+                Some(r) => format!(
+                    "response {}",
+                    responses
+                        .iter()
+                        .position(|v| v.as_ref() == Some(r))
+                        .unwrap()
+                ),
+                None => "failed".to_string(),
+            };
+            if response.is_some() {
+                metrics_eth_rpc_disagreement(&web3.get_url());
+            }
+            report.push(format!("{i}: {} {status}", web3.get_url()));
+        }
+        error!(
+            "Ethereum RPC endpoints could not reach a quorum of {} on {what}: {}",
+            self.quorum,
+            report.join(", ")
+        );
+        GravityError::EthereumQuorumError(format!(
+            "No quorum of {} on {what}: {}",
+            self.quorum,
+            report.join(", ")
+        ))
+    }
+}
+
+/// Returns the index of a response that at least `quorum` successful responses agree with
+fn find_agreement<T: PartialEq>(responses: &[Option<T>], quorum: usize) -> Option<usize> {
+    for (i, response) in responses.iter().enumerate() {
+        if response.is_none() {
+            continue;
+        }
+        if responses.iter().filter(|r| *r == response).count() >= quorum {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// Endpoints will rarely return exactly the same safe block, so we use the highest block
+/// that at least `quorum` endpoints have reached
+fn get_quorum_safe_block(mut blocks: Vec<Uint256>, quorum: usize) -> Option<Uint256> {
+    if quorum == 0 || blocks.len() < quorum {
+        return None;
+    }
+    blocks.sort();
+    blocks.reverse();
+    Some(blocks[quorum - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_agreement() {
+        assert_eq!(find_agreement(&[Some(1), Some(1), Some(2)], 2), Some(0));
+        assert_eq!(find_agreement(&[Some(2), Some(1), Some(1)], 2), Some(1));
+        assert_eq!(find_agreement(&[Some(1), None, Some(2)], 2), None);
+        assert_eq!(find_agreement::<u8>(&[None, None, None], 1), None);
+        assert_eq!(find_agreement(&[Some(1)], 1), Some(0));
+    }
+
+    #[test]
+    fn test_quorum_safe_block() {
+        let blocks: Vec<Uint256> = vec![10u8.into(), 12u8.into(), 11u8.into()];
+        assert_eq!(get_quorum_safe_block(blocks.clone(), 1), Some(12u8.into()));
+        assert_eq!(get_quorum_safe_block(blocks.clone(), 2), Some(11u8.into()));
+        assert_eq!(get_quorum_safe_block(blocks.clone(), 3), Some(10u8.into()));
+        assert_eq!(get_quorum_safe_block(blocks, 4), None);
+    }
+}
@@ -1,6 +1,7 @@
 //! Ethereum Event watcher watches for events such as a deposit to the Gravity Ethereum contract or a validator set update
 //! or a transaction batch update. It then responds to these events by performing actions on the Cosmos chain if required
 
+use clarity::abi::derive_signature;
 use clarity::{utils::bytes_to_hex_str, Address as EthAddress, Uint256};
 use cosmos_gravity::{query::get_last_event_nonce_for_validator, send::send_ethereum_claims};
 use deep_space::Contact;
@@ -9,40 +10,53 @@ use deep_space::{
     private_key::{CosmosPrivateKey, PrivateKey},
 };
 use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
-use gravity_utils::get_with_retry::get_eth_chainid_with_retry;
-use gravity_utils::get_with_retry::{get_block_number_with_retry, get_finalized_block_with_retry};
-use gravity_utils::types::event_signatures::*;
+use gravity_utils::get_with_retry::RETRY_TIME;
+use gravity_utils::types::event_signatures::GRAVITY_EVENT_SIGS;
 use gravity_utils::{
     error::GravityError,
     types::{
-        Erc20DeployedEvent, EthereumEvent, LogicCallExecutedEvent, SendToCosmosEvent,
-        TransactionBatchExecutedEvent, ValsetUpdatedEvent,
+        Erc20DeployedEvent, EthereumEvent, GravityEvents, LogicCallExecutedEvent,
+        SendToCosmosEvent, TransactionBatchExecutedEvent, ValsetUpdatedEvent,
     },
 };
-use metrics_exporter::metrics_errors_counter;
+use metrics_exporter::{
+    metrics_block_hash_conflict, metrics_errors_counter, metrics_warnings_counter,
+};
+use std::future::Future;
+use tokio::time::sleep as delay_for;
 use tonic::transport::Channel;
 use web30::client::Web3;
 use web30::jsonrpc::error::Web3Error;
+use web30::types::{Log, NewFilter};
 
-use crate::oracle_resync::BLOCKS_TO_SEARCH;
+use crate::eth_quorum::EthQuorumClient;
+use crate::oracle_resync::{BLOCKS_TO_SEARCH, MIN_BLOCKS_TO_SEARCH};
+use crate::reorg_detection::{BlockHashConflict, BlockHashTracker};
 
 pub struct CheckedNonces {
     pub block_number: Uint256,
     pub event_nonce: Uint256,
 }
 
+/// Checks for events in the range starting at `starting_block` and submits claims for any new events
+/// the hashes of all blocks examined are recorded in `block_hashes`, if any previously observed block
+/// has changed hash no claims are submitted and an EthereumReorgError is returned
 #[allow(clippy::too_many_arguments)]
 pub async fn check_for_events(
-    web3: &Web3,
+    eth_rpc: &EthQuorumClient,
     contact: &Contact,
     grpc_client: &mut GravityQueryClient<Channel>,
     gravity_contract_address: EthAddress,
     our_private_key: CosmosPrivateKey,
     fee: Coin,
     starting_block: Uint256,
+    block_hashes: &mut BlockHashTracker,
 ) -> Result<CheckedNonces, GravityError> {
+    if let Some(conflict) = block_hashes.get_conflict() {
+        return Err(GravityError::EthereumReorgError(conflict.to_string()));
+    }
     let our_cosmos_address = our_private_key.to_address(&contact.get_prefix()).unwrap();
-    let latest_block = get_latest_safe_block(web3).await;
+    let latest_block = eth_rpc.get_latest_safe_block().await?;
     trace!("Checking for events starting {starting_block} safe {latest_block}");
 
     // if the latest block is more than BLOCKS_TO_SEARCH ahead do not search the full history
@@ -55,210 +69,257 @@ pub async fn check_for_events(
         latest_block
     };
 
-    let deposits = web3
-        .check_for_events(
-            starting_block,
-            Some(latest_block),
-            vec![gravity_contract_address],
-            vec![SENT_TO_COSMOS_EVENT_SIG],
-        )
-        .await;
-    trace!("Deposits {deposits:?}");
-
-    let batches = web3
-        .check_for_events(
-            starting_block,
-            Some(latest_block),
-            vec![gravity_contract_address],
-            vec![TRANSACTION_BATCH_EXECUTED_EVENT_SIG],
-        )
-        .await;
-    trace!("Batches {batches:?}");
-
-    let valsets = web3
-        .check_for_events(
-            starting_block,
-            Some(latest_block),
-            vec![gravity_contract_address],
-            vec![VALSET_UPDATED_EVENT_SIG],
-        )
-        .await;
-    trace!("Valsets {valsets:?}");
+    let (logs, latest_block) = match get_gravity_logs_adaptive(
+        eth_rpc,
+        gravity_contract_address,
+        starting_block,
+        latest_block,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to get events {e}");
+            metrics_errors_counter(1, "Failed to get events");
+            return Err(e);
+        }
+    };
+    trace!("Gravity events {logs:?}");
 
-    let erc20_deployed = web3
-        .check_for_events(
-            starting_block,
-            Some(latest_block),
-            vec![gravity_contract_address],
-            vec![ERC20_DEPLOYED_EVENT_SIG],
-        )
-        .await;
-    trace!("ERC20 Deployments {erc20_deployed:?}");
+    // the starting block is the end of the previous search range, so checking it's hash links
+    // every range we search to the previous one
+    let starting_block_hash = eth_rpc.get_block_hash(starting_block).await?;
+    let latest_block_hash = eth_rpc.get_block_hash(latest_block).await?;
+    if let Err(conflict) = block_hashes
+        .observe(starting_block, starting_block_hash)
+        .and_then(|_| block_hashes.observe(latest_block, latest_block_hash))
+    {
+        return Err(report_block_hash_conflict(conflict));
+    }
 
-    let logic_call_executed = web3
-        .check_for_events(
-            starting_block,
-            Some(latest_block),
-            vec![gravity_contract_address],
-            vec![LOGIC_CALL_EVENT_SIG],
-        )
-        .await;
-    trace!("Logic call executions {logic_call_executed:?}");
+    // all events are checked before any claims are sent, claims can not be retracted
+    if let Err(conflict) = block_hashes.observe_logs(&logs) {
+        return Err(report_block_hash_conflict(conflict));
+    }
 
-    if let (Ok(valsets), Ok(batches), Ok(deposits), Ok(deploys), Ok(logic_calls)) = (
+    let GravityEvents {
         valsets,
-        batches,
+        batches: withdraws,
         deposits,
-        erc20_deployed,
-        logic_call_executed,
-    ) {
-        let valsets = ValsetUpdatedEvent::from_logs(&valsets)?;
-        trace!("parsed valsets {valsets:?}");
-        let withdraws = TransactionBatchExecutedEvent::from_logs(&batches)?;
-        trace!("parsed batches {batches:?}");
-        let deposits = SendToCosmosEvent::from_logs(&deposits)?;
-        trace!("parsed deposits {deposits:?}");
-        let erc20_deploys = Erc20DeployedEvent::from_logs(&deploys)?;
-        trace!("parsed erc20 deploys {erc20_deploys:?}");
-        let logic_calls = LogicCallExecutedEvent::from_logs(&logic_calls)?;
-        trace!("logic call executions {logic_calls:?}");
+        erc20_deploys,
+        logic_calls,
+    } = GravityEvents::from_logs(&logs)?;
+    trace!("parsed valsets {valsets:?}");
+    trace!("parsed batches {withdraws:?}");
+    trace!("parsed deposits {deposits:?}");
+    trace!("parsed erc20 deploys {erc20_deploys:?}");
+    trace!("logic call executions {logic_calls:?}");
+
+    // note that starting block overlaps with our last checked block, because we have to deal with
+    // the possibility that the relayer was killed after relaying only one of multiple events in a single
+    // block, so we also need this routine so make sure we don't send in the first event in this hypothetical
+    // multi event block again. In theory we only send all events for every block and that will pass of fail
+    // atomicly but lets not take that risk.
+    let last_event_nonce =
+        get_last_event_nonce_for_validator(grpc_client, our_cosmos_address, contact.get_prefix())
+            .await?;
+    let valsets = ValsetUpdatedEvent::filter_by_event_nonce(last_event_nonce, &valsets);
+    let deposits = SendToCosmosEvent::filter_by_event_nonce(last_event_nonce, &deposits);
+    let withdraws =
+        TransactionBatchExecutedEvent::filter_by_event_nonce(last_event_nonce, &withdraws);
+    let erc20_deploys = Erc20DeployedEvent::filter_by_event_nonce(last_event_nonce, &erc20_deploys);
+    let logic_calls = LogicCallExecutedEvent::filter_by_event_nonce(last_event_nonce, &logic_calls);
+
+    if !valsets.is_empty() {
+        info!(
+            "Oracle observed Valset update with nonce {} and event nonce {}",
+            valsets[0].valset_nonce, valsets[0].event_nonce
+        )
+    }
+    if !deposits.is_empty() {
+        info!(
+            "Oracle observed deposit with sender {}, destination {:?}, amount {}, and event nonce {}",
+            deposits[0].sender, deposits[0].validated_destination, deposits[0].amount, deposits[0].event_nonce
+        )
+    }
+    if !withdraws.is_empty() {
+        info!(
+            "Oracle observed batch with nonce {}, contract {}, and event nonce {}",
+            withdraws[0].batch_nonce, withdraws[0].erc20, withdraws[0].event_nonce
+        )
+    }
+    if !erc20_deploys.is_empty() {
+        let v = erc20_deploys[0].clone();
+        if v.cosmos_denom.len() < 1000 && v.name.len() < 1000 && v.symbol.len() < 1000 {
+            info!(
+            "Oracle observed ERC20 deployment with denom {} erc20 name {} and symbol {} and event nonce {}",
+            erc20_deploys[0].cosmos_denom, erc20_deploys[0].name, erc20_deploys[0].symbol, erc20_deploys[0].event_nonce,
+            );
+        } else {
+            info!(
+                "Oracle observed ERC20 deployment with  event nonce {}",
+                erc20_deploys[0].event_nonce,
+            );
+        }
+    }
+    if !logic_calls.is_empty() {
+        info!(
+            "Oracle observed logic call execution with ID {} Nonce {} and event nonce {}",
+            bytes_to_hex_str(&logic_calls[0].invalidation_id),
+            logic_calls[0].invalidation_nonce,
+            logic_calls[0].event_nonce
+        )
+    }
 
-        // note that starting block overlaps with our last checked block, because we have to deal with
-        // the possibility that the relayer was killed after relaying only one of multiple events in a single
-        // block, so we also need this routine so make sure we don't send in the first event in this hypothetical
-        // multi event block again. In theory we only send all events for every block and that will pass of fail
-        // atomicly but lets not take that risk.
-        let last_event_nonce = get_last_event_nonce_for_validator(
+    if !deposits.is_empty()
+        || !withdraws.is_empty()
+        || !erc20_deploys.is_empty()
+        || !logic_calls.is_empty()
+        || !valsets.is_empty()
+    {
+        let res = send_ethereum_claims(
+            contact,
+            our_private_key,
+            deposits.clone(),
+            withdraws.clone(),
+            erc20_deploys.clone(),
+            logic_calls.clone(),
+            valsets.clone(),
+            fee,
+        )
+        .await?;
+        let new_event_nonce = get_last_event_nonce_for_validator(
             grpc_client,
             our_cosmos_address,
             contact.get_prefix(),
         )
         .await?;
-        let valsets = ValsetUpdatedEvent::filter_by_event_nonce(last_event_nonce, &valsets);
-        let deposits = SendToCosmosEvent::filter_by_event_nonce(last_event_nonce, &deposits);
-        let withdraws =
-            TransactionBatchExecutedEvent::filter_by_event_nonce(last_event_nonce, &withdraws);
-        let erc20_deploys =
-            Erc20DeployedEvent::filter_by_event_nonce(last_event_nonce, &erc20_deploys);
-        let logic_calls =
-            LogicCallExecutedEvent::filter_by_event_nonce(last_event_nonce, &logic_calls);
 
-        if !valsets.is_empty() {
-            info!(
-                "Oracle observed Valset update with nonce {} and event nonce {}",
-                valsets[0].valset_nonce, valsets[0].event_nonce
-            )
-        }
-        if !deposits.is_empty() {
-            info!(
-                "Oracle observed deposit with sender {}, destination {:?}, amount {}, and event nonce {}",
-                deposits[0].sender, deposits[0].validated_destination, deposits[0].amount, deposits[0].event_nonce
-            )
-        }
-        if !withdraws.is_empty() {
-            info!(
-                "Oracle observed batch with nonce {}, contract {}, and event nonce {}",
-                withdraws[0].batch_nonce, withdraws[0].erc20, withdraws[0].event_nonce
-            )
-        }
-        if !erc20_deploys.is_empty() {
-            let v = erc20_deploys[0].clone();
-            if v.cosmos_denom.len() < 1000 && v.name.len() < 1000 && v.symbol.len() < 1000 {
-                info!(
-                "Oracle observed ERC20 deployment with denom {} erc20 name {} and symbol {} and event nonce {}",
-                erc20_deploys[0].cosmos_denom, erc20_deploys[0].name, erc20_deploys[0].symbol, erc20_deploys[0].event_nonce,
-                );
-            } else {
-                info!(
-                    "Oracle observed ERC20 deployment with  event nonce {}",
-                    erc20_deploys[0].event_nonce,
-                );
-            }
-        }
-        if !logic_calls.is_empty() {
-            info!(
-                "Oracle observed logic call execution with ID {} Nonce {} and event nonce {}",
-                bytes_to_hex_str(&logic_calls[0].invalidation_id),
-                logic_calls[0].invalidation_nonce,
-                logic_calls[0].event_nonce
-            )
+        info!("Current event nonce is {new_event_nonce}");
+
+        // since we can't actually trust that the above txresponse is correct we have to check here
+        // we may be able to trust the tx response post grpc
+        if new_event_nonce == last_event_nonce {
+            return Err(GravityError::InvalidBridgeStateError(
+                format!("Claims did not process, trying to update but still on {last_event_nonce}, trying again in a moment, check txhash {res:?} for errors"),
+            ));
+        } else {
+            info!("Claims processed, new nonce {new_event_nonce}");
         }
 
-        if !deposits.is_empty()
-            || !withdraws.is_empty()
-            || !erc20_deploys.is_empty()
-            || !logic_calls.is_empty()
-            || !valsets.is_empty()
-        {
-            let res = send_ethereum_claims(
-                contact,
-                our_private_key,
-                deposits.clone(),
-                withdraws.clone(),
-                erc20_deploys.clone(),
-                logic_calls.clone(),
-                valsets.clone(),
-                fee,
-            )
-            .await?;
-            let new_event_nonce = get_last_event_nonce_for_validator(
-                grpc_client,
-                our_cosmos_address,
-                contact.get_prefix(),
-            )
-            .await?;
+        // find the eth block for our newest event nonce
+        let valsets = ValsetUpdatedEvent::get_block_for_nonce(new_event_nonce, &valsets);
+        let deposits = SendToCosmosEvent::get_block_for_nonce(new_event_nonce, &deposits);
+        let withdraws =
+            TransactionBatchExecutedEvent::get_block_for_nonce(new_event_nonce, &withdraws);
+        let erc20_deploys =
+            Erc20DeployedEvent::get_block_for_nonce(new_event_nonce, &erc20_deploys);
+        let logic_calls =
+            LogicCallExecutedEvent::get_block_for_nonce(new_event_nonce, &logic_calls);
 
-            info!("Current event nonce is {new_event_nonce}");
+        let block = match (valsets, deposits, withdraws, erc20_deploys, logic_calls) {
+            (Some(b), _, _, _, _) => b,
+            (_, Some(b), _, _, _) => b,
+            (_, _, Some(b), _, _) => b,
+            (_, _, _, Some(b), _) => b,
+            (_, _, _, _, Some(b)) => b,
+            _ => panic!("It's impossible for an event to be in more than one list!"),
+        };
 
-            // since we can't actually trust that the above txresponse is correct we have to check here
-            // we may be able to trust the tx response post grpc
-            if new_event_nonce == last_event_nonce {
-                return Err(GravityError::InvalidBridgeStateError(
-                    format!("Claims did not process, trying to update but still on {last_event_nonce}, trying again in a moment, check txhash {res:?} for errors"),
-                ));
-            } else {
-                info!("Claims processed, new nonce {new_event_nonce}");
-            }
+        Ok(CheckedNonces {
+            block_number: block,
+            event_nonce: new_event_nonce.into(),
+        })
+    } else {
+        // no changes
+        Ok(CheckedNonces {
+            block_number: latest_block,
+            event_nonce: last_event_nonce.into(),
+        })
+    }
+}
 
-            // find the eth block for our newest event nonce
-            let valsets = ValsetUpdatedEvent::get_block_for_nonce(new_event_nonce, &valsets);
-            let deposits = SendToCosmosEvent::get_block_for_nonce(new_event_nonce, &deposits);
-            let withdraws =
-                TransactionBatchExecutedEvent::get_block_for_nonce(new_event_nonce, &withdraws);
-            let erc20_deploys =
-                Erc20DeployedEvent::get_block_for_nonce(new_event_nonce, &erc20_deploys);
-            let logic_calls =
-                LogicCallExecutedEvent::get_block_for_nonce(new_event_nonce, &logic_calls);
+/// Fetches all Gravity contract event logs in the given block range using a single query, any of the
+/// event signatures in GRAVITY_EVENT_SIGS may match. Compared to a query per event type this cuts RPC
+/// load and ensures all events come from the same view of the chain
+pub async fn get_gravity_logs(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    start_block: Uint256,
+    end_block: Uint256,
+) -> Result<Vec<Log>, GravityError> {
+    let mut signatures = Vec::new();
+    for sig in GRAVITY_EVENT_SIGS {
+        signatures.push(Some(format!(
+            "0x{}",
+            bytes_to_hex_str(&derive_signature(sig)?)
+        )));
+    }
+    let filter = NewFilter {
+        address: vec![gravity_contract_address],
+        from_block: Some(format!("{start_block:#x}")),
+        to_block: Some(format!("{end_block:#x}")),
+        // a single topic position containing a list of values is an OR query
+        topics: Some(vec![Some(signatures)]),
+    };
+    Ok(web3.eth_get_logs(filter).await?)
+}
 
-            let block = match (valsets, deposits, withdraws, erc20_deploys, logic_calls) {
-                (Some(b), _, _, _, _) => b,
-                (_, Some(b), _, _, _) => b,
-                (_, _, Some(b), _, _) => b,
-                (_, _, _, Some(b), _) => b,
-                (_, _, _, _, Some(b)) => b,
-                _ => panic!("It's impossible for an event to be in more than one list!"),
-            };
+/// Fetches all Gravity contract event logs starting at `start_block` up to at most `end_block`, if the
+/// node rejects the query (usually because the response is too large) the range is halved until it
+/// succeeds or reaches MIN_BLOCKS_TO_SEARCH. Returns the logs and the last block actually searched
+pub async fn get_gravity_logs_adaptive(
+    eth_rpc: &EthQuorumClient,
+    gravity_contract_address: EthAddress,
+    start_block: Uint256,
+    end_block: Uint256,
+) -> Result<(Vec<Log>, Uint256), GravityError> {
+    search_adaptive(start_block, end_block, |start, end| {
+        eth_rpc.get_gravity_logs(gravity_contract_address, start, end)
+    })
+    .await
+}
 
-            Ok(CheckedNonces {
-                block_number: block,
-                event_nonce: new_event_nonce.into(),
-            })
-        } else {
-            // no changes
-            Ok(CheckedNonces {
-                block_number: latest_block,
-                event_nonce: last_event_nonce.into(),
-            })
+/// Calls `search` for the range `start_block` to `end_block`, halving the range each time it fails
+/// until it succeeds or reaches MIN_BLOCKS_TO_SEARCH. Returns the result and the last block searched
+async fn search_adaptive<T, F, Fut>(
+    start_block: Uint256,
+    end_block: Uint256,
+    mut search: F,
+) -> Result<(T, Uint256), GravityError>
+where
+    F: FnMut(Uint256, Uint256) -> Fut,
+    Fut: Future<Output = Result<T, GravityError>>,
+{
+    let mut end_block = end_block;
+    loop {
+        match search(start_block, end_block).await {
+            Ok(res) => return Ok((res, end_block)),
+            Err(e) => {
+                if end_block <= start_block
+                    || end_block - start_block <= MIN_BLOCKS_TO_SEARCH.into()
+                {
+                    return Err(e);
+                }
+                end_block = start_block + (end_block - start_block) / 2u8.into();
+                warn!("Failed to get events with {e}, retrying with a smaller range ending at {end_block}");
+                metrics_warnings_counter(1, "Failed to get events, reducing search range");
+            }
         }
-    } else {
-        error!("Failed to get events");
-        metrics_errors_counter(1, "Failed to get events");
-        Err(GravityError::EthereumRestError(Web3Error::BadResponse(
-            "Failed to get logs!".to_string(),
-        )))
     }
 }
 
+/// Loudly reports a block hash conflict to the operator, the oracle will not submit
+/// any further claims until it is restarted
+fn report_block_hash_conflict(conflict: BlockHashConflict) -> GravityError {
+    error!("{conflict}");
+    error!("Your Ethereum node has either experienced a reorg deeper than the safe block or is serving a non-canonical chain!");
+    error!("The Oracle will not submit any more claims, verify your Ethereum node against a trusted source before restarting");
+    metrics_errors_counter(1, "Ethereum block hash conflict, oracle halted");
+    metrics_block_hash_conflict();
+    GravityError::EthereumReorgError(conflict.to_string())
+}
+
 /// The latest 'safe block' for Ethereum event checking. This is used to prevent the bridge from
 /// accepting deposits that are not finalized and may be subject to a re-org, resulting in the attacker
 /// recieving tokens that are not actually in the bridge contract.
@@ -284,14 +345,14 @@ pub async fn check_for_events(
 /// https://eth2book.info/altair/part2/incentives/inactivity
 /// https://hackmd.io/@prysmaticlabs/finality
 ///
-pub async fn get_latest_safe_block(web3: &Web3) -> Uint256 {
-    let chainid = get_eth_chainid_with_retry(web3).await;
-    let block_number = get_block_number_with_retry(web3).await;
+pub async fn try_get_latest_safe_block(web3: &Web3) -> Result<Uint256, Web3Error> {
+    let chainid = web3.eth_chainid().await?;
+    let block_number = web3.eth_block_number().await?;
 
-    match chainid {
+    Ok(match chainid {
         // Mainline Ethereum, Ethereum classic, or the Ropsten, Kotti, Mordor testnets
         // all Ethereum proof of stake Chains
-        1 | 3 | 6 | 7 => get_finalized_block_with_retry(web3).await,
+        1 | 3 | 6 | 7 => web3.eth_get_finalized_block().await?.number,
         // Dev (geth --dev, chain id 1337), our own Gravity Ethereum testnet (15),
         // and Hardhat (31337) respectively. All single signer chains with no chance
         // of any reorgs, so we can use the chain tip directly with no confirmation delay.
@@ -302,5 +363,71 @@ pub async fn get_latest_safe_block(web3: &Web3) -> Uint256 {
         4 | 5 => block_number - 10u8.into(),
         // assume the safe option where we don't know
         _ => block_number - 96u8.into(),
+    })
+}
+
+/// Gets the latest safe block (see try_get_latest_safe_block), no matter how long it takes
+pub async fn get_latest_safe_block(web3: &Web3) -> Uint256 {
+    loop {
+        match try_get_latest_safe_block(web3).await {
+            Ok(block) => return block,
+            Err(e) => {
+                error!("Failed to get latest safe block! Is your Eth node working? Does it suppport the 'finalized' param? {e}");
+                delay_for(RETRY_TIME).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_search_adaptive() {
+        // a provider that rejects any range longer than 100 blocks
+        let searched = RefCell::new(Vec::new());
+        let search = |start: Uint256, end: Uint256| {
+            searched.borrow_mut().push((start, end));
+            async move {
+                if end - start > 100u8.into() {
+                    Err(GravityError::InvalidEventLogError(
+                        "too many results".to_string(),
+                    ))
+                } else {
+                    Ok(start)
+                }
+            }
+        };
+        let (res, end) = block_on(search_adaptive(1000u64.into(), 1800u64.into(), search)).unwrap();
+        assert_eq!(res, 1000u64.into());
+        assert_eq!(end, 1100u64.into());
+        let ends: Vec<Uint256> = searched.borrow().iter().map(|(_, e)| *e).collect();
+        assert_eq!(
+            ends,
+            vec![
+                1800u64.into(),
+                1400u64.into(),
+                1200u64.into(),
+                1100u64.into()
+            ]
+        );
+        assert!(searched.borrow().iter().all(|(s, _)| *s == 1000u64.into()));
+
+        // a range that succeeds is searched once
+        searched.borrow_mut().clear();
+        let (_, end) = block_on(search_adaptive(1000u64.into(), 1050u64.into(), search)).unwrap();
+        assert_eq!(end, 1050u64.into());
+        assert_eq!(searched.borrow().len(), 1);
+
+        // the range is not reduced below MIN_BLOCKS_TO_SEARCH
+        let failing = |_: Uint256, _: Uint256| async {
+            Err::<(), _>(GravityError::InvalidEventLogError(
+                "unavailable".to_string(),
+            ))
+        };
+        assert!(block_on(search_adaptive(1000u64.into(), 1800u64.into(), failing)).is_err());
     }
 }
@@ -1,6 +1,13 @@
 #[macro_use]
 extern crate log;
+#[macro_use]
+extern crate serde_derive;
 
+pub mod eth_quorum;
 pub mod ethereum_event_watcher;
 pub mod main_loop;
+pub mod oracle_checkpoint;
 pub mod oracle_resync;
+pub mod presign_validation;
+pub mod reorg_detection;
+pub mod signing_journal;
@@ -2,18 +2,32 @@
 //! that can only be run by a validator. This single binary the 'Orchestrator' runs not only these two rules but also the untrusted role of a relayer, that does not need any permissions and has it's
 //! own crate and binary so that anyone may run it.
 
-use crate::{ethereum_event_watcher::check_for_events, oracle_resync::get_last_checked_block};
+use crate::eth_quorum::EthQuorumClient;
+use crate::oracle_checkpoint::{OracleCheckpoint, OracleCheckpointStore};
+use crate::presign_validation::{
+    get_max_timeout_blocks, validate_batch, validate_logic_call, validate_valset,
+    validate_valset_members,
+};
+use crate::reorg_detection::BlockHashTracker;
+use crate::signing_journal::{
+    batch_checkpoint, logic_call_checkpoint, valset_checkpoint, SignedCheckpoint, SigningJournal,
+};
+use crate::{
+    ethereum_event_watcher::{check_for_events, CheckedNonces},
+    oracle_resync::get_last_checked_block,
+};
 use clarity::PrivateKey as EthPrivateKey;
 use clarity::{address::Address as EthAddress, Uint256};
-use cosmos_gravity::query::get_gravity_params;
+use cosmos_gravity::query::{get_gravity_params, get_last_observed_eth_block};
 use cosmos_gravity::{
     query::{
         get_oldest_unsigned_logic_calls, get_oldest_unsigned_transaction_batches,
-        get_oldest_unsigned_valsets,
+        get_oldest_unsigned_valsets, get_valset,
     },
     send::{send_batch_confirm, send_logic_call_confirm, send_valset_confirms},
     utils::get_last_event_nonce_with_retry,
 };
+use deep_space::address::Address as CosmosAddress;
 use deep_space::client::send::TransactionResponse;
 use deep_space::error::CosmosGrpcError;
 use deep_space::Contact;
@@ -24,11 +38,19 @@ use deep_space::{
 };
 use futures::future::{join, join3};
 use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
-use gravity_utils::types::GravityBridgeToolsConfig;
-use metrics_exporter::{metrics_errors_counter, metrics_latest, metrics_warnings_counter};
+use gravity_utils::connection_prep::CosmosGrpcPool;
+use gravity_utils::error::GravityError;
+use gravity_utils::eth_signer::EthSigner;
+use gravity_utils::types::{GravityBridgeToolsConfig, Valset};
+use metrics_exporter::{
+    metrics_cosmos_grpc_pool, metrics_errors_counter, metrics_latest, metrics_presign_rejection,
+    metrics_signing_conflict, metrics_warnings_counter,
+};
 use num_traits::ToPrimitive;
 use relayer::main_loop::all_relayer_loops;
 use std::cmp::min;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::exit;
 use std::time::Duration;
 use std::time::Instant;
@@ -49,49 +71,73 @@ pub const ETH_ORACLE_WAITING_SPEED: Duration = Duration::from_secs(90);
 /// meaning they will occupy the same thread, but since they do
 /// very little actual cpu bound work and spend the vast majority
 /// of all execution time sleeping this shouldn't be an issue at all.
+/// If `oracle_checkpoint_path` is provided the oracle will persist its progress there
+/// and use it to avoid a full history resync on restart. Confirm signatures are produced
+/// by `eth_signer`, `ethereum_key` is only used by the relayer. Every confirm is recorded in
+/// `signing_journal` before signing, if `signer_cross_check` is provided valsets are only signed
+/// if that Cosmos node agrees with the primary
 #[allow(clippy::too_many_arguments)]
 pub async fn orchestrator_main_loop(
     cosmos_key: CosmosPrivateKey,
     ethereum_key: EthPrivateKey,
+    eth_signer: EthSigner,
     web3: Web3,
-    contact: Contact,
-    grpc_client: GravityQueryClient<Channel>,
+    cosmos_pool: CosmosGrpcPool,
     gravity_contract_address: EthAddress,
     gravity_id: String,
     user_fee_amount: Coin,
     config: GravityBridgeToolsConfig,
-) {
+    oracle_checkpoint_path: Option<PathBuf>,
+    signing_journal: SigningJournal,
+    signer_cross_check: Option<GravityQueryClient<Channel>>,
+) -> Result<(), GravityError> {
     let fee = user_fee_amount;
 
+    let eth_rpc = EthQuorumClient::new(
+        web3.clone(),
+        &config.orchestrator.eth_rpc_quorum_endpoints,
+        config.orchestrator.eth_rpc_quorum,
+    )?;
+
     if config.orchestrator.check_eth_rpc {
         test_eth_connection(web3.clone()).await;
     }
 
+    if !config.orchestrator.eth_rpc_quorum_endpoints.is_empty() {
+        info!(
+            "Oracle requires {} of {} Ethereum RPC endpoints to agree",
+            eth_rpc.get_quorum(),
+            config.orchestrator.eth_rpc_quorum_endpoints.len() + 1
+        );
+    }
+
     let a = eth_oracle_main_loop(
         cosmos_key,
         web3.clone(),
-        contact.clone(),
-        grpc_client.clone(),
+        eth_rpc,
+        cosmos_pool.clone(),
         gravity_contract_address,
         fee.clone(),
+        oracle_checkpoint_path.map(OracleCheckpointStore::new),
     );
     let b = eth_signer_main_loop(
         cosmos_key,
-        ethereum_key,
-        contact.clone(),
-        grpc_client.clone(),
+        eth_signer,
+        web3.clone(),
+        cosmos_pool.clone(),
         fee.clone(),
+        signing_journal,
+        signer_cross_check,
     );
     let c = all_relayer_loops(
         Some(cosmos_key),
         ethereum_key,
         web3.clone(),
-        contact.clone(),
-        grpc_client.clone(),
+        cosmos_pool,
         gravity_contract_address,
         gravity_id,
         Some(fee.clone()),
-        config.relayer,
+        config.relayer.into(),
     );
 
     // if the relayer is not enabled we just don't start the relayer_main_loop or ibc_auto_forward_loop futures
@@ -100,6 +146,7 @@ pub async fn orchestrator_main_loop(
     } else {
         join(a, b).await;
     }
+    Ok(())
 }
 
 const DELAY: Duration = Duration::from_secs(5);
@@ -141,33 +188,49 @@ pub async fn test_eth_connection(web3: Web3) {
 
 /// This function is responsible for making sure that Ethereum events are retrieved from the Ethereum blockchain
 /// and ferried over to Cosmos where they will be used to issue tokens or process batches.
+/// Events are only acted upon if a quorum of the endpoints in `eth_rpc` agree on them
+#[allow(clippy::too_many_arguments)]
 pub async fn eth_oracle_main_loop(
     cosmos_key: CosmosPrivateKey,
     web3: Web3,
-    contact: Contact,
-    grpc_client: GravityQueryClient<Channel>,
+    eth_rpc: EthQuorumClient,
+    cosmos_pool: CosmosGrpcPool,
     gravity_contract_address: EthAddress,
     fee: Coin,
+    checkpoints: Option<OracleCheckpointStore>,
 ) {
+    let (contact, grpc_client) = cosmos_pool.get_healthy_connection().await;
     let our_cosmos_address = cosmos_key.to_address(&contact.get_prefix()).unwrap();
-    let long_timeout_web30 = Web3::new(&web3.get_url(), Duration::from_secs(120));
+    let long_timeout_eth_rpc = eth_rpc.with_timeout(Duration::from_secs(120));
     let mut last_checked_block: Uint256 = get_last_checked_block(
         grpc_client.clone(),
         our_cosmos_address,
         contact.get_prefix(),
         gravity_contract_address,
-        &long_timeout_web30,
+        &long_timeout_eth_rpc,
+        checkpoints.as_ref(),
     )
     .await;
 
     // In case of governance vote to unhalt bridge, need to replay old events. Keep track of the
     // last checked event nonce to detect when this happens
     let mut last_checked_event: Uint256 = 0u8.into();
+    // Hashes of every block we have examined, used to detect reorgs or a misbehaving Ethereum node
+    let mut block_hashes = BlockHashTracker::default();
     info!("Oracle resync complete, Oracle now operational");
-    let mut grpc_client = grpc_client;
 
     loop {
         let loop_start = Instant::now();
+        let (contact, mut grpc_client) = cosmos_pool.get_healthy_connection().await;
+        metrics_cosmos_grpc_pool(&cosmos_pool);
+
+        if let Some(conflict) = block_hashes.get_conflict() {
+            error!("Oracle halted! {conflict}");
+            error!("Verify your Ethereum node against a trusted source, then restart the Orchestrator. If invalid claims were submitted an OracleUnhaltProposal may be required");
+            metrics_errors_counter(1, "Oracle halted due to Ethereum block hash conflict");
+            delay_for(ETH_ORACLE_LOOP_SPEED).await;
+            continue;
+        }
 
         let latest_eth_block = web3.eth_block_number().await;
         let latest_cosmos_block = contact.get_chain_status().await;
@@ -234,20 +297,22 @@ pub async fn eth_oracle_main_loop(
                 our_cosmos_address,
                 contact.get_prefix(),
                 gravity_contract_address,
-                &web3,
+                &long_timeout_eth_rpc,
+                checkpoints.as_ref(),
             )
             .await;
         }
 
         // Relays events from Ethereum -> Cosmos
         match check_for_events(
-            &web3,
+            &eth_rpc,
             &contact,
             &mut grpc_client,
             gravity_contract_address,
             cosmos_key,
             fee.clone(),
             last_checked_block,
+            &mut block_hashes,
         )
         .await
         {
@@ -256,6 +321,18 @@ pub async fn eth_oracle_main_loop(
                 // nonces.event_nonce would return lower value than last_checked_event. We want to keep last_checked_event
                 // value so it could be used in the next iteration to check if we should return to the
                 // earlier block and continue from that point. CheckedNonces is accurate unless a governance vote happens.
+                if let Some(store) = &checkpoints {
+                    if nonces.block_number != last_checked_block {
+                        save_oracle_checkpoint(
+                            store,
+                            &eth_rpc,
+                            gravity_contract_address,
+                            our_cosmos_address,
+                            &nonces,
+                        )
+                        .await;
+                    }
+                }
                 last_checked_block = nonces.block_number;
                 if nonces.event_nonce > last_checked_event {
                     last_checked_event = nonces.event_nonce;
@@ -281,21 +358,59 @@ pub async fn eth_oracle_main_loop(
     }
 }
 
-/// The eth_signer simply signs off on any batches or validator sets provided by the validator
-/// since these are provided directly by a trusted Cosmsos node they can simply be assumed to be
-/// valid and signed off on.
+/// Records the oracle's progress in the checkpoint store, failures are logged but not fatal
+/// since the checkpoint store is only an optimization for restarts
+async fn save_oracle_checkpoint(
+    store: &OracleCheckpointStore,
+    eth_rpc: &EthQuorumClient,
+    gravity_contract_address: EthAddress,
+    our_cosmos_address: CosmosAddress,
+    nonces: &CheckedNonces,
+) {
+    let block_hash = match eth_rpc.get_block_hash(nonces.block_number).await {
+        Ok(h) => h,
+        Err(e) => {
+            warn!("Failed to get block hash for oracle checkpoint {e}");
+            return;
+        }
+    };
+    if let Err(e) = store.record(OracleCheckpoint {
+        gravity_contract_address,
+        orchestrator: our_cosmos_address,
+        block_number: nonces.block_number,
+        block_hash,
+        event_nonce: nonces.event_nonce,
+    }) {
+        warn!("Failed to save oracle checkpoint {e}");
+        metrics_warnings_counter(3, "Failed to save oracle checkpoint");
+    }
+}
+
+/// The eth_signer signs off on any batches or validator sets provided by the validator's Cosmos node.
+/// Since a compromised node could request conflicting signatures, which are slashable, every checkpoint
+/// is checked against and recorded in the signing journal first. Valsets may additionally be cross
+/// checked against a second Cosmos node
+#[allow(clippy::too_many_arguments)]
 pub async fn eth_signer_main_loop(
     cosmos_key: CosmosPrivateKey,
-    ethereum_key: EthPrivateKey,
-    contact: Contact,
-    grpc_client: GravityQueryClient<Channel>,
+    eth_signer: EthSigner,
+    web3: Web3,
+    cosmos_pool: CosmosGrpcPool,
     fee: Coin,
+    mut signing_journal: SigningJournal,
+    mut signer_cross_check: Option<GravityQueryClient<Channel>>,
 ) {
-    let our_cosmos_address = cosmos_key.to_address(&contact.get_prefix()).unwrap();
-    let mut grpc_client = grpc_client;
+    let our_cosmos_address = cosmos_key
+        .to_address(&cosmos_pool.get_contact().get_prefix())
+        .unwrap();
+    // the outcome of checking each unsigned valset's members against the chain, by valset nonce
+    let mut valset_checks: HashMap<u64, ValsetCheck> = HashMap::new();
 
     loop {
         let loop_start = Instant::now();
+        // fails over to the next healthy Cosmos gRPC endpoint, if any are configured
+        let (contact, mut grpc_client) = cosmos_pool.get_healthy_connection().await;
+        metrics_cosmos_grpc_pool(&cosmos_pool);
 
         // repeatedly refreshing the parameters here maintains loop correctness
         // if the gravity_id is changed or slashing windows are changed. Neither of these
@@ -313,6 +428,29 @@ pub async fn eth_signer_main_loop(
             params.signed_logic_calls_window,
         );
         let gravity_id = params.gravity_id;
+        // timeouts are validated against the last Ethereum height observed by the chain, the same height the
+        // module checks them against, if it is unavailable the timed out check is skipped
+        let observed_eth_height = match get_last_observed_eth_block(&mut grpc_client).await {
+            Ok(height) => height,
+            Err(e) => {
+                warn!("Could not get the last observed Ethereum height, timeouts will not be validated {e:?}");
+                0
+            }
+        };
+        // the current Ethereum height only bounds how far in the future a batch timeout may be
+        let eth_height = match get_eth_height(&web3).await {
+            Ok(height) => Some(height),
+            Err(e) => {
+                warn!(
+                    "Could not get the Ethereum height, batch timeouts will not be bounded {e:?}"
+                );
+                None
+            }
+        };
+        let max_timeout_blocks = get_max_timeout_blocks(
+            params.target_batch_timeout,
+            params.average_ethereum_block_time,
+        );
 
         let latest_cosmos_block = contact.get_chain_status().await;
         match latest_cosmos_block {
@@ -357,6 +495,21 @@ pub async fn eth_signer_main_loop(
         .await
         {
             Ok(valsets) => {
+                let valsets = presign_filter(valsets, "Valset", validate_valset);
+                let valsets = validate_valsets_against_chain(
+                    valsets,
+                    &mut valset_checks,
+                    &mut grpc_client,
+                    &contact,
+                )
+                .await;
+                let valsets = match signer_cross_check.as_mut() {
+                    Some(cross_check) => cross_check_valsets(valsets, cross_check).await,
+                    None => valsets,
+                };
+                let valsets = journal_checkpoints(&mut signing_journal, valsets, |v| {
+                    valset_checkpoint(&gravity_id, v)
+                });
                 if valsets.is_empty() {
                     trace!("No validator sets to sign, node is caught up!")
                 } else {
@@ -367,7 +520,7 @@ pub async fn eth_signer_main_loop(
                     );
                     let res = send_valset_confirms(
                         &contact,
-                        ethereum_key,
+                        &eth_signer,
                         fee.clone(),
                         valsets,
                         cosmos_key,
@@ -390,6 +543,13 @@ pub async fn eth_signer_main_loop(
         .await
         {
             Ok(last_unsigned_batches) => {
+                let last_unsigned_batches = presign_filter(last_unsigned_batches, "Batch", |b| {
+                    validate_batch(b, observed_eth_height, eth_height, max_timeout_blocks)
+                });
+                let last_unsigned_batches =
+                    journal_checkpoints(&mut signing_journal, last_unsigned_batches, |b| {
+                        batch_checkpoint(&gravity_id, b)
+                    });
                 if last_unsigned_batches.is_empty() {
                     trace!("No unsigned batch sets to sign, node is caught up!")
                 } else {
@@ -401,7 +561,7 @@ pub async fn eth_signer_main_loop(
 
                     let res = send_batch_confirm(
                         &contact,
-                        ethereum_key,
+                        &eth_signer,
                         fee.clone(),
                         last_unsigned_batches,
                         cosmos_key,
@@ -423,6 +583,13 @@ pub async fn eth_signer_main_loop(
         .await
         {
             Ok(last_unsigned_calls) => {
+                let last_unsigned_calls = presign_filter(last_unsigned_calls, "LogicCall", |c| {
+                    validate_logic_call(c, observed_eth_height)
+                });
+                let last_unsigned_calls =
+                    journal_checkpoints(&mut signing_journal, last_unsigned_calls, |c| {
+                        logic_call_checkpoint(&gravity_id, c)
+                    });
                 if last_unsigned_calls.is_empty() {
                     trace!("No unsigned call sets to sign, node is caught up!")
                 } else {
@@ -433,7 +600,7 @@ pub async fn eth_signer_main_loop(
                     );
                     let res = send_logic_call_confirm(
                         &contact,
-                        ethereum_key,
+                        &eth_signer,
                         fee.clone(),
                         last_unsigned_calls,
                         cosmos_key,
@@ -457,6 +624,149 @@ pub async fn eth_signer_main_loop(
     }
 }
 
+/// Gets the current Ethereum height as a u64
+async fn get_eth_height(web3: &Web3) -> Result<u64, GravityError> {
+    let height = web3.eth_block_number().await?;
+    height.to_u64().ok_or_else(|| {
+        GravityError::InvalidBridgeStateError(format!(
+            "Ethereum height {height} does not fit in a u64"
+        ))
+    })
+}
+
+/// Drops and reports any checkpoint that fails pre-sign validation
+fn presign_filter<T>(
+    items: Vec<T>,
+    checkpoint_type: &str,
+    validate: impl Fn(&T) -> Result<(), GravityError>,
+) -> Vec<T> {
+    let mut out = Vec::new();
+    for item in items {
+        match validate(&item) {
+            Ok(()) => out.push(item),
+            Err(e) => {
+                error!("Refusing to sign {checkpoint_type}, it failed validation! {e}");
+                error!("Your Cosmos node may be compromised! Investigate immediately");
+                metrics_presign_rejection(checkpoint_type);
+            }
+        }
+    }
+    out
+}
+
+/// The outcome of checking a valset's members against the chain, a valset never changes once created
+/// so this is only queried once per nonce
+#[derive(Debug, Clone)]
+enum ValsetCheck {
+    /// The valset may be signed, with an anomaly to report if there is one
+    Valid(Option<String>),
+    /// The valset must not be signed
+    Invalid(String),
+}
+
+/// Validates valset members against the chain's registered and bonded validators, valsets that can not
+/// be checked due to a query failure are left to be signed on a later iteration. Outcomes are kept in
+/// `checks` so the members are only queried once per valset nonce while it remains unsigned
+async fn validate_valsets_against_chain(
+    valsets: Vec<Valset>,
+    checks: &mut HashMap<u64, ValsetCheck>,
+    grpc_client: &mut GravityQueryClient<Channel>,
+    contact: &Contact,
+) -> Vec<Valset> {
+    checks.retain(|nonce, _| valsets.iter().any(|v| v.nonce == *nonce));
+    let mut out = Vec::new();
+    for valset in valsets {
+        let check = match checks.get(&valset.nonce) {
+            Some(check) => check.clone(),
+            None => {
+                let check = match validate_valset_members(&valset, grpc_client, contact).await {
+                    Ok(warning) => ValsetCheck::Valid(warning),
+                    Err(GravityError::InvalidBridgeStateError(e)) => ValsetCheck::Invalid(e),
+                    Err(e) => {
+                        warn!(
+                            "Could not validate valset {} against the chain, not signing it yet {e}",
+                            valset.nonce
+                        );
+                        continue;
+                    }
+                };
+                checks.insert(valset.nonce, check.clone());
+                check
+            }
+        };
+        match check {
+            ValsetCheck::Valid(warning) => {
+                if let Some(warning) = warning {
+                    warn!("Valset anomaly, signing anyways {warning}");
+                    metrics_warnings_counter(2, "Valset anomaly");
+                }
+                out.push(valset)
+            }
+            ValsetCheck::Invalid(e) => {
+                error!("Refusing to sign Valset, it failed validation! {e}");
+                error!("Your Cosmos node may be compromised! Investigate immediately");
+                metrics_presign_rejection("Valset");
+            }
+        }
+    }
+    out
+}
+
+/// Records each checkpoint in the signing journal before it is signed, dropping any that conflict
+/// with a checkpoint we have already signed or that could not be recorded
+fn journal_checkpoints<T>(
+    journal: &mut SigningJournal,
+    items: Vec<T>,
+    to_checkpoint: impl Fn(&T) -> SignedCheckpoint,
+) -> Vec<T> {
+    let mut out = Vec::new();
+    for item in items {
+        let checkpoint = to_checkpoint(&item);
+        if let Err(conflict) = journal.check(&checkpoint) {
+            error!("{conflict}");
+            error!("Your Cosmos node may be compromised! Investigate immediately, signing this would get you slashed");
+            metrics_signing_conflict(&format!("{:?}", checkpoint.checkpoint_type));
+            continue;
+        }
+        match journal.record(checkpoint) {
+            Ok(()) => out.push(item),
+            Err(e) => {
+                error!("Failed to write to the signing journal, refusing to sign {e}");
+                metrics_errors_counter(0, "Failed to write to the signing journal");
+            }
+        }
+    }
+    out
+}
+
+/// Returns only the valsets that the cross check Cosmos node reports identically
+async fn cross_check_valsets(
+    valsets: Vec<Valset>,
+    cross_check: &mut GravityQueryClient<Channel>,
+) -> Vec<Valset> {
+    let mut out = Vec::new();
+    for valset in valsets {
+        match get_valset(cross_check, valset.nonce).await {
+            Ok(Some(v)) if v == valset => out.push(valset),
+            Ok(v) => {
+                error!(
+                    "Cross check Cosmos node disagrees on valset {}, refusing to sign! Primary {:?} cross check {:?}",
+                    valset.nonce, valset, v
+                );
+                metrics_signing_conflict("Valset");
+            }
+            Err(e) => {
+                warn!(
+                    "Could not cross check valset {} not signing it yet {e}",
+                    valset.nonce
+                );
+                metrics_warnings_counter(2, "Could not cross check valset");
+            }
+        }
+    }
+    out
+}
+
 /// Checks for fee errors on our confirm submission transactions, a failure here
 /// can be fatal and cause slashing so we want to warn the user and exit. There is
 /// no point in running if we can't perform our most important function
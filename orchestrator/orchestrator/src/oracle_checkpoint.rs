@@ -0,0 +1,192 @@
+//! The oracle checkpoint store persists the progress of the Ethereum oracle to disk. On restart
+//! the oracle can resume from the newest checkpoint that is still consistent with both the Ethereum
+//! chain and the Gravity chain, instead of walking backwards through the entire Ethereum history
+//! as is done in oracle_resync.rs
+
+use crate::eth_quorum::EthQuorumClient;
+use clarity::{Address as EthAddress, Uint256};
+use deep_space::address::Address as CosmosAddress;
+use gravity_utils::error::GravityError;
+use std::fs;
+use std::path::PathBuf;
+use web30::client::Web3;
+use web30::jsonrpc::error::Web3Error;
+
+/// The number of checkpoints retained on disk, older checkpoints are useful
+/// only if a governance unhalt vote has reset our event nonce to a lower value
+pub const MAX_CHECKPOINTS: usize = 32;
+
+/// A point in Ethereum history that the oracle has fully processed, at `block_number`
+/// (with hash `block_hash`) the last event nonce submitted by `orchestrator` was `event_nonce`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OracleCheckpoint {
+    pub gravity_contract_address: EthAddress,
+    pub orchestrator: CosmosAddress,
+    pub block_number: Uint256,
+    pub block_hash: Uint256,
+    pub event_nonce: Uint256,
+}
+
+/// A simple json file backed store of oracle checkpoints, ordered oldest to newest
+#[derive(Debug, Clone)]
+pub struct OracleCheckpointStore {
+    path: PathBuf,
+}
+
+impl OracleCheckpointStore {
+    pub fn new(path: PathBuf) -> OracleCheckpointStore {
+        OracleCheckpointStore { path }
+    }
+
+    /// Loads all checkpoints on disk, a missing file is treated as an empty store
+    pub fn load(&self) -> Result<Vec<OracleCheckpoint>, GravityError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(&self.path)?;
+        let checkpoints: Vec<OracleCheckpoint> =
+            serde_json::from_str(&contents).map_err(std::io::Error::from)?;
+        Ok(checkpoints)
+    }
+
+    /// Returns the checkpoints for the given contract and orchestrator, newest first
+    pub fn get_checkpoints(
+        &self,
+        gravity_contract_address: EthAddress,
+        orchestrator: CosmosAddress,
+    ) -> Result<Vec<OracleCheckpoint>, GravityError> {
+        let mut checkpoints: Vec<OracleCheckpoint> = self
+            .load()?
+            .into_iter()
+            .filter(|c| {
+                c.gravity_contract_address == gravity_contract_address
+                    && c.orchestrator == orchestrator
+            })
+            .collect();
+        checkpoints.reverse();
+        Ok(checkpoints)
+    }
+
+    /// Appends a checkpoint to the store, pruning the oldest entries beyond MAX_CHECKPOINTS.
+    /// The file is written to a temporary location and then renamed so that a crash mid
+    /// write can not leave a corrupted store behind
+    pub fn record(&self, checkpoint: OracleCheckpoint) -> Result<(), GravityError> {
+        // a corrupted store is simply replaced, it will fail validation anyways
+        let mut checkpoints = self.load().unwrap_or_default();
+        if checkpoints.last() == Some(&checkpoint) {
+            return Ok(());
+        }
+        checkpoints.push(checkpoint);
+        if checkpoints.len() > MAX_CHECKPOINTS {
+            checkpoints.drain(0..checkpoints.len() - MAX_CHECKPOINTS);
+        }
+
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(
+            &tmp_path,
+            serde_json::to_string_pretty(&checkpoints).map_err(std::io::Error::from)?,
+        )?;
+        fs::rename(tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Gets the hash of the Ethereum block at the given height
+pub async fn get_block_hash(web3: &Web3, block_number: Uint256) -> Result<Uint256, Web3Error> {
+    Ok(web3
+        .eth_get_concise_block_by_number(block_number)
+        .await?
+        .hash)
+}
+
+/// Searches the checkpoint store for the newest checkpoint that can be safely resumed from. A checkpoint is
+/// valid only if its event nonce matches the last event nonce the Gravity chain has for this orchestrator,
+/// it is not ahead of the latest safe block, and the block hash still matches the canonical Ethereum chain as
+/// agreed on by the quorum. Returns None if no checkpoint can be validated, in which case the caller should
+/// fall back to a full resync
+pub async fn find_valid_checkpoint(
+    store: &OracleCheckpointStore,
+    gravity_contract_address: EthAddress,
+    orchestrator: CosmosAddress,
+    last_event_nonce: Uint256,
+    latest_safe_block: Uint256,
+    eth_rpc: &EthQuorumClient,
+) -> Option<OracleCheckpoint> {
+    let checkpoints = match store.get_checkpoints(gravity_contract_address, orchestrator) {
+        Ok(c) => c,
+        Err(e) => {
+            warn!("Could not read oracle checkpoints, performing a full resync {e}");
+            return None;
+        }
+    };
+
+    for checkpoint in checkpoints {
+        if checkpoint.event_nonce != last_event_nonce || checkpoint.block_number > latest_safe_block
+        {
+            continue;
+        }
+        match eth_rpc.get_block_hash(checkpoint.block_number).await {
+            Ok(hash) => {
+                if hash == checkpoint.block_hash {
+                    return Some(checkpoint);
+                }
+                warn!(
+                    "Oracle checkpoint at block {} has hash {:#x} but the chain has {:#x}, ignoring it",
+                    checkpoint.block_number, checkpoint.block_hash, hash
+                );
+            }
+            Err(e) => {
+                warn!("Could not verify oracle checkpoint against Ethereum, performing a full resync {e}");
+                return None;
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity::constants::zero_address;
+    use deep_space::private_key::{CosmosPrivateKey, PrivateKey};
+
+    #[test]
+    fn test_checkpoint_store_pruning_and_order() {
+        let path = std::env::temp_dir().join(format!(
+            "gravity_test_checkpoint_store_pruning_and_order_{}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        let store = OracleCheckpointStore::new(path.clone());
+        let contract =
+            EthAddress::parse_and_validate("0x70EDF1c215D0ce69E7F16FD4E6276ba0d99d4de7").unwrap();
+        let orchestrator = CosmosPrivateKey::from_secret(&[1u8; 32])
+            .to_address("gravity")
+            .unwrap();
+
+        for i in 0..(MAX_CHECKPOINTS as u64 + 10) {
+            let checkpoint = OracleCheckpoint {
+                gravity_contract_address: contract,
+                orchestrator,
+                block_number: i.into(),
+                block_hash: (i + 1000).into(),
+                event_nonce: (i / 2).into(),
+            };
+            store.record(checkpoint.clone()).unwrap();
+            // duplicates are not stored twice
+            store.record(checkpoint).unwrap();
+        }
+
+        let checkpoints = store.get_checkpoints(contract, orchestrator).unwrap();
+        assert_eq!(checkpoints.len(), MAX_CHECKPOINTS);
+        assert_eq!(
+            checkpoints[0].block_number,
+            (MAX_CHECKPOINTS as u64 + 9).into()
+        );
+        assert!(store
+            .get_checkpoints(zero_address(), orchestrator)
+            .unwrap()
+            .is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,364 @@
+//! Validation performed by the Eth signer before signing anything provided by the Cosmos node. The Cosmos
+//! module should never produce a malformed batch, valset, or logic call, so any failure here indicates a
+//! compromised or malfunctioning node and is refused and reported rather than signed.
+//!
+//! Care is taken to only refuse checkpoints that are invalid beyond doubt, refusing to sign a legitimate
+//! checkpoint is itself slashable. Softer anomalies are reported but still signed.
+
+use clarity::Address as EthAddress;
+use clarity::Uint256;
+use deep_space::Contact;
+use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
+use gravity_proto::gravity::v1::QueryDelegateKeysByEthAddress;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::{
+    LogicCall, TransactionBatch, Valset, ValsetMember, GRAVITY_POWER_TO_PASS, TOTAL_GRAVITY_POWER,
+};
+use num_traits::CheckedAdd;
+use sha3::{Digest, Keccak256};
+use std::collections::HashSet;
+use tonic::transport::Channel;
+
+/// Batch timeouts are projected from the chain's target_batch_timeout and average_ethereum_block_time,
+/// a timeout more than this multiple of the projection into the future is refused
+pub const MAX_TIMEOUT_MULTIPLE: u64 = 4;
+
+fn invalid(msg: String) -> Result<(), GravityError> {
+    Err(GravityError::InvalidBridgeStateError(msg))
+}
+
+/// The furthest into the future, in Ethereum blocks, a batch timeout is expected to be
+pub fn get_max_timeout_blocks(target_batch_timeout_ms: u64, average_block_time_ms: u64) -> u64 {
+    if average_block_time_ms == 0 {
+        return u64::MAX;
+    }
+    (target_batch_timeout_ms / average_block_time_ms).saturating_mul(MAX_TIMEOUT_MULTIPLE)
+}
+
+/// Checks the internal consistency of a batch, every transaction and fee must be in the batch token, the
+/// total fee must be the sum of the fees, and ids must be unique. The timeout must be past the last Ethereum
+/// height observed by the chain, which is what the module checks against, and if the current Ethereum height
+/// is known no further than `max_timeout_blocks` past it
+pub fn validate_batch(
+    batch: &TransactionBatch,
+    observed_eth_height: u64,
+    eth_height: Option<u64>,
+    max_timeout_blocks: u64,
+) -> Result<(), GravityError> {
+    if batch.transactions.is_empty() {
+        return invalid(format!("Batch {} has no transactions", batch.nonce));
+    }
+    let mut ids = HashSet::new();
+    let mut fee_total: Uint256 = 0u8.into();
+    for tx in batch.transactions.iter() {
+        if !ids.insert(tx.id) {
+            return invalid(format!(
+                "Batch {} contains transaction {} twice",
+                batch.nonce, tx.id
+            ));
+        }
+        if tx.erc20_token.token_contract_address != batch.token_contract
+            || tx.erc20_fee.token_contract_address != batch.token_contract
+        {
+            return invalid(format!(
+                "Batch {} for {} contains transaction {} in another token",
+                batch.nonce, batch.token_contract, tx.id
+            ));
+        }
+        fee_total = match fee_total.checked_add(&tx.erc20_fee.amount) {
+            Some(v) => v,
+            None => return invalid(format!("Batch {} fees overflow", batch.nonce)),
+        };
+    }
+    if batch.total_fee.token_contract_address != batch.token_contract
+        || batch.total_fee.amount != fee_total
+    {
+        return invalid(format!(
+            "Batch {} total fee {} does not match the transaction fees {}",
+            batch.nonce, batch.total_fee.amount, fee_total
+        ));
+    }
+    if batch.batch_timeout <= observed_eth_height {
+        return invalid(format!(
+            "Batch {} timed out at {} before the last observed Ethereum height {}",
+            batch.nonce, batch.batch_timeout, observed_eth_height
+        ));
+    }
+    if let Some(eth_height) = eth_height {
+        let eth_height = eth_height.max(observed_eth_height);
+        if batch.batch_timeout - eth_height > max_timeout_blocks {
+            return invalid(format!(
+                "Batch {} timeout {} is unreasonably far past the current Ethereum height {}",
+                batch.nonce, batch.batch_timeout, eth_height
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The EIP-55 checksummed hex of an address, the form the Cosmos module compares addresses in
+fn checksummed_hex(address: &EthAddress) -> String {
+    let hex = address.to_string().trim_start_matches("0x").to_lowercase();
+    let hash = Keccak256::digest(hex.as_bytes());
+    let checksummed: String = hex
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{checksummed}")
+}
+
+/// Returns true if `a` may come before `b` in a valset, a copy of the module's InternalBridgeValidators
+/// Sort. Power is sorted greatest to least and ties are broken by EthAddrLessThan, which compares the
+/// checksummed hex strings. Mixed case hex does not sort like the address bytes, so the Ord impl for
+/// ValsetMember can not be used here
+fn sorted_before(a: &ValsetMember, b: &ValsetMember) -> bool {
+    if a.power == b.power {
+        checksummed_hex(&a.eth_address) <= checksummed_hex(&b.eth_address)
+    } else {
+        a.power > b.power
+    }
+}
+
+/// Checks that a valset is sorted the same way as the Cosmos module sorts it, has no duplicate members,
+/// and that its total power is normalized and sufficient to ever pass anything on the bridge. Members
+/// with zero power are legitimate, the module's power normalization rounds very small validators down to 0
+pub fn validate_valset(valset: &Valset) -> Result<(), GravityError> {
+    let mut addresses = HashSet::new();
+    let mut total: u64 = 0;
+    for member in valset.members.iter() {
+        if !addresses.insert(member.eth_address) {
+            return invalid(format!(
+                "Valset {} contains {} twice",
+                valset.nonce, member.eth_address
+            ));
+        }
+        total = total.saturating_add(member.power);
+    }
+    if !valset
+        .members
+        .windows(2)
+        .all(|w| sorted_before(&w[0], &w[1]))
+    {
+        return invalid(format!("Valset {} members are not sorted", valset.nonce));
+    }
+    if total > TOTAL_GRAVITY_POWER || total <= GRAVITY_POWER_TO_PASS {
+        return invalid(format!(
+            "Valset {} total power {} is not between {} and {}",
+            valset.nonce, total, GRAVITY_POWER_TO_PASS, TOTAL_GRAVITY_POWER
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the valset members against the chain, every member must be a registered delegate Ethereum key.
+/// Members who are no longer bonded are normal for older valsets, but if the bonded members could not pass
+/// anything on the bridge the anomaly is returned as a warning
+pub async fn validate_valset_members(
+    valset: &Valset,
+    grpc_client: &mut GravityQueryClient<Channel>,
+    contact: &Contact,
+) -> Result<Option<String>, GravityError> {
+    let bonded: HashSet<String> = contact
+        .get_active_validators()
+        .await?
+        .into_iter()
+        .map(|v| v.operator_address)
+        .collect();
+    let mut bonded_power: u64 = 0;
+    for member in valset.members.iter() {
+        let res = grpc_client
+            .get_delegate_key_by_eth(QueryDelegateKeysByEthAddress {
+                eth_address: member.eth_address.to_string(),
+            })
+            .await;
+        match res {
+            Ok(keys) => {
+                if bonded.contains(&keys.into_inner().validator_address) {
+                    bonded_power += member.power;
+                }
+            }
+            Err(e)
+                if e.code() == tonic::Code::NotFound
+                    || e.code() == tonic::Code::InvalidArgument =>
+            {
+                return Err(GravityError::InvalidBridgeStateError(format!(
+                    "Valset {} member {} is not a registered delegate key",
+                    valset.nonce, member.eth_address
+                )))
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    if bonded_power <= GRAVITY_POWER_TO_PASS {
+        return Ok(Some(format!(
+            "Only {bonded_power} of valset {} power belongs to currently bonded validators",
+            valset.nonce
+        )));
+    }
+    Ok(None)
+}
+
+/// Checks that a logic call has not timed out as of the last observed Ethereum height and has a valid
+/// invalidation id
+pub fn validate_logic_call(call: &LogicCall, observed_eth_height: u64) -> Result<(), GravityError> {
+    if call.invalidation_id.is_empty() || call.invalidation_id.len() > 32 {
+        return invalid(format!(
+            "Logic call {} has an invalid invalidation id",
+            call.invalidation_nonce
+        ));
+    }
+    if call.timeout <= observed_eth_height {
+        return invalid(format!(
+            "Logic call {} timed out at {} before the last observed Ethereum height {}",
+            call.invalidation_nonce, call.timeout, observed_eth_height
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use deep_space::private_key::{CosmosPrivateKey, PrivateKey};
+    use gravity_utils::types::{BatchTransaction, Erc20Token};
+
+    #[test]
+    fn test_validate_batch() {
+        let contract =
+            EthAddress::parse_and_validate("0x70EDF1c215D0ce69E7F16FD4E6276ba0d99d4de7").unwrap();
+        let other =
+            EthAddress::parse_and_validate("0x00000000219ab540356cBB839Cbe05303d7705Fa").unwrap();
+        let sender = CosmosPrivateKey::from_secret(&[1u8; 32])
+            .to_address("gravity")
+            .unwrap();
+        let token = |amount: u64, token_contract_address| Erc20Token {
+            amount: amount.into(),
+            token_contract_address,
+        };
+        let tx = |id, fee| BatchTransaction {
+            id,
+            sender,
+            destination: other,
+            erc20_token: token(100, contract),
+            erc20_fee: token(fee, contract),
+        };
+        let mut batch = TransactionBatch {
+            nonce: 1,
+            batch_timeout: 1100,
+            transactions: vec![tx(1, 5), tx(2, 7)],
+            total_fee: token(12, contract),
+            token_contract: contract,
+            cosmos_block_created: 0,
+        };
+        validate_batch(&batch, 1000, Some(1000), 200).unwrap();
+        assert!(validate_batch(&batch, 1100, Some(1100), 200).is_err());
+        assert!(validate_batch(&batch, 800, Some(800), 200).is_err());
+        // only the observed height can time a batch out, the Ethereum tip may be ahead of it
+        validate_batch(&batch, 1000, Some(1100), 200).unwrap();
+        assert!(validate_batch(&batch, 1100, Some(1000), 200).is_err());
+        // without the Ethereum tip the timeout is not bounded
+        validate_batch(&batch, 800, None, 200).unwrap();
+
+        batch.total_fee = token(13, contract);
+        assert!(validate_batch(&batch, 1000, Some(1000), 200).is_err());
+        batch.total_fee = token(12, contract);
+
+        batch.transactions[1].id = 1;
+        assert!(validate_batch(&batch, 1000, Some(1000), 200).is_err());
+        batch.transactions[1].id = 2;
+
+        batch.transactions[1].erc20_fee = token(7, other);
+        assert!(validate_batch(&batch, 1000, Some(1000), 200).is_err());
+    }
+
+    #[test]
+    fn test_validate_valset() {
+        let a =
+            EthAddress::parse_and_validate("0x70EDF1c215D0ce69E7F16FD4E6276ba0d99d4de7").unwrap();
+        let b =
+            EthAddress::parse_and_validate("0x00000000219ab540356cBB839Cbe05303d7705Fa").unwrap();
+        let mut valset = Valset {
+            nonce: 1,
+            members: vec![
+                ValsetMember {
+                    power: 3_000_000_000,
+                    eth_address: a,
+                },
+                ValsetMember {
+                    power: 1_000_000_000,
+                    eth_address: b,
+                },
+            ],
+            reward_amount: 0u8.into(),
+            reward_token: None,
+        };
+        validate_valset(&valset).unwrap();
+
+        valset.members.reverse();
+        assert!(validate_valset(&valset).is_err());
+        valset.members.reverse();
+
+        valset.members[1].eth_address = a;
+        assert!(validate_valset(&valset).is_err());
+        valset.members[1].eth_address = b;
+
+        // normalization can round a small validator down to zero power
+        valset.members.push(ValsetMember {
+            power: 0,
+            eth_address: EthAddress::parse_and_validate(
+                "0x0000000000000000000000000000000000000001",
+            )
+            .unwrap(),
+        });
+        validate_valset(&valset).unwrap();
+        valset.members.pop();
+
+        valset.members[0].power = 1_500_000_000;
+        assert!(validate_valset(&valset).is_err());
+    }
+
+    #[test]
+    fn test_validate_valset_tied_power() {
+        // the byte order of these addresses is the reverse of the order of their checksummed hex
+        let a =
+            EthAddress::parse_and_validate("0xaBabaBABaBaBABABaBabAbaBAbabABabababaB03").unwrap();
+        let b =
+            EthAddress::parse_and_validate("0xAbABaBAbAbABAbAbaBaBaBababababaBAbAbab04").unwrap();
+        assert!(a.to_string().to_lowercase() < b.to_string().to_lowercase());
+        assert_eq!(
+            checksummed_hex(&a),
+            "0xaBabaBABaBaBABABaBabAbaBAbabABabababaB03"
+        );
+        assert_eq!(
+            checksummed_hex(&b),
+            "0xAbABaBAbAbABAbAbaBaBaBababababaBAbAbab04"
+        );
+        let mut valset = Valset {
+            nonce: 1,
+            members: vec![
+                ValsetMember {
+                    power: 2_000_000_000,
+                    eth_address: b,
+                },
+                ValsetMember {
+                    power: 2_000_000_000,
+                    eth_address: a,
+                },
+            ],
+            reward_amount: 0u8.into(),
+            reward_token: None,
+        };
+        validate_valset(&valset).unwrap();
+
+        valset.members.reverse();
+        assert!(validate_valset(&valset).is_err());
+    }
+}
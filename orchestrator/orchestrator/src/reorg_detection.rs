@@ -0,0 +1,129 @@
+//! Tracks the hashes of Ethereum blocks the oracle has derived claims from. Claims submitted to Cosmos
+//! can not be retracted, so if a block we have already seen changes hash, either due to a reorg deeper than
+//! our safe block or a misbehaving Ethereum RPC returning a non-canonical chain, the oracle must stop
+//! submitting claims immediately and alert the operator.
+
+use clarity::Uint256;
+use std::collections::BTreeMap;
+use std::fmt;
+use web30::types::Log;
+
+/// The maximum number of block hashes retained, the oldest are dropped first. Blocks this far
+/// behind the oracle are well past finality and are not expected to change
+pub const MAX_TRACKED_BLOCKS: usize = 10_000;
+
+/// Two different hashes observed for the same Ethereum block height
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHashConflict {
+    pub block_number: Uint256,
+    /// The hash first observed for this block
+    pub expected_hash: Uint256,
+    /// The newly observed, conflicting hash
+    pub observed_hash: Uint256,
+}
+
+impl fmt::Display for BlockHashConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Ethereum block {} previously had hash {:#x} but now has hash {:#x}",
+            self.block_number, self.expected_hash, self.observed_hash
+        )
+    }
+}
+
+/// Records the hash of every block the oracle has observed, once a conflict is detected
+/// it is retained so that the oracle remains halted until the operator intervenes
+#[derive(Debug, Clone, Default)]
+pub struct BlockHashTracker {
+    seen: BTreeMap<Uint256, Uint256>,
+    conflict: Option<BlockHashConflict>,
+}
+
+impl BlockHashTracker {
+    /// Records the hash for the given block, returning the conflict if a different
+    /// hash has previously been observed for the same block
+    pub fn observe(
+        &mut self,
+        block_number: Uint256,
+        block_hash: Uint256,
+    ) -> Result<(), BlockHashConflict> {
+        if let Some(expected_hash) = self.seen.get(&block_number) {
+            if *expected_hash != block_hash {
+                let conflict = BlockHashConflict {
+                    block_number,
+                    expected_hash: *expected_hash,
+                    observed_hash: block_hash,
+                };
+                self.conflict = Some(conflict.clone());
+                return Err(conflict);
+            }
+            return Ok(());
+        }
+
+        self.seen.insert(block_number, block_hash);
+        while self.seen.len() > MAX_TRACKED_BLOCKS {
+            let oldest = *self.seen.keys().next().unwrap();
+            self.seen.remove(&oldest);
+        }
+        Ok(())
+    }
+
+    /// Records the block hashes of all the provided logs. Logs flagged as removed by the
+    /// node are also treated as a conflict since they may have already been claimed
+    pub fn observe_logs(&mut self, logs: &[Log]) -> Result<(), BlockHashConflict> {
+        for log in logs {
+            if let (Some(block_number), Some(block_hash)) = (log.block_number, &log.block_hash) {
+                let block_hash = Uint256::from_be_bytes(block_hash);
+                if log.removed == Some(true) {
+                    let conflict = BlockHashConflict {
+                        block_number,
+                        expected_hash: block_hash,
+                        observed_hash: 0u8.into(),
+                    };
+                    self.conflict = Some(conflict.clone());
+                    return Err(conflict);
+                }
+                self.observe(block_number, block_hash)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The first conflict detected, if any
+    pub fn get_conflict(&self) -> Option<&BlockHashConflict> {
+        self.conflict.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_hash_conflict() {
+        let mut tracker = BlockHashTracker::default();
+        tracker.observe(1u8.into(), 100u8.into()).unwrap();
+        tracker.observe(2u8.into(), 200u8.into()).unwrap();
+        // seeing the same hash again is fine
+        tracker.observe(1u8.into(), 100u8.into()).unwrap();
+        assert!(tracker.get_conflict().is_none());
+
+        let conflict = tracker.observe(2u8.into(), 201u8.into()).unwrap_err();
+        assert_eq!(conflict.block_number, 2u8.into());
+        assert_eq!(conflict.expected_hash, 200u8.into());
+        assert_eq!(conflict.observed_hash, 201u8.into());
+        assert_eq!(tracker.get_conflict(), Some(&conflict));
+    }
+
+    #[test]
+    fn test_block_hash_pruning() {
+        let mut tracker = BlockHashTracker::default();
+        for i in 0..(MAX_TRACKED_BLOCKS as u64 + 5) {
+            tracker.observe(i.into(), i.into()).unwrap();
+        }
+        assert_eq!(tracker.seen.len(), MAX_TRACKED_BLOCKS);
+        // the oldest blocks have been forgotten
+        tracker.observe(0u8.into(), 1u8.into()).unwrap();
+    }
+}
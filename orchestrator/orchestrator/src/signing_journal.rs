@@ -0,0 +1,110 @@
+//! Builds the signing journal entries for the checkpoints the Eth signer signs, the journal itself is
+//! shared with the remote signer, see gravity_utils::signing_journal
+
+use clarity::utils::bytes_to_hex_str;
+use ethereum_gravity::message_signatures::{
+    encode_logic_call_confirm_hashed, encode_tx_batch_confirm_hashed, encode_valset_confirm_hashed,
+};
+pub use gravity_utils::signing_journal::{
+    CheckpointType, SignedCheckpoint, SigningConflict, SigningJournal,
+};
+use gravity_utils::types::{LogicCall, TransactionBatch, Valset};
+
+pub fn valset_checkpoint(gravity_id: &str, valset: &Valset) -> SignedCheckpoint {
+    SignedCheckpoint {
+        checkpoint_type: CheckpointType::Valset,
+        nonce: valset.nonce,
+        scope: String::new(),
+        checkpoint_hash: bytes_to_hex_str(&encode_valset_confirm_hashed(
+            gravity_id.to_string(),
+            valset.clone(),
+        )),
+    }
+}
+
+pub fn batch_checkpoint(gravity_id: &str, batch: &TransactionBatch) -> SignedCheckpoint {
+    SignedCheckpoint {
+        checkpoint_type: CheckpointType::Batch,
+        nonce: batch.nonce,
+        scope: batch.token_contract.to_string(),
+        checkpoint_hash: bytes_to_hex_str(&encode_tx_batch_confirm_hashed(
+            gravity_id.to_string(),
+            batch.clone(),
+        )),
+    }
+}
+
+pub fn logic_call_checkpoint(gravity_id: &str, call: &LogicCall) -> SignedCheckpoint {
+    SignedCheckpoint {
+        checkpoint_type: CheckpointType::LogicCall,
+        nonce: call.invalidation_nonce,
+        scope: bytes_to_hex_str(&call.invalidation_id),
+        checkpoint_hash: bytes_to_hex_str(&encode_logic_call_confirm_hashed(
+            gravity_id.to_string(),
+            call.clone(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_gravity::message_signatures::{
+        encode_logic_call_confirm, encode_tx_batch_confirm, encode_valset_confirm,
+    };
+    use gravity_utils::types::{Erc20Token, ValsetMember};
+
+    #[test]
+    fn test_checkpoint_from_message() {
+        let token =
+            clarity::Address::parse_and_validate("0x70EDF1c215D0ce69E7F16FD4E6276ba0d99d4de7")
+                .unwrap();
+        let valset = Valset {
+            nonce: 7,
+            members: vec![ValsetMember {
+                power: 4_000_000_000,
+                eth_address: token,
+            }],
+            reward_amount: 0u8.into(),
+            reward_token: None,
+        };
+        let message = encode_valset_confirm("gravity-test".to_string(), valset.clone());
+        assert_eq!(
+            SignedCheckpoint::from_checkpoint(&message).unwrap(),
+            valset_checkpoint("gravity-test", &valset)
+        );
+
+        let batch = TransactionBatch {
+            nonce: 9,
+            batch_timeout: 1000,
+            transactions: Vec::new(),
+            total_fee: Erc20Token {
+                amount: 0u8.into(),
+                token_contract_address: token,
+            },
+            token_contract: token,
+            cosmos_block_created: 0,
+        };
+        let message = encode_tx_batch_confirm("gravity-test".to_string(), batch.clone());
+        assert_eq!(
+            SignedCheckpoint::from_checkpoint(&message).unwrap(),
+            batch_checkpoint("gravity-test", &batch)
+        );
+
+        // the invalidation id is read zero padded, so only a full length id has the same scope
+        let call = LogicCall {
+            transfers: Vec::new(),
+            fees: Vec::new(),
+            logic_contract_address: token,
+            payload: vec![1, 2, 3],
+            timeout: 1000,
+            invalidation_id: vec![5u8; 32],
+            invalidation_nonce: 3,
+        };
+        let message = encode_logic_call_confirm("gravity-test".to_string(), call.clone());
+        assert_eq!(
+            SignedCheckpoint::from_checkpoint(&message).unwrap(),
+            logic_call_checkpoint("gravity-test", &call)
+        );
+    }
+}
@@ -1,11 +1,18 @@
 /// Contains logic specific to altruistic relaying, including gas tracking
 use clarity::Uint256;
+use cosmos_gravity::utils::get_block_time;
+use gravity_proto::cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::service_client::ServiceClient as TendermintServiceClient;
+use gravity_utils::error::GravityError;
 use gravity_utils::num_conversion::print_gwei;
-use gravity_utils::types::RelayerConfig;
+use gravity_utils::types::{AltruisticUrgency, RelayerConfig, SharedRelayerConfig};
+use metrics_exporter::metrics_relayer_iteration;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
-use std::time::Instant;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tonic::transport::Channel;
 use web30::client::Web3;
-use web30::gas_estimator::GasTracker;
 
 use crate::main_loop::delay_until_next_iteration;
 
@@ -13,9 +20,294 @@ use crate::main_loop::delay_until_next_iteration;
 // the donor, while providing maximum utility to the blockchain. Other modes are profitable only
 // and just to always relay everything which is mostly used for tests.
 
+/// The gas history is written to disk once every this many samples
+pub const GAS_HISTORY_SAVE_INTERVAL: u64 = 10;
+/// The most blocks requested from eth_feeHistory when backfilling, many nodes limit requests to 1024
+pub const FEE_HISTORY_MAX_BLOCKS: u64 = 1024;
+/// The approximate Ethereum block time in seconds, used to date backfilled samples
+pub const ETH_BLOCK_TIME: u64 = 12;
+
 lazy_static! {
     // Define a gas tracker with a small sample size, the size can be increased later as needed
-    static ref GAS_TRACKER: Arc<RwLock<GasTracker>> = Arc::new(RwLock::new(GasTracker::new(1)));
+    static ref GAS_TRACKER: Arc<RwLock<GasHistory>> = Arc::new(RwLock::new(GasHistory::new(1)));
+    static ref GAS_HISTORY_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+/// A single gas price observation, `time` is in unix seconds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasSample {
+    pub time: u64,
+    pub price: Uint256,
+}
+
+/// The most recent gas price samples, oldest first, up to `size` samples are kept
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GasHistory {
+    samples: VecDeque<GasSample>,
+    size: usize,
+}
+
+impl GasHistory {
+    pub fn new(size: usize) -> GasHistory {
+        GasHistory {
+            samples: VecDeque::new(),
+            size,
+        }
+    }
+
+    pub fn update(&mut self, sample: GasSample) {
+        self.samples.push_back(sample);
+        while self.samples.len() > self.size {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Adds samples older than any already held, used to backfill a history that was just started
+    pub fn prepend(&mut self, samples: &[GasSample]) {
+        let oldest = self.samples.front().map(|s| s.time).unwrap_or(u64::MAX);
+        for sample in samples.iter().rev().filter(|s| s.time < oldest) {
+            if self.samples.len() >= self.size {
+                break;
+            }
+            self.samples.push_front(*sample);
+        }
+    }
+
+    /// Increases the number of samples kept, the history never shrinks so that a config reload
+    /// does not discard samples
+    pub fn expand_history_size(&mut self, size: usize) {
+        if size > self.size {
+            self.size = size;
+        }
+    }
+
+    pub fn get_current_size(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn get_samples(&self) -> Vec<GasSample> {
+        self.samples.iter().cloned().collect()
+    }
+
+    /// The seconds between the oldest and newest samples, zero if the clock went backwards while
+    /// the samples were taken
+    pub fn get_time_span(&self) -> u64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(oldest), Some(newest)) => newest.time.saturating_sub(oldest.time),
+            _ => 0,
+        }
+    }
+
+    pub fn latest_gas_price(&self) -> Option<Uint256> {
+        self.samples.back().map(|s| s.price)
+    }
+
+    /// The gas price below which the lowest `percentage` (between 0 and 1) of the samples fall
+    pub fn get_acceptable_gas_price(&self, percentage: f32) -> Option<Uint256> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut prices: Vec<Uint256> = self.samples.iter().map(|s| s.price).collect();
+        prices.sort();
+        let index = (prices.len() as f32 * percentage) as usize;
+        Some(prices[index.min(prices.len() - 1)])
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// The number of seconds of gas price history the altruistic relayer considers, older samples are stale
+pub fn get_gas_history_window(config: &RelayerConfig) -> u64 {
+    config.altruistic_gas_price_samples * config.gas_tracker_loop_speed
+}
+
+/// Reads the gas history saved at `path`, discarding samples older than the configured window.
+/// A missing file is an empty history
+pub fn read_gas_history(path: &Path, config: &RelayerConfig) -> Result<GasHistory, GravityError> {
+    let mut history = GasHistory::new(config.altruistic_gas_price_samples as usize);
+    if !path.exists() {
+        return Ok(history);
+    }
+    let contents = fs::read_to_string(path)?;
+    let samples: Vec<GasSample> = serde_json::from_str(&contents).map_err(std::io::Error::from)?;
+    let cutoff = now().saturating_sub(get_gas_history_window(config));
+    for sample in samples.into_iter().filter(|s| s.time >= cutoff) {
+        history.update(sample);
+    }
+    Ok(history)
+}
+
+/// Loads the gas history left by a previous run and persists the gas history at `path` from now
+/// on, so that a restarted relayer does not have to collect samples again before relaying
+pub fn load_gas_history(path: PathBuf, config: &RelayerConfig) -> Result<(), GravityError> {
+    let history = read_gas_history(&path, config)?;
+    info!(
+        "Loaded {} gas price samples from {}",
+        history.get_current_size(),
+        path.display()
+    );
+    *GAS_TRACKER.write().unwrap() = history;
+    *GAS_HISTORY_PATH.write().unwrap() = Some(path);
+    Ok(())
+}
+
+/// Writes the gas history to a temporary file and renames it so that a crash mid write can not
+/// leave a corrupted file behind
+fn save_gas_history() {
+    let path = match GAS_HISTORY_PATH.read().unwrap().clone() {
+        Some(p) => p,
+        None => return,
+    };
+    let samples = GAS_TRACKER.read().unwrap().get_samples();
+    let tmp_path = path.with_extension("tmp");
+    let res = serde_json::to_string(&samples)
+        .map_err(std::io::Error::from)
+        .and_then(|v| fs::write(&tmp_path, v))
+        .and_then(|_| fs::rename(&tmp_path, &path));
+    if let Err(e) = res {
+        error!("Failed to save the gas price history {e:?}");
+    }
+}
+
+/// Converts eth_feeHistory results into gas samples `interval` seconds apart, ending at `now`.
+/// `base_fees` has an entry for each block plus the next block, `rewards` the priority fee paid in
+/// each block
+pub fn fee_history_to_samples(
+    base_fees: &[Uint256],
+    rewards: &[Uint256],
+    now: u64,
+    interval: u64,
+) -> Vec<GasSample> {
+    let blocks = base_fees.len().saturating_sub(1);
+    let step = (interval / ETH_BLOCK_TIME).max(1) as usize;
+    let mut samples = Vec::new();
+    for i in 0..blocks {
+        let blocks_ago = blocks - 1 - i;
+        if blocks_ago % step != 0 {
+            continue;
+        }
+        let reward = rewards.get(i).cloned().unwrap_or_default();
+        samples.push(GasSample {
+            time: now.saturating_sub(blocks_ago as u64 * ETH_BLOCK_TIME),
+            price: base_fees[i] + reward,
+        });
+    }
+    samples
+}
+
+/// Fills the gas history with samples derived from recent blocks, so that an altruistic relayer
+/// without saved history does not have to wait for samples to be collected
+async fn backfill_gas_history(web3: &Web3, config: &RelayerConfig) -> Result<usize, GravityError> {
+    let blocks = (get_gas_history_window(config) / ETH_BLOCK_TIME).clamp(1, FEE_HISTORY_MAX_BLOCKS);
+    let latest = web3.eth_block_number().await?;
+    let history = web3
+        .eth_fee_history(blocks.into(), latest, vec![50.0])
+        .await?;
+    let rewards: Vec<Uint256> = history
+        .reward
+        .unwrap_or_default()
+        .iter()
+        .map(|r| r.first().cloned().unwrap_or_default())
+        .collect();
+    let samples = fee_history_to_samples(
+        &history.base_fee_per_gas,
+        &rewards,
+        now(),
+        config.gas_tracker_loop_speed,
+    );
+    let mut tracker = GAS_TRACKER.write().unwrap();
+    let before = tracker.get_current_size();
+    tracker.prepend(&samples);
+    Ok(tracker.get_current_size() - before)
+}
+
+/// Returns how long in seconds an item created in the Cosmos block `created_height` has been
+/// waiting to be relayed, from the time of that block. None if the block time can not be found
+pub async fn get_waiting_time(
+    tendermint_client: &TendermintServiceClient<Channel>,
+    created_height: u64,
+) -> Option<u64> {
+    let mut tendermint_client = tendermint_client.clone();
+    match get_block_time(&mut tendermint_client, created_height).await {
+        Ok(time) => Some(now().saturating_sub(time)),
+        Err(e) => {
+            warn!("Could not get the time of Cosmos block {created_height}, waiting time is not considered {e:?}");
+            None
+        }
+    }
+}
+
+/// How urgent relaying an item is, from 0 for a new item to 1 for a batch about to time out or an
+/// item that has waited `max_wait` seconds. Valsets do not time out and have no `blocks_remaining`
+pub fn get_urgency(urgency: &AltruisticUrgency, blocks_remaining: Option<u64>, waited: u64) -> f32 {
+    let timeout_urgency = blocks_remaining
+        .map(|b| 1.0 - b as f32 / urgency.timeout_blocks as f32)
+        .unwrap_or(0.0);
+    let wait_urgency = waited as f32 / urgency.max_wait as f32;
+    timeout_urgency.max(wait_urgency).clamp(0.0, 1.0)
+}
+
+/// The percentage of gas price samples altruistic relaying accepts for an item with the given
+/// urgency, never lower than altruistic_acceptable_gas_price_percentage
+pub fn get_altruistic_gas_percentage(config: &RelayerConfig, urgency: f32) -> f32 {
+    let base = config.altruistic_acceptable_gas_price_percentage;
+    match &config.altruistic_urgency {
+        Some(u) if u.max_percentage > base => {
+            base + (u.max_percentage - base) * urgency.powf(u.exponent)
+        }
+        _ => base,
+    }
+}
+
+/// Gets the urgency of relaying an item created in the Cosmos block `created_height`, zero if
+/// urgency is not configured
+pub async fn get_submission_urgency(
+    config: &RelayerConfig,
+    tendermint_client: &TendermintServiceClient<Channel>,
+    created_height: Option<u64>,
+    blocks_remaining: Option<u64>,
+) -> f32 {
+    match &config.altruistic_urgency {
+        Some(u) => {
+            let waited = match created_height {
+                Some(height) => get_waiting_time(tendermint_client, height)
+                    .await
+                    .unwrap_or(0),
+                None => 0,
+            };
+            get_urgency(u, blocks_remaining, waited)
+        }
+        None => 0.0,
+    }
+}
+
+/// Returns true if the current gas price is low enough to altruistically relay an item with the
+/// given urgency
+pub fn is_altruistic_gas_price(config: &RelayerConfig, urgency: f32) -> bool {
+    let percentage = get_altruistic_gas_percentage(config, urgency);
+    match (
+        get_current_gas_price(),
+        get_acceptable_gas_price(percentage),
+    ) {
+        (Some(current), Some(acceptable)) => {
+            if urgency > 0.0 {
+                info!(
+                    "Urgency {:.2} accepts gas prices up to the lowest {:.1}% of samples, {} gwei. Current gas price {} gwei",
+                    urgency,
+                    percentage * 100.0,
+                    print_gwei(acceptable),
+                    print_gwei(current)
+                );
+            }
+            current <= acceptable
+        }
+        _ => false,
+    }
 }
 
 /// writes a new gas price entry into the tracker with the current ethereum gas price
@@ -23,15 +315,18 @@ lazy_static! {
 /// Adjust relayer_config.gas_tracker_loop_speed and ALTRUISTIC_SAMPLES to control the tracker
 /// panics if the GAS_TRACKER lock is currently held by another thread or is poisoned
 async fn update_gas_tracker(web3: &Web3) -> Option<Uint256> {
-    let sample = GasTracker::sample(web3).await;
+    let sample = web3.eth_gas_price().await;
     match sample {
-        None => {
-            warn!("Failed to update gas price sample");
+        Err(e) => {
+            warn!("Failed to update gas price sample {e:?}");
             None
         }
-        Some(price) => {
-            GAS_TRACKER.write().unwrap().update(price.clone());
-            Some(price.sample)
+        Ok(price) => {
+            GAS_TRACKER
+                .write()
+                .unwrap()
+                .update(GasSample { time: now(), price });
+            Some(price)
         }
     }
 }
@@ -64,7 +359,7 @@ pub fn get_current_gas_price() -> Option<Uint256> {
     }
 }
 
-/// updates the gas tracker's number of samples stored, panics if the input is smaller than the current tracker's size
+/// updates the gas tracker's number of samples stored, the tracker never shrinks
 /// ideally this function will only be called once from a single thread
 /// panics if the GAS_TRACKER lock is currently held by another thread or is poisoned
 pub fn update_gas_history_samples(size: usize) {
@@ -72,13 +367,119 @@ pub fn update_gas_history_samples(size: usize) {
 }
 
 /// continually updates the gas tracker with a new gas price entry to enable altruistic batch requests and batch relaying
-pub async fn gas_tracker_loop(web3: &Web3, relayer_config: RelayerConfig) {
+pub async fn gas_tracker_loop(web3: &Web3, shared_config: SharedRelayerConfig) {
+    let mut history_samples = shared_config.get().altruistic_gas_price_samples;
+    let config = shared_config.get();
+    let current_samples = get_num_gas_tracker_samples().unwrap_or_default();
+    if config.gas_history_backfill
+        && current_samples < config.altruistic_batch_relaying_samples_delay as usize
+    {
+        match backfill_gas_history(web3, &config).await {
+            Ok(added) => info!("Backfilled {added} gas price samples from eth_feeHistory"),
+            Err(e) => warn!("Failed to backfill gas price history {e:?}"),
+        }
+    }
+
+    let mut iterations: u64 = 0;
     loop {
         let loop_start = Instant::now();
+        let relayer_config = shared_config.get();
+        // the tracker can grow on a config reload without losing history, but never shrinks
+        if relayer_config.altruistic_gas_price_samples > history_samples {
+            history_samples = relayer_config.altruistic_gas_price_samples;
+            update_gas_history_samples(history_samples as usize);
+        }
 
         let current = update_gas_tracker(web3).await;
         debug!("Updated gas price history {:?}", current.map(print_gwei),);
+        iterations += 1;
+        if iterations % GAS_HISTORY_SAVE_INTERVAL == 0 {
+            save_gas_history();
+        }
+        metrics_relayer_iteration("gas_tracker", loop_start.elapsed());
 
         delay_until_next_iteration(loop_start, relayer_config.gas_tracker_loop_speed).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(time: u64, price: u64) -> GasSample {
+        GasSample {
+            time,
+            price: price.into(),
+        }
+    }
+
+    #[test]
+    fn test_gas_history() {
+        let mut history = GasHistory::new(4);
+        assert_eq!(history.get_acceptable_gas_price(0.5), None);
+        for (time, price) in [(10, 40), (20, 10), (30, 30), (40, 20), (50, 50)] {
+            history.update(sample(time, price));
+        }
+        // the oldest sample was dropped
+        assert_eq!(history.get_current_size(), 4);
+        assert_eq!(history.latest_gas_price(), Some(50u8.into()));
+        assert_eq!(history.get_acceptable_gas_price(0.0), Some(10u8.into()));
+        assert_eq!(history.get_acceptable_gas_price(0.5), Some(30u8.into()));
+        assert_eq!(history.get_acceptable_gas_price(1.0), Some(50u8.into()));
+        assert_eq!(history.get_time_span(), 30);
+
+        // a sample taken after the clock was set back
+        history.update(sample(5, 60));
+        assert_eq!(history.get_time_span(), 0);
+
+        let mut history = GasHistory::new(3);
+        history.update(sample(100, 1));
+        history.prepend(&[sample(80, 2), sample(90, 3), sample(100, 4)]);
+        assert_eq!(
+            history.get_samples(),
+            vec![sample(80, 2), sample(90, 3), sample(100, 1)]
+        );
+    }
+
+    #[test]
+    fn test_urgency() {
+        let urgency = AltruisticUrgency {
+            timeout_blocks: 1000,
+            max_wait: 3600,
+            max_percentage: 0.85,
+            exponent: 2.0,
+        };
+        assert_eq!(get_urgency(&urgency, None, 0), 0.0);
+        assert_eq!(get_urgency(&urgency, Some(5000), 0), 0.0);
+        assert_eq!(get_urgency(&urgency, Some(250), 0), 0.75);
+        assert_eq!(get_urgency(&urgency, Some(900), 1800), 0.5);
+        assert_eq!(get_urgency(&urgency, Some(0), 0), 1.0);
+        assert_eq!(get_urgency(&urgency, None, 7200), 1.0);
+
+        let mut config = RelayerConfig {
+            altruistic_acceptable_gas_price_percentage: 0.05,
+            ..Default::default()
+        };
+        assert_eq!(get_altruistic_gas_percentage(&config, 1.0), 0.05);
+        config.altruistic_urgency = Some(urgency);
+        assert_eq!(get_altruistic_gas_percentage(&config, 0.0), 0.05);
+        assert!((get_altruistic_gas_percentage(&config, 0.5) - 0.25).abs() < 1e-6);
+        assert!((get_altruistic_gas_percentage(&config, 1.0) - 0.85).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fee_history_to_samples() {
+        let base_fees: Vec<Uint256> = vec![10u8.into(), 20u8.into(), 30u8.into(), 40u8.into()];
+        let rewards: Vec<Uint256> = vec![1u8.into(), 2u8.into(), 3u8.into()];
+        // one sample per block
+        assert_eq!(
+            fee_history_to_samples(&base_fees, &rewards, 1000, 12),
+            vec![sample(976, 11), sample(988, 22), sample(1000, 33)]
+        );
+        // every other block, counting back from the latest
+        assert_eq!(
+            fee_history_to_samples(&base_fees, &rewards, 1000, 24),
+            vec![sample(976, 11), sample(1000, 33)]
+        );
+    }
+}
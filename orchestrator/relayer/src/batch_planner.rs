@@ -0,0 +1,191 @@
+//! Chooses which of the currently submittable batches the relayer submits in an iteration, and in what
+//! order. Submitting a batch invalidates every older batch for the same token, but older batches may still
+//! be submitted first, so any set of batches is valid as long as each token's batches are submitted oldest
+//! first. The planner picks the set with the most expected profit that fits within the per iteration gas
+//! budget and the wallet balance, a batch left out of the plan that is older than a planned batch for the
+//! same token will be invalidated by it.
+
+use clarity::Address as EthAddress;
+use clarity::Uint256;
+use gravity_utils::num_conversion::print_eth;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// A batch the relayer is willing to submit, with its estimated costs and reward
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchPlanItem {
+    pub token_contract: EthAddress,
+    pub nonce: u64,
+    /// estimated gas used by the submission
+    pub gas: Uint256,
+    /// worst case cost of the submission in wei
+    pub max_cost: Uint256,
+    /// the value of the batch reward in wei, if it was determined
+    pub reward: Option<Uint256>,
+}
+
+impl BatchPlanItem {
+    /// the expected profit in wei, zero if the reward is unknown or below the cost
+    pub fn get_profit(&self) -> Uint256 {
+        match self.reward {
+            Some(reward) if reward > self.max_cost => reward - self.max_cost,
+            _ => 0u8.into(),
+        }
+    }
+}
+
+/// Returns the indexes of the batches to submit, in submission order. The total gas of the plan is at
+/// most `gas_budget` if one is given, and its total worst case cost is at most `balance`
+pub fn plan_batches(
+    candidates: &[BatchPlanItem],
+    gas_budget: Option<Uint256>,
+    balance: Uint256,
+) -> Vec<usize> {
+    let fits = |gas: Uint256, cost: Uint256| {
+        gas_budget.map(|budget| gas <= budget).unwrap_or(true) && cost <= balance
+    };
+
+    // greedy by profit per unit of gas, compared by cross multiplication to avoid rounding
+    let mut by_density: Vec<usize> = (0..candidates.len()).collect();
+    by_density.sort_by(|a, b| {
+        let (a, b) = (&candidates[*a], &candidates[*b]);
+        (b.get_profit() * a.gas)
+            .cmp(&(a.get_profit() * b.gas))
+            .then(a.nonce.cmp(&b.nonce))
+    });
+    let mut selected = Vec::new();
+    let mut gas: Uint256 = 0u8.into();
+    let mut cost: Uint256 = 0u8.into();
+    let mut profit: Uint256 = 0u8.into();
+    for i in by_density {
+        let c = &candidates[i];
+        if fits(gas + c.gas, cost + c.max_cost) {
+            gas += c.gas;
+            cost += c.max_cost;
+            profit += c.get_profit();
+            selected.push(i);
+        }
+    }
+
+    // greedy selection can be beaten by the single most profitable batch when that batch uses
+    // most of the budget, taking the better of the two bounds the loss to half the optimum
+    let best_single = (0..candidates.len())
+        .filter(|i| fits(candidates[*i].gas, candidates[*i].max_cost))
+        .max_by(|a, b| {
+            candidates[*a]
+                .get_profit()
+                .cmp(&candidates[*b].get_profit())
+        });
+    if let Some(best) = best_single {
+        if candidates[best].get_profit() > profit {
+            selected = vec![best];
+        }
+    }
+
+    // the most profitable tokens go first, each token's batches are submitted oldest first so that
+    // no planned batch invalidates another
+    let mut token_profit: HashMap<EthAddress, Uint256> = HashMap::new();
+    for i in selected.iter() {
+        let c = &candidates[*i];
+        *token_profit.entry(c.token_contract).or_default() += c.get_profit();
+    }
+    selected.sort_by(|a, b| {
+        let (a, b) = (&candidates[*a], &candidates[*b]);
+        match token_profit[&b.token_contract].cmp(&token_profit[&a.token_contract]) {
+            Ordering::Equal => a
+                .token_contract
+                .cmp(&b.token_contract)
+                .then(a.nonce.cmp(&b.nonce)),
+            o => o,
+        }
+    });
+    selected
+}
+
+/// Logs the chosen plan, and why each candidate left out of it was not planned
+pub fn log_batch_plan(candidates: &[BatchPlanItem], plan: &[usize]) {
+    if candidates.is_empty() {
+        return;
+    }
+    info!(
+        "Planned {} of {} submittable batches",
+        plan.len(),
+        candidates.len()
+    );
+    for (position, i) in plan.iter().enumerate() {
+        let c = &candidates[*i];
+        info!(
+            "Batch plan {}: {}/{} Gas: {} Max Cost: {} Expected Profit: {}",
+            position + 1,
+            c.token_contract,
+            c.nonce,
+            c.gas,
+            print_eth(c.max_cost),
+            print_eth(c.get_profit())
+        );
+    }
+    for (i, c) in candidates.iter().enumerate() {
+        if plan.contains(&i) {
+            continue;
+        }
+        let newer = plan
+            .iter()
+            .map(|p| &candidates[*p])
+            .find(|p| p.token_contract == c.token_contract && p.nonce > c.nonce);
+        match newer {
+            Some(newer) => info!(
+                "Batch {}/{} is not planned and will be invalidated by the more profitable batch {}",
+                c.token_contract, c.nonce, newer.nonce
+            ),
+            None => info!(
+                "Batch {}/{} is not planned, it does not fit within the gas budget or our balance",
+                c.token_contract, c.nonce
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_batches() {
+        let a =
+            EthAddress::parse_and_validate("0x70EDF1c215D0ce69E7F16FD4E6276ba0d99d4de7").unwrap();
+        let b =
+            EthAddress::parse_and_validate("0x00000000219ab540356cBB839Cbe05303d7705Fa").unwrap();
+        let item = |token_contract, nonce, gas: u64, max_cost: u64, reward: u64| BatchPlanItem {
+            token_contract,
+            nonce,
+            gas: gas.into(),
+            max_cost: max_cost.into(),
+            reward: Some(reward.into()),
+        };
+        let candidates = vec![
+            item(a, 2, 100, 10, 30),
+            item(a, 1, 100, 10, 15),
+            item(b, 5, 300, 30, 200),
+        ];
+
+        // everything fits, b is most profitable and a is submitted oldest first
+        assert_eq!(
+            plan_batches(&candidates, None, 1000u64.into()),
+            vec![2, 1, 0]
+        );
+        // the budget only allows two batches, the older a batch is left to be invalidated
+        assert_eq!(
+            plan_batches(&candidates, Some(400u64.into()), 1000u64.into()),
+            vec![2, 0]
+        );
+        // the balance only covers the a batches
+        assert_eq!(plan_batches(&candidates, None, 25u64.into()), vec![1, 0]);
+        // the single large batch beats the greedy choice of many small ones
+        let candidates = vec![item(a, 1, 10, 1, 10), item(b, 1, 100, 10, 80)];
+        assert_eq!(
+            plan_batches(&candidates, Some(100u64.into()), 1000u64.into()),
+            vec![1]
+        );
+        assert!(plan_batches(&candidates, None, 0u8.into()).is_empty());
+    }
+}
@@ -1,19 +1,30 @@
-use crate::main_loop::ETH_SUBMIT_WAIT_TIME;
+use crate::altruistic::{get_submission_urgency, is_altruistic_gas_price};
+use crate::batch_planner::{log_batch_plan, plan_batches, BatchPlanItem};
+use crate::fee_strategy::get_relaying_fees;
+use crate::funds_guard::check_funds;
+use crate::ledger::RelayReward;
+use crate::tx_manager::{has_pending, submit_transaction, Submission};
 use clarity::address::Address as EthAddress;
 use clarity::PrivateKey as EthPrivateKey;
 use clarity::Uint256;
 use cosmos_gravity::query::get_latest_transaction_batches;
 use cosmos_gravity::query::get_transaction_batch_signatures;
 use ethereum_gravity::message_signatures::encode_tx_batch_confirm_hashed;
-use ethereum_gravity::submit_batch::send_eth_transaction_batch;
+use ethereum_gravity::submit_batch::encode_batch_payload;
 use ethereum_gravity::utils::get_tx_batch_nonce;
+use gravity_proto::cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::service_client::ServiceClient as TendermintServiceClient;
 use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::num_conversion::print_eth;
 use gravity_utils::num_conversion::print_gwei;
-use gravity_utils::prices::get_weth_price_with_retries;
+use gravity_utils::num_conversion::wei_to_gwei;
+use gravity_utils::prices::get_relayer_weth_price;
 use gravity_utils::types::BatchRelayingMode;
 use gravity_utils::types::WhitelistToken;
 use gravity_utils::types::{BatchConfirmResponse, RelayerConfig, TransactionBatch, Valset};
+use metrics_exporter::{
+    metrics_relayer_batches, metrics_relayer_candidate, metrics_relayer_reset_candidates,
+};
+use num_traits::ToPrimitive;
 use std::collections::HashMap;
 use tonic::transport::Channel;
 use web30::client::Web3;
@@ -32,10 +43,12 @@ struct SubmittableBatch {
 /// valid to submit given the current chain state. From there we simulate a submission
 /// and if that succeeds and we like the gas cost we complete the relaying process and
 /// actually submit the data to Ethereum
+#[allow(clippy::too_many_arguments)]
 pub async fn relay_batches(
     // the validator set currently in the contract on Ethereum
     current_valset: Valset,
     ethereum_key: EthPrivateKey,
+    tendermint_client: &TendermintServiceClient<Channel>,
     web3: &Web3,
     grpc_client: &mut GravityQueryClient<Channel>,
     gravity_contract_address: EthAddress,
@@ -50,6 +63,7 @@ pub async fn relay_batches(
     submit_batches(
         current_valset,
         ethereum_key,
+        tendermint_client,
         web3,
         gravity_contract_address,
         gravity_id,
@@ -79,8 +93,11 @@ async fn get_batches_and_signatures(
     };
     trace!("Latest batches {latest_batches:?}");
 
+    let mut pending_per_token: HashMap<EthAddress, u64> = HashMap::new();
+    let mut undersigned = 0;
     let mut possible_batches = HashMap::new();
     for batch in latest_batches {
+        *pending_per_token.entry(batch.token_contract).or_default() += 1;
         let sigs =
             get_transaction_batch_signatures(grpc_client, batch.nonce, batch.token_contract).await;
         trace!("Got sigs {sigs:?}");
@@ -96,6 +113,7 @@ async fn get_batches_and_signatures(
                 let list = possible_batches.get_mut(&batch.token_contract).unwrap();
                 list.push(SubmittableBatch { batch, sigs });
             } else {
+                undersigned += 1;
                 warn!(
                     "Batch {}/{} can not be submitted yet, waiting for more signatures",
                     batch.token_contract, batch.nonce
@@ -108,6 +126,16 @@ async fn get_batches_and_signatures(
             );
         }
     }
+    let submittable = possible_batches
+        .values()
+        .map(|v: &Vec<_>| v.len() as u64)
+        .sum();
+    let pending_per_token: Vec<(String, u64)> = pending_per_token
+        .into_iter()
+        .map(|(token, count)| (token.to_string(), count))
+        .collect();
+    metrics_relayer_batches(&pending_per_token, submittable, undersigned);
+
     // reverse the list so that it is oldest first, we want to submit
     // older batches so that we don't invalidate newer batches
     for (_key, value) in possible_batches.iter_mut() {
@@ -116,15 +144,18 @@ async fn get_batches_and_signatures(
     possible_batches
 }
 
-// Determines whether or not submitting `batch` will be profitable given the estimated `cost`
-// and the current exchange rate available on uniswap
+// Determines whether or not submitting `batch` will be profitable given the worst case `cost`
+// and the current exchange rate available on uniswap, or for altruistic relaying whether the
+// gas price is acceptable given the batch's `urgency`
 async fn should_relay_batch(
     web3: &Web3,
     batch: &TransactionBatch,
     cost: Uint256,
+    urgency: f32,
     pubkey: EthAddress,
-    config: &BatchRelayingMode,
+    relayer_config: &RelayerConfig,
 ) -> (bool, Option<Uint256>) {
+    let config = &relayer_config.batch_relaying_mode;
     // skip price request below in the trivial case, couldn't really
     // figure the code duplication / extra network IO balance otherwise
     if let BatchRelayingMode::EveryBatch = config {
@@ -134,11 +165,18 @@ async fn should_relay_batch(
     let batch_reward_amount = batch.total_fee.amount;
     let batch_reward_token = batch.total_fee.token_contract_address;
     // gets the price of the provided amount of the provided token in weth
-    let price =
-        get_weth_price_with_retries(pubkey, batch_reward_token, batch_reward_amount, web3).await;
+    let price = get_relayer_weth_price(
+        relayer_config,
+        pubkey,
+        batch_reward_token,
+        batch_reward_amount,
+        web3,
+    )
+    .await;
 
     match config {
-        BatchRelayingMode::EveryBatch | BatchRelayingMode::Altruistic => (true, None),
+        BatchRelayingMode::EveryBatch => (true, None),
+        BatchRelayingMode::Altruistic => (is_altruistic_gas_price(relayer_config, urgency), None),
         BatchRelayingMode::ProfitableOnly { margin } => {
             let cost_with_margin = get_cost_with_margin(cost, *margin);
 
@@ -208,15 +246,19 @@ pub fn get_cost_with_margin(cost: Uint256, margin: f32) -> Uint256 {
 /// been submitted. The goal of this function is to submit batches in chronological order
 /// of their creation, submitting batches newest first will invalidate old batches and is
 /// less efficient if those old batches are profitable.
-/// This function estimates the cost of submitting a batch before actually submitting it
+/// This function estimates the cost of submitting every batch before actually submitting any
 /// to Ethereum, if it is determined that the ETH cost to submit is too high the batch will
-/// be skipped and a later, more profitable, batch may be submitted.
+/// be skipped and a later, more profitable, batch may be submitted. The batches worth submitting
+/// are then planned by plan_batches, which picks the most profitable set within the gas budget
+/// and our ETH balance.
 /// Keep in mind that many other relayers are making this same computation and some may have
 /// different standards for their profit margin, therefore there may be a race not only to
 /// submit individual batches but also batches in different orders
+#[allow(clippy::too_many_arguments)]
 async fn submit_batches(
     current_valset: Valset,
     ethereum_key: EthPrivateKey,
+    tendermint_client: &TendermintServiceClient<Channel>,
     web3: &Web3,
     gravity_contract_address: EthAddress,
     gravity_id: String,
@@ -230,11 +272,31 @@ async fn submit_batches(
         warn!("Failed to get eth block height, is your eth node working?");
         return;
     };
+    let fees = match get_relaying_fees(web3, &config).await {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("Not relaying batches, could not determine transaction fees {e}");
+            return;
+        }
+    };
 
-    // requests data from Ethereum only once per token type, this is valid because we are
-    // iterating from oldest to newest, so submitting a batch earlier in the loop won't
-    // ever invalidate submitting a batch later in the loop. Another relayer could always
-    // do that though.
+    let balance = match web3.eth_get_balance(our_ethereum_address).await {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("Not relaying batches, could not get our ETH balance {e:?}");
+            return;
+        }
+    };
+
+    // every submittable batch is estimated and priced before anything is submitted so that
+    // the planner can choose between them
+    let mut candidates: Vec<SubmittableBatch> = Vec::new();
+    let mut plan_items: Vec<BatchPlanItem> = Vec::new();
+    metrics_relayer_reset_candidates();
+
+    // requests data from Ethereum only once per token type, this is valid because the planner
+    // submits each token's batches oldest first, so submitting one of our batches won't
+    // ever invalidate submitting another. Another relayer could always do that though.
     for (token_type, possible_batches) in possible_batches {
         let erc20_contract = token_type;
         let latest_ethereum_batch = get_tx_batch_nonce(
@@ -251,8 +313,8 @@ async fn submit_batches(
         let latest_ethereum_batch = latest_ethereum_batch.unwrap();
 
         for batch in possible_batches {
-            let oldest_signed_batch = batch.batch;
-            let oldest_signatures = batch.sigs;
+            let oldest_signed_batch = &batch.batch;
+            let oldest_signatures = &batch.sigs;
 
             let timeout_height: Uint256 = oldest_signed_batch.batch_timeout.into();
             if timeout_height < ethereum_block_height {
@@ -263,16 +325,28 @@ async fn submit_batches(
                 continue;
             }
 
-            let latest_cosmos_batch_nonce = oldest_signed_batch.clone().nonce;
+            let latest_cosmos_batch_nonce = oldest_signed_batch.nonce;
             if latest_cosmos_batch_nonce > latest_ethereum_batch {
+                let submission = Submission::Batch {
+                    token_contract: oldest_signed_batch.token_contract,
+                    nonce: oldest_signed_batch.nonce,
+                };
+                if has_pending(&submission) {
+                    info!(
+                        "Batch {}/{} already has a pending transaction, waiting for it",
+                        oldest_signed_batch.token_contract, oldest_signed_batch.nonce
+                    );
+                    continue;
+                }
                 let cost = ethereum_gravity::submit_batch::estimate_tx_batch_cost(
                     current_valset.clone(),
                     oldest_signed_batch.clone(),
-                    &oldest_signatures,
+                    oldest_signatures,
                     web3,
                     gravity_contract_address,
                     gravity_id.clone(),
                     ethereum_key.to_address(),
+                    fees,
                 )
                 .await;
                 if cost.is_err() {
@@ -282,53 +356,117 @@ async fn submit_batches(
                 let cost = cost.unwrap();
 
                 info!(
-                    "We have detected a batch to relay. This batch is estimated to cost {} Gas @ {} gwei / {:.4} ETH to submit",
+                    "We have detected a batch to relay. This batch is estimated to cost {} Gas @ {} gwei / {:.4} ETH to submit, at most {} gwei / {:.4} ETH",
                     cost.gas.clone(),
                     print_gwei(cost.gas_price),
-                    print_eth(cost.get_total())
+                    print_eth(cost.get_total()),
+                    print_gwei(cost.max_fee_per_gas),
+                    print_eth(cost.get_worst_case_total())
                 );
                 oldest_signed_batch
                     .display_with_eth_info(our_ethereum_address, web3)
                     .await;
 
+                let blocks_remaining = (timeout_height - ethereum_block_height).to_u64();
+                // only altruistic relaying weighs urgency, don't look up the batch's age otherwise
+                let urgency = if config.batch_relaying_mode == BatchRelayingMode::Altruistic {
+                    get_submission_urgency(
+                        &config,
+                        tendermint_client,
+                        Some(oldest_signed_batch.cosmos_block_created),
+                        blocks_remaining,
+                    )
+                    .await
+                } else {
+                    0.0
+                };
                 let (should_relay, reward_in_weth) = should_relay_batch(
                     web3,
-                    &oldest_signed_batch,
-                    cost.get_total(),
+                    oldest_signed_batch,
+                    cost.get_worst_case_total(),
+                    urgency,
                     our_ethereum_address,
-                    &config.batch_relaying_mode,
+                    &config,
                 )
                 .await;
+                metrics_relayer_candidate(
+                    &oldest_signed_batch.token_contract.to_string(),
+                    oldest_signed_batch.nonce,
+                    wei_to_gwei(cost.get_worst_case_total()),
+                    reward_in_weth.map(wei_to_gwei),
+                );
 
                 if should_relay {
-                    info!(
-                        "Attempting to relay batch {}/{} Expected Cost: {:} Reward: {:?}",
-                        oldest_signed_batch.token_contract,
-                        oldest_signed_batch.nonce,
-                        print_eth(cost.get_total()),
-                        reward_in_weth.map(print_eth),
-                    );
-                    let res = send_eth_transaction_batch(
-                        current_valset.clone(),
-                        oldest_signed_batch,
-                        &oldest_signatures,
-                        web3,
-                        ETH_SUBMIT_WAIT_TIME,
-                        gravity_contract_address,
-                        gravity_id.clone(),
-                        ethereum_key,
-                    )
-                    .await;
-                    if res.is_err() {
-                        info!("Batch submission failed with {res:?}");
-                    }
+                    plan_items.push(BatchPlanItem {
+                        token_contract: oldest_signed_batch.token_contract,
+                        nonce: oldest_signed_batch.nonce,
+                        gas: cost.gas,
+                        max_cost: cost.get_worst_case_total(),
+                        reward: reward_in_weth,
+                    });
+                    candidates.push(batch);
                 } else {
                     info!(
-                        "Not relaying batch {}/{} due to it not being profitable. Cost: {}, Reward: {:?}",
-                        oldest_signed_batch.token_contract, oldest_signed_batch.nonce, print_eth(cost.get_total()), reward_in_weth.map(print_eth),
+                        "Not relaying batch {}/{} due to it not being profitable. Max Cost: {}, Reward: {:?}",
+                        oldest_signed_batch.token_contract, oldest_signed_batch.nonce, print_eth(cost.get_worst_case_total()), reward_in_weth.map(print_eth),
                     );
                 }
             }
         }
     }
+
+    let plan = plan_batches(
+        &plan_items,
+        config.batch_gas_budget.map(|v| v.into()),
+        balance,
+    );
+    log_batch_plan(&plan_items, &plan);
+
+    for i in plan {
+        let batch = &candidates[i];
+        let item = &plan_items[i];
+        info!(
+            "Attempting to relay batch {}/{} Max Cost: {} Reward: {:?}",
+            item.token_contract,
+            item.nonce,
+            print_eth(item.max_cost),
+            item.reward.map(print_eth),
+        );
+        if !check_funds(web3, our_ethereum_address, item.max_cost, None, &config).await {
+            return;
+        }
+        let submission = Submission::Batch {
+            token_contract: item.token_contract,
+            nonce: item.nonce,
+        };
+        let reward = RelayReward {
+            tokens: vec![batch.batch.total_fee.clone()],
+            weth_value: item.reward,
+        };
+        let res = match encode_batch_payload(
+            current_valset.clone(),
+            &batch.batch,
+            &batch.sigs,
+            gravity_id.clone(),
+        ) {
+            Ok(payload) => {
+                submit_transaction(
+                    web3,
+                    ethereum_key,
+                    gravity_contract_address,
+                    payload,
+                    fees,
+                    item.max_cost,
+                    submission,
+                    reward,
+                    &config,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        if res.is_err() {
+            info!("Batch submission failed with {res:?}");
+        }
+    }
 }
@@ -0,0 +1,150 @@
+//! Determines the EIP-1559 fees relayed transactions are submitted with according to the
+//! configured FeeStrategy, and enforces the configured max fee per gas cap
+
+use crate::altruistic::get_current_gas_price;
+use crate::batch_relaying::get_cost_with_margin;
+use clarity::Uint256;
+use ethereum_gravity::utils::Eip1559Fees;
+use gravity_utils::error::GravityError;
+use gravity_utils::num_conversion::print_gwei;
+use gravity_utils::types::{FeeStrategy, RelayerConfig};
+use std::cmp::min;
+use web30::client::Web3;
+
+/// Gets the fees to submit relayed transactions with, returns an error if the fees the
+/// current gas price calls for exceed the configured cap, in which case nothing should be relayed
+pub async fn get_relaying_fees(
+    web3: &Web3,
+    config: &RelayerConfig,
+) -> Result<Eip1559Fees, GravityError> {
+    let (fees, current_gas_price) = match &config.fee_strategy {
+        FeeStrategy::NodeSuggested => {
+            let gas_price = web3.eth_gas_price().await?;
+            // increase the value by 20% to compensate for the 12.5% maximum base
+            // fee increase allowed per block in eip1559
+            let fees = Eip1559Fees {
+                max_fee_per_gas: gas_price + (gas_price / 5u8.into()),
+                max_priority_fee_per_gas: None,
+            };
+            (fees, Some(gas_price))
+        }
+        FeeStrategy::Fixed {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        } => {
+            let fees = Eip1559Fees {
+                max_fee_per_gas: *max_fee_per_gas,
+                max_priority_fee_per_gas: Some(*max_priority_fee_per_gas),
+            };
+            (fees, None)
+        }
+        FeeStrategy::FeeHistory { blocks, percentile } => {
+            let latest = web3.eth_block_number().await?;
+            let history = web3
+                .eth_fee_history((*blocks).into(), latest, vec![*percentile])
+                .await?;
+            // the last entry is the base fee of the next block
+            let base_fee = match history.base_fee_per_gas.last() {
+                Some(v) => *v,
+                None => {
+                    return Err(GravityError::InvalidOptionsError(
+                        "Fee history did not include a base fee, is this an EIP-1559 chain?"
+                            .to_string(),
+                    ))
+                }
+            };
+            let rewards: Vec<Uint256> = history
+                .reward
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|r| r.first().cloned())
+                .collect();
+            let priority_fee = average(&rewards);
+            let fees = Eip1559Fees {
+                max_fee_per_gas: base_fee + base_fee + priority_fee,
+                max_priority_fee_per_gas: Some(priority_fee),
+            };
+            (fees, Some(base_fee + priority_fee))
+        }
+        FeeStrategy::GasTracker { multiplier } => {
+            let gas_price = match get_current_gas_price() {
+                Some(v) => v,
+                None => web3.eth_gas_price().await?,
+            };
+            let fees = Eip1559Fees {
+                max_fee_per_gas: get_cost_with_margin(gas_price, *multiplier),
+                max_priority_fee_per_gas: None,
+            };
+            (fees, Some(gas_price))
+        }
+    };
+    apply_fee_cap(fees, current_gas_price, config.max_fee_per_gas_cap)
+}
+
+/// Limits the fees to the cap, if the current gas price is itself above the cap a transaction
+/// at the capped fee is unlikely to be included and an error is returned instead
+pub fn apply_fee_cap(
+    fees: Eip1559Fees,
+    current_gas_price: Option<Uint256>,
+    cap: Option<Uint256>,
+) -> Result<Eip1559Fees, GravityError> {
+    let cap = match cap {
+        Some(v) => v,
+        None => return Ok(fees),
+    };
+    if let Some(current) = current_gas_price {
+        if current > cap {
+            return Err(GravityError::InvalidOptionsError(format!(
+                "The current gas price of {} gwei exceeds the max_fee_per_gas_cap of {} gwei",
+                print_gwei(current),
+                print_gwei(cap)
+            )));
+        }
+    }
+    let max_fee_per_gas = min(fees.max_fee_per_gas, cap);
+    Ok(Eip1559Fees {
+        max_fee_per_gas,
+        max_priority_fee_per_gas: fees
+            .max_priority_fee_per_gas
+            .map(|v| min(v, max_fee_per_gas)),
+    })
+}
+
+fn average(values: &[Uint256]) -> Uint256 {
+    if values.is_empty() {
+        return 0u8.into();
+    }
+    let mut sum: Uint256 = 0u8.into();
+    for v in values {
+        sum = sum + *v;
+    }
+    sum / Uint256::from(values.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_fee_cap() {
+        let fees = Eip1559Fees {
+            max_fee_per_gas: 100u8.into(),
+            max_priority_fee_per_gas: Some(10u8.into()),
+        };
+        assert_eq!(apply_fee_cap(fees, Some(50u8.into()), None).unwrap(), fees);
+        assert_eq!(
+            apply_fee_cap(fees, Some(50u8.into()), Some(200u8.into())).unwrap(),
+            fees
+        );
+
+        let capped = apply_fee_cap(fees, Some(50u8.into()), Some(60u8.into())).unwrap();
+        assert_eq!(capped.max_fee_per_gas, 60u8.into());
+        assert_eq!(capped.max_priority_fee_per_gas, Some(10u8.into()));
+
+        let capped = apply_fee_cap(fees, None, Some(5u8.into())).unwrap();
+        assert_eq!(capped.max_fee_per_gas, 5u8.into());
+        assert_eq!(capped.max_priority_fee_per_gas, Some(5u8.into()));
+
+        assert!(apply_fee_cap(fees, Some(61u8.into()), Some(60u8.into())).is_err());
+    }
+}
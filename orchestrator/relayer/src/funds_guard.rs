@@ -0,0 +1,220 @@
+//! Guards the relayer's wallet against being drained. Before every submission, replacement, cancel, and
+//! resend the relayer checks that the worst case cost leaves at least `min_eth_balance` in its wallet and
+//! keeps its gas spend within `max_spend_per_hour` and `max_spend_per_day`. While any limit would be exceeded the
+//! relayer is paused for lack of funds, it resumes on its own once it is topped up or enough time has
+//! passed for the spend limits to allow submissions again.
+//!
+//! Spend is the actual cost of settled transactions as recorded in the ledger, so that a restarted
+//! relayer remembers what it spent in the last day, plus the worst case cost of every transaction that
+//! is still pending and may yet be included
+
+use crate::ledger::LedgerEntry;
+use crate::tx_manager::get_pending_cost;
+use clarity::Address as EthAddress;
+use clarity::Uint256;
+use gravity_utils::num_conversion::{print_eth, wei_to_gwei};
+use gravity_utils::types::RelayerConfig;
+use metrics_exporter::{metrics_relayer_eth_balance, metrics_relayer_funds};
+use std::collections::VecDeque;
+use std::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use web30::client::Web3;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+/// How often the balance metric is refreshed while the relayer is not submitting, every
+/// check_funds call refreshes it as well
+const BALANCE_METRIC_INTERVAL: Duration = Duration::from_secs(600);
+
+lazy_static! {
+    static ref FUNDS_GUARD: RwLock<FundsGuard> = RwLock::new(FundsGuard::default());
+}
+
+#[derive(Debug, Default)]
+struct FundsGuard {
+    /// the settlement time and cost in wei of every transaction settled in the last day
+    spends: VecDeque<(u64, Uint256)>,
+    paused: bool,
+    /// when the balance metric was last set
+    balance_updated: Option<Instant>,
+}
+
+impl FundsGuard {
+    fn get_spent_since(&self, since: u64) -> Uint256 {
+        let mut total: Uint256 = 0u8.into();
+        for (_, cost) in self.spends.iter().filter(|(t, _)| *t >= since) {
+            total += *cost;
+        }
+        total
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Records the cost of a settled transaction against the spend limits
+pub fn record_spend(settled_at: u64, cost: Uint256) {
+    let mut guard = FUNDS_GUARD.write().unwrap();
+    guard.spends.push_back((settled_at, cost));
+    let cutoff = now().saturating_sub(DAY);
+    while matches!(guard.spends.front(), Some((t, _)) if *t < cutoff) {
+        guard.spends.pop_front();
+    }
+}
+
+/// Records the spend of previously settled transactions, used to restore the spend limits after a restart
+pub fn load_spends(entries: &[LedgerEntry]) {
+    let cutoff = now().saturating_sub(DAY);
+    for entry in entries.iter().filter(|e| e.settled_at >= cutoff) {
+        record_spend(entry.settled_at, entry.get_cost());
+    }
+}
+
+/// Checks a submission with the worst case cost `cost` against the configured limits, returning
+/// the reason the submission must not be made if any limit would be exceeded
+pub fn check_limits(
+    balance: Uint256,
+    cost: Uint256,
+    spent_hour: Uint256,
+    spent_day: Uint256,
+    config: &RelayerConfig,
+) -> Result<(), String> {
+    if cost > balance {
+        return Err(format!(
+            "the submission may cost {} ETH but our balance is only {} ETH",
+            print_eth(cost),
+            print_eth(balance)
+        ));
+    }
+    if let Some(min) = config.min_eth_balance {
+        if balance - cost < min {
+            return Err(format!(
+                "our balance of {} ETH less the submission cost of up to {} ETH is below the minimum balance of {} ETH",
+                print_eth(balance),
+                print_eth(cost),
+                print_eth(min)
+            ));
+        }
+    }
+    for (window, spent, limit) in [
+        ("hour", spent_hour, config.max_spend_per_hour),
+        ("day", spent_day, config.max_spend_per_day),
+    ] {
+        if let Some(limit) = limit {
+            if spent + cost > limit {
+                return Err(format!(
+                    "we have spent or have pending {} ETH in the last {window}, the submission cost of up to {} ETH would exceed the limit of {} ETH",
+                    print_eth(spent),
+                    print_eth(cost),
+                    print_eth(limit)
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if the relayer may make a submission with a worst case cost of `cost` wei. When
+/// the submission replaces the pending transaction with nonce `replacing` the cost of that
+/// transaction is not counted, only one of the two can be included. While the relayer may not
+/// submit it is paused, the pause and the recovery are logged once each
+pub async fn check_funds(
+    web3: &Web3,
+    our_address: EthAddress,
+    cost: Uint256,
+    replacing: Option<Uint256>,
+    config: &RelayerConfig,
+) -> bool {
+    let balance = match web3.eth_get_balance(our_address).await {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("Not relaying, could not get our ETH balance {e:?}");
+            return false;
+        }
+    };
+    let pending = get_pending_cost(our_address, replacing);
+    let mut guard = FUNDS_GUARD.write().unwrap();
+    let now = now();
+    let spent_hour = guard.get_spent_since(now.saturating_sub(HOUR)) + pending;
+    let spent_day = guard.get_spent_since(now.saturating_sub(DAY)) + pending;
+    let res = check_limits(balance, cost, spent_hour, spent_day, config);
+    match &res {
+        Err(reason) if !guard.paused => {
+            error!("Relayer paused: insufficient funds, {reason}. Relaying resumes automatically once the wallet is topped up or the spend limits allow it");
+        }
+        Err(reason) => info!("Relayer still paused for insufficient funds, {reason}"),
+        Ok(()) if guard.paused => {
+            info!(
+                "Relayer resumed, our balance of {} ETH is sufficient",
+                print_eth(balance)
+            );
+        }
+        Ok(()) => {}
+    }
+    guard.paused = res.is_err();
+    guard.balance_updated = Some(Instant::now());
+    metrics_relayer_funds(
+        wei_to_gwei(balance),
+        wei_to_gwei(spent_hour),
+        wei_to_gwei(spent_day),
+        guard.paused,
+    );
+    !guard.paused
+}
+
+/// Refreshes the balance metric if check_funds has not done so recently
+pub async fn update_balance_metric(web3: &Web3, our_address: EthAddress) {
+    let updated = FUNDS_GUARD.read().unwrap().balance_updated;
+    if matches!(updated, Some(t) if t.elapsed() < BALANCE_METRIC_INTERVAL) {
+        return;
+    }
+    match web3.eth_get_balance(our_address).await {
+        Ok(balance) => {
+            metrics_relayer_eth_balance(wei_to_gwei(balance));
+            FUNDS_GUARD.write().unwrap().balance_updated = Some(Instant::now());
+        }
+        Err(e) => warn!("Could not get our ETH balance {e:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gravity_utils::num_conversion::one_eth;
+
+    #[test]
+    fn test_check_limits() {
+        let config = RelayerConfig {
+            min_eth_balance: Some(one_eth()),
+            max_spend_per_hour: Some(one_eth()),
+            max_spend_per_day: Some(one_eth() * Uint256::from(2u8)),
+            ..Default::default()
+        };
+        let zero: Uint256 = 0u8.into();
+        let balance = one_eth() * Uint256::from(3u8);
+        assert!(check_limits(balance, one_eth(), zero, zero, &config).is_ok());
+        // the submission would leave less than the minimum balance
+        assert!(
+            check_limits(balance, one_eth() * Uint256::from(3u8), zero, zero, &config).is_err()
+        );
+        assert!(check_limits(one_eth(), 1u8.into(), zero, zero, &config).is_err());
+        // the hourly and daily spend limits
+        assert!(check_limits(balance, 1u8.into(), one_eth(), one_eth(), &config).is_err());
+        assert!(check_limits(
+            balance,
+            1u8.into(),
+            zero,
+            one_eth() * Uint256::from(2u8),
+            &config
+        )
+        .is_err());
+        // without limits only the balance itself is checked
+        let config = RelayerConfig::default();
+        assert!(check_limits(balance, balance, one_eth(), one_eth(), &config).is_ok());
+        assert!(check_limits(zero, 1u8.into(), zero, zero, &config).is_err());
+    }
+}
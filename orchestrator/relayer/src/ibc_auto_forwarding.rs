@@ -1,12 +1,12 @@
 use cosmos_gravity::{
     query::get_all_pending_ibc_auto_forwards, send::execute_pending_ibc_auto_forwards,
 };
-use deep_space::{Coin, Contact, CosmosPrivateKey};
-use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
-use gravity_utils::types::RelayerConfig;
+use deep_space::{Coin, CosmosPrivateKey};
+use gravity_utils::connection_prep::CosmosGrpcPool;
+use gravity_utils::types::SharedRelayerConfig;
+use metrics_exporter::{metrics_relayer_ibc_auto_forward_queue, metrics_relayer_iteration};
 use std::time::{Duration, Instant};
 use tokio::time::sleep as delay_for;
-use tonic::transport::Channel;
 
 /// This function contains the ibc auto forward executor primary loop, which periodically queries for
 /// pending IBC Auto Forwards and submits a MsgExecuteIbcAutoForwards to the gravity chain to handle
@@ -16,13 +16,10 @@ use tonic::transport::Channel;
 #[allow(clippy::too_many_arguments)]
 pub async fn ibc_auto_forward_loop(
     cosmos_key: Option<CosmosPrivateKey>,
-    contact: &Contact,
-    grpc_client: GravityQueryClient<Channel>,
+    cosmos_pool: CosmosGrpcPool,
     fee: Option<Coin>,
-    relayer_config: RelayerConfig,
+    shared_config: SharedRelayerConfig,
 ) {
-    let mut grpc_client = grpc_client;
-
     if cosmos_key.is_none() {
         error!("Unable to execute pending ibc auto forwards with no configured private key!");
         return;
@@ -39,8 +36,11 @@ pub async fn ibc_auto_forward_loop(
 
     loop {
         let loop_start = Instant::now();
+        let relayer_config = shared_config.get();
+        let (contact, mut grpc_client) = cosmos_pool.get_healthy_connection().await;
         let pending_forwards = get_all_pending_ibc_auto_forwards(&mut grpc_client).await;
         let should_execute_pending_ibc_auto_forwards = !pending_forwards.is_empty();
+        metrics_relayer_ibc_auto_forward_queue(pending_forwards.len());
 
         if should_execute_pending_ibc_auto_forwards {
             info!(
@@ -50,7 +50,7 @@ pub async fn ibc_auto_forward_loop(
             );
 
             let res = execute_pending_ibc_auto_forwards(
-                contact,
+                &contact,
                 cosmos_key,
                 fee.clone(),
                 relayer_config.ibc_auto_forwards_to_execute,
@@ -67,6 +67,7 @@ pub async fn ibc_auto_forward_loop(
         // a bit of logic that tries to keep things running every ibc_auto_forward_loop_speed seconds
         // exactly, this is not required for any specific reason.
         let elapsed = Instant::now() - loop_start;
+        metrics_relayer_iteration("ibc_auto_forward", elapsed);
         let loop_speed = Duration::from_secs(relayer_config.ibc_auto_forward_loop_speed);
         if elapsed < loop_speed {
             delay_for(loop_speed - elapsed).await;
@@ -0,0 +1,322 @@
+//! A durable profit and loss ledger of every valset, batch, and logic call the relayer has submitted.
+//! Each settled transaction records what it cost in gas, the reward it was submitted for, and whether
+//! it succeeded, reverted, or was beaten by another relayer, so that operators can tell whether their
+//! fee and profitability settings actually make money.
+//!
+//! The ledger is an append only file of json lines, written once the transaction manager sees a
+//! transaction included
+
+use crate::funds_guard::load_spends;
+use crate::tx_manager::Submission;
+use clarity::Address as EthAddress;
+use clarity::Uint256;
+use gravity_utils::error::GravityError;
+use gravity_utils::prices::get_relayer_weth_price;
+use gravity_utils::types::{Erc20Token, RelayerConfig};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use web30::amm::WETH_CONTRACT_ADDRESS;
+use web30::client::Web3;
+
+lazy_static! {
+    static ref LEDGER_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+/// The reward a submission was made for, carried with the pending transaction until it settles
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelayReward {
+    pub tokens: Vec<Erc20Token>,
+    /// the value of the reward in wei at submission time, if it was determined
+    pub weth_value: Option<Uint256>,
+}
+
+impl RelayReward {
+    /// Prices the reward tokens with the configured price source, the value is left unknown if
+    /// any token can not be priced
+    pub async fn new(
+        tokens: Vec<Erc20Token>,
+        our_address: EthAddress,
+        web3: &Web3,
+        config: &RelayerConfig,
+    ) -> RelayReward {
+        let mut weth_value: Option<Uint256> = Some(0u8.into());
+        for token in tokens.iter() {
+            // WETH directly counts as ETH
+            if token.token_contract_address == *WETH_CONTRACT_ADDRESS {
+                weth_value = weth_value.map(|v| v + token.amount);
+                continue;
+            }
+            let price = get_relayer_weth_price(
+                config,
+                our_address,
+                token.token_contract_address,
+                token.amount,
+                web3,
+            )
+            .await;
+            weth_value = match (weth_value, price) {
+                (Some(total), Ok(price)) => Some(total + price),
+                _ => None,
+            };
+        }
+        RelayReward { tokens, weth_value }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// the submission was executed and the reward collected
+    Success,
+    /// the submission reverted for some reason other than another relayer submitting first
+    Reverted,
+    /// another relayer submitted first, our transaction either reverted or was cancelled
+    Beaten,
+    /// none of our transactions for the nonce were found, the nonce was used by something else
+    Unknown,
+}
+
+impl Outcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Success => "success",
+            Outcome::Reverted => "reverted",
+            Outcome::Beaten => "beaten",
+            Outcome::Unknown => "unknown",
+        }
+    }
+}
+
+/// A single settled relayer transaction
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LedgerEntry {
+    pub submission: Submission,
+    pub txid: Uint256,
+    pub gas_used: Option<Uint256>,
+    pub effective_gas_price: Option<Uint256>,
+    pub rewards: Vec<Erc20Token>,
+    pub reward_weth_value: Option<Uint256>,
+    pub outcome: Outcome,
+    /// Unix time in seconds the transaction was seen included
+    pub settled_at: u64,
+}
+
+impl LedgerEntry {
+    /// The gas cost of the transaction in wei, zero if it is unknown
+    pub fn get_cost(&self) -> Uint256 {
+        match (self.gas_used, self.effective_gas_price) {
+            (Some(gas), Some(price)) => gas * price,
+            _ => 0u8.into(),
+        }
+    }
+
+    /// The value collected in wei, only successful submissions collect their reward
+    pub fn get_revenue(&self) -> Uint256 {
+        match (self.outcome, self.reward_weth_value) {
+            (Outcome::Success, Some(v)) => v,
+            _ => 0u8.into(),
+        }
+    }
+
+    /// The token the submission was rewarded in, batches and valsets only have a single reward
+    /// token and logic calls are grouped by their first fee token
+    pub fn get_reward_token(&self) -> Option<EthAddress> {
+        self.rewards.first().map(|t| t.token_contract_address)
+    }
+}
+
+/// Sets the file settled transactions are recorded to, without a path the ledger is not kept. The
+/// spend already recorded in the ledger is counted against the relayer's spend limits
+pub fn set_ledger_path(path: PathBuf) {
+    match load_ledger(&path) {
+        Ok(entries) => load_spends(&entries),
+        Err(e) => warn!("Failed to load the relayer ledger, recent spend is not known {e}"),
+    }
+    *LEDGER_PATH.write().unwrap() = Some(path);
+}
+
+/// Appends a settled transaction to the ledger, failures are logged since the transaction has
+/// already been included and there is nothing to retry
+pub fn record_settled_transaction(entry: LedgerEntry) {
+    info!(
+        "{:?} settled as {:?} costing {} wei",
+        entry.submission,
+        entry.outcome,
+        entry.get_cost()
+    );
+    let path = match LEDGER_PATH.read().unwrap().clone() {
+        Some(p) => p,
+        None => return,
+    };
+    let res = serde_json::to_string(&entry)
+        .map_err(std::io::Error::from)
+        .and_then(|mut line| {
+            line.push('\n');
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+            file.write_all(line.as_bytes())?;
+            file.sync_all()
+        });
+    if let Err(e) = res {
+        error!("Failed to record settled transaction in the relayer ledger {e:?}");
+    }
+}
+
+/// Loads every entry in the ledger at `path`, a missing file is an empty ledger. A crash mid append
+/// can only truncate the final entry, which is skipped
+pub fn load_ledger(path: &Path) -> Result<Vec<LedgerEntry>, GravityError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path)?;
+    let lines: Vec<&str> = contents.lines().filter(|l| !l.trim().is_empty()).collect();
+    let mut entries = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        match serde_json::from_str::<LedgerEntry>(line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) if i == lines.len() - 1 && !contents.ends_with('\n') => {
+                warn!("Skipping incomplete final relayer ledger entry {e}");
+            }
+            Err(e) => {
+                return Err(GravityError::IoError(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "Corrupt relayer ledger {} line {} {e}",
+                        path.display(),
+                        i + 1
+                    ),
+                )))
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// The totals of a group of ledger entries, values in wei
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LedgerSummary {
+    pub submissions: u64,
+    pub successes: u64,
+    pub reverts: u64,
+    pub beaten: u64,
+    pub cost: Uint256,
+    pub revenue: Uint256,
+}
+
+impl LedgerSummary {
+    fn add(&mut self, entry: &LedgerEntry) {
+        self.submissions += 1;
+        match entry.outcome {
+            Outcome::Success => self.successes += 1,
+            Outcome::Reverted => self.reverts += 1,
+            Outcome::Beaten => self.beaten += 1,
+            Outcome::Unknown => {}
+        }
+        self.cost += entry.get_cost();
+        self.revenue += entry.get_revenue();
+    }
+
+    /// Revenue minus cost, computed on Uint256 so that no total is too large to report
+    pub fn get_profit(&self) -> Profit {
+        if self.revenue >= self.cost {
+            Profit::Gain(self.revenue - self.cost)
+        } else {
+            Profit::Loss(self.cost - self.revenue)
+        }
+    }
+}
+
+/// The profit or loss of a LedgerSummary in wei
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Profit {
+    Gain(Uint256),
+    Loss(Uint256),
+}
+
+/// Summarizes the entries settled at or after `since` by reward token and by period, periods are
+/// `period` seconds long and keyed by their start time. Entries without a reward token are grouped
+/// under None
+#[allow(clippy::type_complexity)]
+pub fn summarize(
+    entries: &[LedgerEntry],
+    period: u64,
+    since: u64,
+) -> (
+    BTreeMap<Option<EthAddress>, LedgerSummary>,
+    BTreeMap<u64, LedgerSummary>,
+) {
+    let mut by_token: BTreeMap<Option<EthAddress>, LedgerSummary> = BTreeMap::new();
+    let mut by_period: BTreeMap<u64, LedgerSummary> = BTreeMap::new();
+    for entry in entries.iter().filter(|e| e.settled_at >= since) {
+        by_token
+            .entry(entry.get_reward_token())
+            .or_default()
+            .add(entry);
+        let start = entry.settled_at - entry.settled_at % period;
+        by_period.entry(start).or_default().add(entry);
+    }
+    (by_token, by_period)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_ledger() {
+        let a =
+            EthAddress::parse_and_validate("0x70EDF1c215D0ce69E7F16FD4E6276ba0d99d4de7").unwrap();
+        // every entry costs 1000 in gas and is worth 3000 if it was rewarded
+        let entry = |token: Option<EthAddress>, outcome, settled_at| LedgerEntry {
+            submission: Submission::Valset { nonce: 1 },
+            txid: 1u8.into(),
+            gas_used: Some(100u64.into()),
+            effective_gas_price: Some(10u64.into()),
+            rewards: token
+                .map(|t| {
+                    vec![Erc20Token {
+                        amount: 5u8.into(),
+                        token_contract_address: t,
+                    }]
+                })
+                .unwrap_or_default(),
+            reward_weth_value: Some(3000u64.into()),
+            outcome,
+            settled_at,
+        };
+        let entries = vec![
+            entry(Some(a), Outcome::Success, 100),
+            entry(Some(a), Outcome::Beaten, 150),
+            entry(None, Outcome::Reverted, 250),
+            entry(Some(a), Outcome::Success, 10),
+        ];
+        let (by_token, by_period) = summarize(&entries, 100, 50);
+
+        let a_summary = &by_token[&Some(a)];
+        assert_eq!(a_summary.submissions, 2);
+        assert_eq!(a_summary.successes, 1);
+        assert_eq!(a_summary.beaten, 1);
+        assert_eq!(a_summary.get_profit(), Profit::Gain(1000u64.into()));
+        assert_eq!(by_token[&None].get_profit(), Profit::Loss(1000u64.into()));
+
+        // totals beyond i128 are still reported exactly
+        let huge = LedgerSummary {
+            cost: 1u8.into(),
+            revenue: Uint256::from_be_bytes(&[0xff; 32]),
+            ..Default::default()
+        };
+        assert_eq!(
+            huge.get_profit(),
+            Profit::Gain(Uint256::from_be_bytes(&[0xff; 32]) - Uint256::from(1u8))
+        );
+
+        assert_eq!(
+            by_period.keys().cloned().collect::<Vec<_>>(),
+            vec![100, 200]
+        );
+        assert_eq!(by_period[&100].submissions, 2);
+        assert_eq!(by_period[&200].reverts, 1);
+    }
+}
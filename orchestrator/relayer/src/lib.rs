@@ -1,10 +1,16 @@
 pub mod altruistic;
+pub mod batch_planner;
 pub mod batch_relaying;
+pub mod fee_strategy;
 pub mod find_latest_valset;
+pub mod funds_guard;
 pub mod ibc_auto_forwarding;
+pub mod ledger;
 pub mod logic_call_relaying;
 pub mod main_loop;
+pub mod private_relay;
 pub mod request_batches;
+pub mod tx_manager;
 pub mod valset_relaying;
 
 #[macro_use]
@@ -12,3 +18,6 @@ extern crate log;
 
 #[macro_use]
 extern crate lazy_static;
+
+#[macro_use]
+extern crate serde_derive;
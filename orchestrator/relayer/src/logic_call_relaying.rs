@@ -1,12 +1,15 @@
-use crate::main_loop::ETH_SUBMIT_WAIT_TIME;
+use crate::fee_strategy::get_relaying_fees;
+use crate::funds_guard::check_funds;
+use crate::ledger::RelayReward;
+use crate::tx_manager::{has_pending, submit_transaction, Submission};
 use clarity::{address::Address as EthAddress, utils::bytes_to_hex_str};
 use clarity::{PrivateKey as EthPrivateKey, Uint256};
 use cosmos_gravity::query::{get_latest_logic_calls, get_logic_call_signatures};
 use ethereum_gravity::message_signatures::encode_logic_call_confirm_hashed;
-use ethereum_gravity::{logic_call::send_eth_logic_call, utils::get_logic_call_nonce};
+use ethereum_gravity::{logic_call::encode_logic_call_payload, utils::get_logic_call_nonce};
 use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::num_conversion::{print_eth, print_gwei};
-use gravity_utils::prices::get_weth_price_with_retries;
+use gravity_utils::prices::get_relayer_weth_price;
 use gravity_utils::types::{LogicCall, RelayerConfig};
 use gravity_utils::types::{LogicCallConfirmResponse, Valset};
 use std::collections::HashMap;
@@ -14,13 +17,14 @@ use tonic::transport::Channel;
 use web30::amm::WETH_CONTRACT_ADDRESS;
 use web30::client::Web3;
 
-// Determines whether or not submitting `logic_call` will be profitable given the estimated `cost`
+// Determines whether or not submitting `logic_call` will be profitable given the worst case `cost`
 // and the current exchange rate available on uniswap
 async fn should_relay_logic_call(
     our_address: EthAddress,
     web3: &Web3,
     logic_call: &LogicCall,
     cost: Uint256,
+    config: &RelayerConfig,
 ) -> bool {
     // Fill a hashmap with reward totals by token type
     let mut rewards: HashMap<EthAddress, Uint256> = HashMap::new();
@@ -38,7 +42,8 @@ async fn should_relay_logic_call(
             total_weth_reward += *total;
         } else {
             // Get the token's value in ETH as of the current moment
-            let weth_equiv = get_weth_price_with_retries(our_address, *token, *total, web3).await;
+            let weth_equiv =
+                get_relayer_weth_price(config, our_address, *token, *total, web3).await;
             if weth_equiv.is_err() {
                 // Can't get the price so we ignore it
                 info!(
@@ -129,6 +134,24 @@ pub async fn relay_logic_calls(
     let latest_ethereum_call = latest_ethereum_call.unwrap();
     let latest_cosmos_call_nonce = oldest_signed_call.clone().invalidation_nonce;
     if latest_cosmos_call_nonce > latest_ethereum_call {
+        let submission = Submission::LogicCall {
+            invalidation_id: bytes_to_hex_str(&oldest_signed_call.invalidation_id),
+            nonce: oldest_signed_call.invalidation_nonce,
+        };
+        if has_pending(&submission) {
+            info!(
+                "LogicCall {} already has a pending transaction, waiting for it",
+                latest_cosmos_call_nonce
+            );
+            return;
+        }
+        let fees = match get_relaying_fees(web3, &config).await {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("Not relaying logic calls, could not determine transaction fees {e}");
+                return;
+            }
+        };
         let cost = ethereum_gravity::logic_call::estimate_logic_call_cost(
             current_valset.clone(),
             oldest_signed_call.clone(),
@@ -137,6 +160,7 @@ pub async fn relay_logic_calls(
             gravity_contract_address,
             gravity_id.clone(),
             ethereum_key.to_address(),
+            fees,
         )
         .await;
         if cost.is_err() {
@@ -145,12 +169,14 @@ pub async fn relay_logic_calls(
         }
         let cost = cost.unwrap();
         info!(
-                "We have detected latest LogicCall {} but latest on Ethereum is {} This LogicCall is estimated to cost {} Gas @ {} Gwei / {:.4} ETH to submit",
+                "We have detected latest LogicCall {} but latest on Ethereum is {} This LogicCall is estimated to cost {} Gas @ {} Gwei / {:.4} ETH to submit, at most {} Gwei / {:.4} ETH",
                 latest_cosmos_call_nonce,
                 latest_ethereum_call,
                 cost.gas.clone(),
                 print_gwei(cost.gas_price),
-                print_eth(cost.get_total())
+                print_eth(cost.get_total()),
+                print_gwei(cost.max_fee_per_gas),
+                print_eth(cost.get_worst_case_total())
             );
 
         let should_relay = if config.logic_call_market_enabled {
@@ -158,25 +184,53 @@ pub async fn relay_logic_calls(
                 our_ethereum_address,
                 web3,
                 &oldest_signed_call,
-                cost.get_total(),
+                cost.get_worst_case_total(),
+                &config,
             )
             .await
         } else {
             true
         };
 
-        if should_relay {
-            let res = send_eth_logic_call(
-                current_valset,
-                oldest_signed_call,
-                &oldest_signatures,
+        if should_relay
+            && check_funds(
                 web3,
-                ETH_SUBMIT_WAIT_TIME,
-                gravity_contract_address,
-                gravity_id.clone(),
-                ethereum_key,
+                our_ethereum_address,
+                cost.get_worst_case_total(),
+                None,
+                &config,
+            )
+            .await
+        {
+            let reward = RelayReward::new(
+                oldest_signed_call.fees.clone(),
+                our_ethereum_address,
+                web3,
+                &config,
             )
             .await;
+            let res = match encode_logic_call_payload(
+                current_valset,
+                &oldest_signed_call,
+                &oldest_signatures,
+                gravity_id.clone(),
+            ) {
+                Ok(payload) => {
+                    submit_transaction(
+                        web3,
+                        ethereum_key,
+                        gravity_contract_address,
+                        payload,
+                        fees,
+                        cost.get_worst_case_total(),
+                        submission,
+                        reward,
+                        &config,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
             if res.is_err() {
                 info!("LogicCall submission failed with {res:?}");
             }
@@ -2,8 +2,10 @@ use crate::altruistic::{
     gas_tracker_loop, get_acceptable_gas_price, get_current_gas_price, get_num_gas_tracker_samples,
     update_gas_history_samples,
 };
+use crate::funds_guard::update_balance_metric;
 use crate::ibc_auto_forwarding::ibc_auto_forward_loop;
 use crate::request_batches::request_batches;
+use crate::tx_manager::process_pending_transactions;
 use crate::{
     batch_relaying::relay_batches, find_latest_valset::find_latest_valset,
     logic_call_relaying::relay_logic_calls, valset_relaying::relay_valsets,
@@ -12,9 +14,14 @@ use clarity::address::Address as EthAddress;
 use clarity::PrivateKey as EthPrivateKey;
 use deep_space::{Coin, Contact, CosmosPrivateKey};
 use futures::future::join3;
+use gravity_proto::cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::service_client::ServiceClient as TendermintServiceClient;
 use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
-use gravity_utils::num_conversion::print_gwei;
-use gravity_utils::types::{BatchRelayingMode, RelayerConfig, ValsetRelayingMode};
+use gravity_utils::connection_prep::CosmosGrpcPool;
+use gravity_utils::num_conversion::{print_gwei, wei_to_gwei};
+use gravity_utils::types::{
+    BatchRelayingMode, RelayerConfig, SharedRelayerConfig, ValsetRelayingMode,
+};
+use metrics_exporter::{metrics_relayer_gas_price, metrics_relayer_iteration};
 use std::time::{Duration, Instant};
 use tokio::time::sleep as delay_for;
 use tonic::transport::Channel;
@@ -26,18 +33,19 @@ pub const TIMEOUT: Duration = Duration::from_secs(10);
 pub const ETH_SUBMIT_WAIT_TIME: Duration = Duration::from_secs(600);
 
 /// bundles the relayer_main_loop, ibc_auto_forward_loop, and gas_tracker_loop together into a single future
+/// `config` may be replaced while the loops are running, each loop picks up the new config on its next iteration
 #[allow(clippy::too_many_arguments)]
 pub async fn all_relayer_loops(
     cosmos_key: Option<CosmosPrivateKey>,
     ethereum_key: EthPrivateKey,
     web3: Web3,
-    contact: Contact,
-    grpc_client: GravityQueryClient<Channel>,
+    cosmos_pool: CosmosGrpcPool,
     gravity_contract_address: EthAddress,
     gravity_id: String,
     fee: Option<Coin>,
-    config: RelayerConfig,
+    shared_config: SharedRelayerConfig,
 ) {
+    let config = shared_config.get();
     if config.gas_tracker_loop_speed > 60u64 {
         panic!(
             "Invalid configured gas_tracker_loop_speed ({}): must be 60 seconds or less",
@@ -53,20 +61,13 @@ pub async fn all_relayer_loops(
         cosmos_key,
         fee.clone(),
         web3.clone(),
-        contact.clone(),
-        grpc_client.clone(),
+        cosmos_pool.clone(),
         gravity_contract_address,
         gravity_id,
-        config.clone(),
-    );
-    let b = ibc_auto_forward_loop(
-        cosmos_key,
-        &contact,
-        grpc_client.clone(),
-        fee.clone(),
-        config.clone(),
+        shared_config.clone(),
     );
-    let c = gas_tracker_loop(&web3, config.clone());
+    let b = ibc_auto_forward_loop(cosmos_key, cosmos_pool, fee.clone(), shared_config.clone());
+    let c = gas_tracker_loop(&web3, shared_config);
 
     join3(a, b, c).await;
 }
@@ -79,16 +80,16 @@ pub async fn relayer_main_loop(
     cosmos_key: Option<CosmosPrivateKey>,
     cosmos_fee: Option<Coin>,
     web3: Web3,
-    contact: Contact,
-    grpc_client: GravityQueryClient<Channel>,
+    cosmos_pool: CosmosGrpcPool,
     gravity_contract_address: EthAddress,
     gravity_id: String,
-    relayer_config: RelayerConfig,
+    shared_config: SharedRelayerConfig,
 ) {
-    let grpc_client = grpc_client;
-
     loop {
         let loop_start = Instant::now();
+        let relayer_config = shared_config.get();
+        let (contact, grpc_client) = cosmos_pool.get_healthy_connection().await;
+        let tendermint_client = cosmos_pool.get_tendermint();
 
         // use the gas estimator to determine if we should relay altruistically
         let current_gas_price = get_current_gas_price();
@@ -99,6 +100,11 @@ pub async fn relayer_main_loop(
             current_gas_price.map(print_gwei),
             ideal_gas.map(print_gwei)
         );
+        metrics_relayer_gas_price(
+            current_gas_price.map(wei_to_gwei),
+            ideal_gas.map(wei_to_gwei),
+        );
+        update_balance_metric(&web3, ethereum_key.to_address()).await;
         let should_relay_altruistic =
             if let (Some(current_price), Some(good_price)) = (current_gas_price, ideal_gas) {
                 current_price <= good_price
@@ -113,12 +119,14 @@ pub async fn relayer_main_loop(
             &contact,
             &web3,
             &grpc_client,
+            &tendermint_client,
             gravity_contract_address,
             &gravity_id,
             &relayer_config,
             should_relay_altruistic,
         )
         .await;
+        metrics_relayer_iteration("relayer", loop_start.elapsed());
 
         delay_until_next_iteration(loop_start, relayer_config.relayer_loop_speed).await;
     }
@@ -137,12 +145,17 @@ pub async fn single_relayer_iteration(
     contact: &Contact,
     web3: &Web3,
     grpc_client: &GravityQueryClient<Channel>,
+    tendermint_client: &TendermintServiceClient<Channel>,
     gravity_contract_address: EthAddress,
     gravity_id: &str,
     relayer_config: &RelayerConfig,
     should_relay_altruistic: bool,
 ) {
     let mut grpc_client: GravityQueryClient<Channel> = grpc_client.clone();
+    // replace or cancel any transactions still pending from previous iterations before
+    // relaying anything new
+    process_pending_transactions(web3, ethereum_key, relayer_config).await;
+
     if let (Some(cosmos_key), Some(cosmos_fee)) = (cosmos_key, cosmos_fee.clone()) {
         // Batches are only requested if it is a good time to do so, no checks needed here
         request_batches(
@@ -157,12 +170,16 @@ pub async fn single_relayer_iteration(
         .await
     }
 
-    // we should relay if we're not altruistic or if we are and the gas price is good
+    // we should relay if we're not altruistic or if we are and the gas price is good, when
+    // urgency is weighed the gas price is judged separately for each valset and batch
+    let weigh_urgency = relayer_config.altruistic_urgency.is_some();
     let should_relay_valsets = relayer_config.valset_relaying_mode
         != ValsetRelayingMode::Altruistic
-        || should_relay_altruistic;
+        || should_relay_altruistic
+        || weigh_urgency;
     let should_relay_batches = relayer_config.batch_relaying_mode != BatchRelayingMode::Altruistic
-        || should_relay_altruistic;
+        || should_relay_altruistic
+        || weigh_urgency;
 
     let current_valset = find_latest_valset(&mut grpc_client, gravity_contract_address, web3).await;
     if current_valset.is_err() {
@@ -175,6 +192,7 @@ pub async fn single_relayer_iteration(
         relay_valsets(
             current_valset.clone(),
             ethereum_key,
+            tendermint_client,
             web3,
             &mut grpc_client,
             gravity_contract_address,
@@ -199,6 +217,7 @@ pub async fn single_relayer_iteration(
         relay_batches(
             current_valset.clone(),
             ethereum_key,
+            tendermint_client,
             web3,
             &mut grpc_client,
             gravity_contract_address,
@@ -0,0 +1,270 @@
+//! Submission of signed relay transactions to a private relay rather than the public mempool. Profitable
+//! batches are a race, a transaction in the public mempool can be copied by another relayer paying a
+//! higher priority fee who then collects the reward while ours reverts. Private relays only share the
+//! transaction with block builders, who include it as is or not at all.
+//!
+//! Both the Flashbots style eth_sendPrivateTransaction and eth_sendBundle methods are supported, the
+//! transaction manager falls back to the public mempool if the transaction is not included in time.
+//! Flashbots style relays reject requests that are not signed, every request is signed with the
+//! relayer's Ethereum key in the X-Flashbots-Signature header
+
+use clarity::utils::bytes_to_hex_str;
+use clarity::{PrivateKey as EthPrivateKey, Uint256};
+use futures::future::join_all;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::SubmissionMode;
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use std::time::Duration;
+use web30::jsonrpc::error::Web3Error;
+
+/// The timeout for requests to the private relay
+pub const PRIVATE_RELAY_TIMEOUT: Duration = Duration::from_secs(10);
+/// The most blocks a bundle is sent for, longer fallback windows are shortened to this in Bundle mode
+pub const MAX_BUNDLE_BLOCKS: u64 = 25;
+/// The header Flashbots style relays authenticate requests with
+pub const FLASHBOTS_SIGNATURE_HEADER: &str = "X-Flashbots-Signature";
+
+fn to_hex(v: u64) -> String {
+    format!("{v:#x}")
+}
+
+fn raw_tx_to_hex(raw_tx: &[u8]) -> String {
+    format!("0x{}", bytes_to_hex_str(raw_tx))
+}
+
+/// The params of an eth_sendPrivateTransaction request, the relay drops the transaction after
+/// `max_block_number`
+pub fn private_transaction_params(raw_tx: &[u8], max_block_number: u64) -> Value {
+    json!([{
+        "tx": raw_tx_to_hex(raw_tx),
+        "maxBlockNumber": to_hex(max_block_number),
+    }])
+}
+
+/// The params of an eth_sendBundle request for a bundle of only `raw_tx`, valid for only `block_number`
+pub fn bundle_params(raw_tx: &[u8], block_number: u64) -> Value {
+    json!([{
+        "txs": [raw_tx_to_hex(raw_tx)],
+        "blockNumber": to_hex(block_number),
+    }])
+}
+
+/// The hash of a signed transaction, private relays do not reliably return it
+pub fn get_txid(raw_tx: &[u8]) -> Uint256 {
+    Uint256::from_be_bytes(&Keccak256::digest(raw_tx))
+}
+
+/// The X-Flashbots-Signature header value for a request `body`, the signer's address and its EIP-191
+/// signature of the hex encoded keccak256 hash of the body
+pub fn flashbots_signature(key: EthPrivateKey, body: &str) -> String {
+    let hash = format!(
+        "0x{}",
+        bytes_to_hex_str(&Keccak256::digest(body.as_bytes()))
+    );
+    let signature = key.sign_ethereum_msg(hash.as_bytes());
+    format!(
+        "{}:0x{}",
+        key.to_address(),
+        bytes_to_hex_str(&signature.to_bytes())
+    )
+}
+
+fn relay_error(msg: String) -> GravityError {
+    GravityError::EthereumRestError(Web3Error::BadResponse(msg))
+}
+
+/// Sends a signed JSON-RPC request to the private relay at `endpoint`, returning the result
+async fn relay_request(
+    endpoint: &str,
+    key: EthPrivateKey,
+    method: &str,
+    params: Value,
+) -> Result<Value, GravityError> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    })
+    .to_string();
+    let client = reqwest::Client::builder()
+        .timeout(PRIVATE_RELAY_TIMEOUT)
+        .build()
+        .map_err(|e| relay_error(format!("Failed to build private relay client {e}")))?;
+    let mut response: Value = client
+        .post(endpoint)
+        .header("Content-Type", "application/json")
+        .header(FLASHBOTS_SIGNATURE_HEADER, flashbots_signature(key, &body))
+        .body(body)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| relay_error(format!("Private relay {method} request failed {e}")))?
+        .json()
+        .await
+        .map_err(|e| relay_error(format!("Invalid private relay {method} response {e}")))?;
+    if let Some(e) = response.get("error") {
+        return Err(relay_error(format!("Private relay {method} failed {e}")));
+    }
+    match response.get_mut("result") {
+        Some(result) => Ok(result.take()),
+        None => Err(relay_error(format!(
+            "Private relay {method} response has no result {response}"
+        ))),
+    }
+}
+
+/// Sends a signed transaction to the private relay configured in `mode`, returning the last block
+/// the relay may include it in. After that block a transaction that is still pending should be
+/// sent to the public mempool. Returns None in Public mode, nothing is sent in that case. Requests
+/// are signed with `key`
+pub async fn send_private(
+    mode: &SubmissionMode,
+    key: EthPrivateKey,
+    raw_tx: &[u8],
+    current_block: u64,
+) -> Result<Option<u64>, GravityError> {
+    match mode {
+        SubmissionMode::Public => Ok(None),
+        SubmissionMode::PrivateTransaction {
+            endpoint,
+            fallback_blocks,
+        } => {
+            let last_block = current_block + fallback_blocks;
+            relay_request(
+                endpoint,
+                key,
+                "eth_sendPrivateTransaction",
+                private_transaction_params(raw_tx, last_block),
+            )
+            .await?;
+            Ok(Some(last_block))
+        }
+        SubmissionMode::Bundle {
+            endpoint,
+            fallback_blocks,
+        } => {
+            let last_block = current_block + (*fallback_blocks).min(MAX_BUNDLE_BLOCKS);
+            // a bundle targets a single block, so one is sent for each block in the window, all at
+            // once so that a slow relay can not stall the relayer for the whole window
+            let bundle_blocks = current_block + 1..=last_block;
+            let results = join_all(bundle_blocks.clone().map(|block| {
+                relay_request(
+                    endpoint,
+                    key,
+                    "eth_sendBundle",
+                    bundle_params(raw_tx, block),
+                )
+            }))
+            .await;
+            let mut sent = 0;
+            for (block, res) in bundle_blocks.zip(results) {
+                match res {
+                    Ok(_) => sent += 1,
+                    Err(e) => warn!("Failed to send bundle for block {block} {e}"),
+                }
+            }
+            if sent == 0 {
+                return Err(GravityError::InvalidOptionsError(format!(
+                    "The private relay {endpoint} did not accept any bundles"
+                )));
+            }
+            Ok(Some(last_block))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix::System;
+    use futures::future::{select, Either};
+    use jsonrpc_server::server::{get_private_relay_requests, get_private_transactions, run};
+
+    #[test]
+    fn test_private_relay_params() {
+        let raw_tx = [0x02u8, 0xf8, 0x01];
+        assert_eq!(
+            private_transaction_params(&raw_tx, 255),
+            json!([{"tx": "0x02f801", "maxBlockNumber": "0xff"}])
+        );
+        assert_eq!(
+            bundle_params(&raw_tx, 16),
+            json!([{"txs": ["0x02f801"], "blockNumber": "0x10"}])
+        );
+    }
+
+    #[test]
+    fn test_send_private() {
+        let key = EthPrivateKey::from_bytes([5u8; 32]).unwrap();
+        let endpoint = "http://127.0.0.1:8595".to_string();
+        let raw_tx = [0x02u8, 0xf8, 0x6f, 0x01];
+        let runner = System::new();
+        runner.block_on(async move {
+            let server = run(
+                Some("127.0.0.1".to_string()),
+                Some("8595".to_string()),
+                None,
+                None,
+                None,
+            );
+            let client = async {
+                // wait for the mock relay to bind
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                let mode = SubmissionMode::PrivateTransaction {
+                    endpoint: endpoint.clone(),
+                    fallback_blocks: 5,
+                };
+                let res = send_private(&mode, key, &raw_tx, 100).await.unwrap();
+                assert_eq!(res, Some(105));
+
+                let mode = SubmissionMode::Bundle {
+                    endpoint: endpoint.clone(),
+                    fallback_blocks: 100,
+                };
+                let res = send_private(&mode, key, &raw_tx, 100).await.unwrap();
+                assert_eq!(res, Some(100 + MAX_BUNDLE_BLOCKS));
+
+                // one private transaction and one bundle per block in the capped window
+                let received = get_private_transactions();
+                assert_eq!(received.len() as u64, 1 + MAX_BUNDLE_BLOCKS);
+                assert!(received.iter().all(|tx| *tx == "0x02f86f01"));
+
+                let requests = get_private_relay_requests();
+                assert_eq!(requests.len() as u64, 1 + MAX_BUNDLE_BLOCKS);
+                for (signature, body) in requests.iter() {
+                    let signature = signature.as_ref().expect("Unsigned private relay request");
+                    assert_eq!(*signature, flashbots_signature(key, body));
+                    assert!(signature.starts_with(&format!("{}:0x", key.to_address())));
+                }
+                let bodies: Vec<Value> = requests
+                    .iter()
+                    .map(|(_, body)| serde_json::from_str(body).unwrap())
+                    .collect();
+                assert_eq!(bodies[0]["method"], "eth_sendPrivateTransaction");
+                assert_eq!(
+                    bodies[0]["params"],
+                    private_transaction_params(&raw_tx, 105)
+                );
+                let mut bundle_blocks: Vec<Value> = bodies[1..]
+                    .iter()
+                    .map(|b| {
+                        assert_eq!(b["method"], "eth_sendBundle");
+                        b["params"][0]["blockNumber"].clone()
+                    })
+                    .collect();
+                bundle_blocks
+                    .sort_by_key(|b| u64::from_str_radix(&b.as_str().unwrap()[2..], 16).unwrap());
+                let expected: Vec<Value> = (101..=100 + MAX_BUNDLE_BLOCKS)
+                    .map(|block| json!(to_hex(block)))
+                    .collect();
+                assert_eq!(bundle_blocks, expected);
+            };
+            match select(Box::pin(server), Box::pin(client)).await {
+                Either::Left((res, _)) => panic!("Mock relay exited {res:?}"),
+                Either::Right(_) => {}
+            }
+        });
+    }
+}
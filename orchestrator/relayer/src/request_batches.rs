@@ -14,7 +14,7 @@ use cosmos_gravity::send::send_request_batch;
 use deep_space::{Coin, Contact, PrivateKey};
 use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::num_conversion::print_eth;
-use gravity_utils::prices::get_weth_price_with_retries;
+use gravity_utils::prices::get_relayer_weth_price;
 use gravity_utils::types::BatchRequestMode;
 use gravity_utils::types::RelayerConfig;
 use tokio::time::sleep as delay_for;
@@ -77,7 +77,7 @@ pub async fn request_batches(
         match config.batch_request_mode {
             BatchRequestMode::ProfitableOnly => {
                 let weth_cost_estimate = eth_gas_price * BATCH_GAS.into();
-                match get_weth_price_with_retries(eth_address, token, total_fee, web30).await {
+                match get_relayer_weth_price(config, eth_address, token, total_fee, web30).await {
                     Ok(price) => {
                         if price > weth_cost_estimate {
                             info!(
@@ -0,0 +1,756 @@
+//! The relayer transaction manager tracks every transaction the relayer submits to Ethereum by its
+//! account nonce until one transaction with that nonce is included. Transactions that are not
+//! included within `tx_bump_interval` are replaced with the same transaction at higher fees, and
+//! transactions whose valset, batch, or logic call has already been submitted by another relayer are
+//! replaced with a zero value transfer to ourselves so that the relayer does not pay for a revert.
+//!
+//! The pending set is persisted to disk so that transactions left in the mempool by a restart are
+//! still managed, and so that a restarted relayer does not submit a conflicting transaction.
+//!
+//! If a private relay is configured transactions are sent there first, and only sent to the public
+//! mempool once they have not been included for the configured number of blocks.
+
+use crate::funds_guard::{check_funds, record_spend};
+use crate::ledger::{record_settled_transaction, LedgerEntry, Outcome, RelayReward};
+use crate::main_loop::ETH_SUBMIT_WAIT_TIME;
+use crate::private_relay::{get_txid, send_private};
+use clarity::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use clarity::{Address as EthAddress, PrivateKey as EthPrivateKey, Transaction, Uint256};
+use ethereum_gravity::utils::{
+    get_logic_call_nonce, get_tx_batch_nonce, get_valset_nonce, Eip1559Fees,
+};
+use gravity_utils::error::GravityError;
+use gravity_utils::num_conversion::print_gwei;
+use gravity_utils::types::{RelayerConfig, SubmissionMode};
+use metrics_exporter::metrics_relayer_submission;
+use num_traits::ToPrimitive;
+use std::cmp::{max, min};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use web30::client::Web3;
+use web30::types::SendTxOption;
+
+/// The priority fee used when the fee strategy leaves the priority fee to the node, an explicit
+/// priority fee is required so that replacements can be priced above the original
+pub const DEFAULT_PRIORITY_FEE: u64 = 1_000_000_000;
+/// Ethereum clients require a replacement transaction to increase both fees by at least 10%
+pub const MIN_BUMP_PERCENTAGE: f32 = 0.1;
+/// The gas used by the zero value transfer to ourselves that cancels a submission
+pub const CANCEL_GAS: u64 = 21_000;
+
+lazy_static! {
+    static ref TX_MANAGER: RwLock<TxManager> = RwLock::new(TxManager::default());
+}
+
+/// What a pending transaction is submitting
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Submission {
+    Valset {
+        nonce: u64,
+    },
+    Batch {
+        token_contract: EthAddress,
+        nonce: u64,
+    },
+    LogicCall {
+        invalidation_id: String,
+        nonce: u64,
+    },
+}
+
+impl Submission {
+    pub fn get_type(&self) -> &'static str {
+        match self {
+            Submission::Valset { .. } => "valset",
+            Submission::Batch { .. } => "batch",
+            Submission::LogicCall { .. } => "logic_call",
+        }
+    }
+
+    /// Checks if the Gravity contract has already advanced past this submission, in which
+    /// case submitting it would only revert
+    async fn is_superseded(
+        &self,
+        gravity_contract_address: EthAddress,
+        our_address: EthAddress,
+        web3: &Web3,
+    ) -> Result<bool, GravityError> {
+        Ok(match self {
+            Submission::Valset { nonce } => {
+                get_valset_nonce(gravity_contract_address, our_address, web3).await? >= *nonce
+            }
+            Submission::Batch {
+                token_contract,
+                nonce,
+            } => {
+                get_tx_batch_nonce(gravity_contract_address, *token_contract, our_address, web3)
+                    .await?
+                    >= *nonce
+            }
+            Submission::LogicCall {
+                invalidation_id,
+                nonce,
+            } => {
+                let invalidation_id = hex_str_to_bytes(invalidation_id).map_err(|e| {
+                    GravityError::InvalidOptionsError(format!("Invalid invalidation id {e:?}"))
+                })?;
+                get_logic_call_nonce(gravity_contract_address, invalidation_id, our_address, web3)
+                    .await?
+                    >= *nonce
+            }
+        })
+    }
+}
+
+/// A submitted transaction that has not yet been included, replacements keep the same account nonce
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PendingTransaction {
+    pub submission: Submission,
+    pub from: EthAddress,
+    /// The Gravity contract the submission is for, used to check if it has been superseded
+    pub gravity_contract: EthAddress,
+    /// The destination of the most recent replacement, our own address once cancelled
+    pub to: EthAddress,
+    pub data: String,
+    pub nonce: Uint256,
+    /// The hash of the most recent replacement
+    pub txid: Uint256,
+    pub max_fee_per_gas: Uint256,
+    pub max_priority_fee_per_gas: Uint256,
+    /// The worst case cost in wei of the most recent replacement, counted against the spend limits
+    /// until the transaction settles
+    #[serde(default)]
+    pub max_cost: Uint256,
+    /// Unix time in seconds of the most recent replacement
+    pub submitted_at: u64,
+    pub replacements: u32,
+    /// If the transaction was sent to a private relay, the last block the relay may include it in
+    #[serde(default)]
+    pub private_until_block: Option<u64>,
+    /// The hashes of the transactions this one replaced, any of which may be the one included
+    #[serde(default)]
+    pub replaced_txids: Vec<Uint256>,
+    /// The hashes of the zero value transfers to ourselves sent to cancel the submission once it
+    /// was no longer needed, empty if it was never cancelled
+    #[serde(default)]
+    pub cancel_txids: Vec<Uint256>,
+    /// What relaying the submission earns, recorded in the ledger once it is included
+    #[serde(default)]
+    pub reward: RelayReward,
+}
+
+impl PendingTransaction {
+    pub fn is_cancelled(&self) -> bool {
+        !self.cancel_txids.is_empty()
+    }
+
+    /// The worst case cost of replacing this transaction at `max_fee_per_gas`, a replacement uses
+    /// the same gas limit and a cancel only the gas of a transfer
+    pub fn get_replacement_cost(&self, max_fee_per_gas: Uint256, cancel: bool) -> Uint256 {
+        if cancel || self.is_cancelled() {
+            Uint256::from(CANCEL_GAS) * max_fee_per_gas
+        } else if self.max_fee_per_gas == 0u8.into() {
+            self.max_cost
+        } else {
+            self.max_cost * max_fee_per_gas / self.max_fee_per_gas
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct TxManager {
+    path: Option<PathBuf>,
+    pending: Vec<PendingTransaction>,
+}
+
+impl TxManager {
+    /// Writes the pending set to a temporary file and renames it so that a crash mid write can
+    /// not leave a corrupted file behind
+    fn save(&self) {
+        let path = match &self.path {
+            Some(p) => p,
+            None => return,
+        };
+        let tmp_path = path.with_extension("tmp");
+        let res = serde_json::to_string_pretty(&self.pending)
+            .map_err(std::io::Error::from)
+            .and_then(|v| fs::write(&tmp_path, v))
+            .and_then(|_| fs::rename(&tmp_path, path));
+        if let Err(e) = res {
+            error!("Failed to save pending relayer transactions {e:?}");
+        }
+    }
+
+    fn upsert(&mut self, tx: PendingTransaction) {
+        self.pending
+            .retain(|p| !(p.from == tx.from && p.nonce == tx.nonce));
+        self.pending.push(tx);
+        self.save();
+    }
+
+    fn remove(&mut self, from: EthAddress, nonce: Uint256) {
+        self.pending
+            .retain(|p| !(p.from == from && p.nonce == nonce));
+        self.save();
+    }
+}
+
+/// Loads the pending transactions left by a previous run and persists the pending set at `path`
+/// from now on, a missing file is treated as an empty pending set
+pub fn load_pending_transactions(path: PathBuf) -> Result<(), GravityError> {
+    let pending = if path.exists() {
+        let contents = fs::read_to_string(&path)?;
+        serde_json::from_str(&contents).map_err(std::io::Error::from)?
+    } else {
+        Vec::new()
+    };
+    let mut manager = TX_MANAGER.write().unwrap();
+    manager.path = Some(path);
+    manager.pending = pending;
+    Ok(())
+}
+
+/// Returns true if a transaction for this submission is still pending
+pub fn has_pending(submission: &Submission) -> bool {
+    TX_MANAGER
+        .read()
+        .unwrap()
+        .pending
+        .iter()
+        .any(|p| p.submission == *submission)
+}
+
+/// The total worst case cost of the pending transactions from `from`, other than the one with nonce
+/// `excluding` which is being replaced
+pub fn get_pending_cost(from: EthAddress, excluding: Option<Uint256>) -> Uint256 {
+    let mut total: Uint256 = 0u8.into();
+    for p in get_pending(from) {
+        if Some(p.nonce) != excluding {
+            total += p.max_cost;
+        }
+    }
+    total
+}
+
+fn get_pending(from: EthAddress) -> Vec<PendingTransaction> {
+    TX_MANAGER
+        .read()
+        .unwrap()
+        .pending
+        .iter()
+        .filter(|p| p.from == from)
+        .cloned()
+        .collect()
+}
+
+fn decode_data(data: &str) -> Result<Vec<u8>, GravityError> {
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+    hex_str_to_bytes(data).map_err(|e| {
+        GravityError::InvalidOptionsError(format!("Invalid pending transaction data {e:?}"))
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Increases the fee by the given percentage, or at least the minimum replacement increase
+pub fn bump_fee(fee: Uint256, percentage: f32) -> Uint256 {
+    let percentage = percentage.max(MIN_BUMP_PERCENTAGE);
+    // integer basis points so that rounding never produces an increase below the minimum
+    let basis_points: Uint256 = ((percentage * 10_000.0).round() as u64).into();
+    let denominator: Uint256 = 10_000u64.into();
+    let bumped = (fee * (denominator + basis_points) + denominator - 1u8.into()) / denominator;
+    // rounding must never result in a replacement that is not strictly higher
+    max(bumped, fee + 1u8.into())
+}
+
+/// Signs a transaction with the given nonce and fees, returning it along with its txid
+async fn sign_with_nonce(
+    web3: &Web3,
+    key: EthPrivateKey,
+    to: EthAddress,
+    data: Vec<u8>,
+    nonce: Uint256,
+    max_fee_per_gas: Uint256,
+    max_priority_fee_per_gas: Uint256,
+) -> Result<(Transaction, Uint256), GravityError> {
+    let tx = web3
+        .prepare_transaction(
+            to,
+            data,
+            0u32.into(),
+            key,
+            vec![
+                SendTxOption::Nonce(nonce),
+                SendTxOption::GasMaxFee(max_fee_per_gas),
+                SendTxOption::GasPriorityFee(max_priority_fee_per_gas),
+            ],
+        )
+        .await?;
+    let txid = get_txid(&tx.to_bytes());
+    Ok((tx, txid))
+}
+
+/// Persists `record` as the pending transaction for its nonce and only then sends `tx`, so that a
+/// crash can never leave a transaction in flight that is not tracked. The transaction is sent to
+/// the private relay in `submission_mode` if there is one, and publicly if the private relay fails.
+/// Returns the last block the private relay may include it in, if it was sent privately. If sending
+/// fails `previous` is restored as the pending transaction for the nonce, or the record removed
+async fn record_and_send(
+    web3: &Web3,
+    key: EthPrivateKey,
+    tx: Transaction,
+    record: PendingTransaction,
+    previous: Option<PendingTransaction>,
+    submission_mode: &SubmissionMode,
+) -> Result<Option<u64>, GravityError> {
+    let (from, nonce) = (record.from, record.nonce);
+    TX_MANAGER.write().unwrap().upsert(record);
+    let res = send(web3, key, tx, submission_mode).await;
+    if res.is_err() {
+        let mut manager = TX_MANAGER.write().unwrap();
+        match previous {
+            Some(previous) => manager.upsert(previous),
+            None => manager.remove(from, nonce),
+        }
+    }
+    res
+}
+
+async fn send(
+    web3: &Web3,
+    key: EthPrivateKey,
+    tx: Transaction,
+    submission_mode: &SubmissionMode,
+) -> Result<Option<u64>, GravityError> {
+    if *submission_mode != SubmissionMode::Public {
+        let raw_tx = tx.to_bytes();
+        let current_block = web3.eth_block_number().await?.to_u64().unwrap_or(u64::MAX);
+        match send_private(submission_mode, key, &raw_tx, current_block).await {
+            Ok(private_until_block) => return Ok(private_until_block),
+            Err(e) => warn!("Private relay submission failed, sending publicly {e}"),
+        }
+    }
+    web3.send_prepared_transaction(tx).await?;
+    Ok(None)
+}
+
+/// Submits a transaction for the given submission and manages it until it is included, replacing it
+/// as needed. If it is not settled within ETH_SUBMIT_WAIT_TIME it is left pending, and managed by
+/// process_pending_transactions on following relayer iterations. Once included its outcome and
+/// `reward` are recorded in the ledger. `max_cost` is the worst case cost the funds guard allowed
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_transaction(
+    web3: &Web3,
+    key: EthPrivateKey,
+    gravity_contract: EthAddress,
+    data: Vec<u8>,
+    fees: Eip1559Fees,
+    max_cost: Uint256,
+    submission: Submission,
+    reward: RelayReward,
+    config: &RelayerConfig,
+) -> Result<(), GravityError> {
+    let from = key.to_address();
+    // pending transactions may not yet be reflected in the node's transaction count
+    let mut nonce = web3.eth_get_transaction_count(from).await?;
+    for p in get_pending(from) {
+        if p.nonce >= nonce {
+            nonce = p.nonce + 1u8.into();
+        }
+    }
+    let max_priority_fee_per_gas = min(
+        fees.max_priority_fee_per_gas
+            .unwrap_or_else(|| DEFAULT_PRIORITY_FEE.into()),
+        fees.max_fee_per_gas,
+    );
+
+    let (tx, txid) = sign_with_nonce(
+        web3,
+        key,
+        gravity_contract,
+        data.clone(),
+        nonce,
+        fees.max_fee_per_gas,
+        max_priority_fee_per_gas,
+    )
+    .await?;
+    let record = PendingTransaction {
+        submission,
+        from,
+        gravity_contract,
+        to: gravity_contract,
+        data: bytes_to_hex_str(&data),
+        nonce,
+        txid,
+        max_fee_per_gas: fees.max_fee_per_gas,
+        max_priority_fee_per_gas,
+        max_cost,
+        submitted_at: now(),
+        replacements: 0,
+        private_until_block: None,
+        replaced_txids: Vec::new(),
+        cancel_txids: Vec::new(),
+        reward,
+    };
+    let private_until_block =
+        record_and_send(web3, key, tx, record.clone(), None, &config.submission_mode).await?;
+    info!(
+        "Sent {:?} with nonce {nonce} and txid {txid:#066x}",
+        record.submission
+    );
+    if private_until_block.is_some() {
+        TX_MANAGER.write().unwrap().upsert(PendingTransaction {
+            private_until_block,
+            ..record
+        });
+    }
+
+    let deadline = Instant::now() + ETH_SUBMIT_WAIT_TIME;
+    let bump_interval = Duration::from_secs(config.tx_bump_interval);
+    loop {
+        let pending = get_pending(from).into_iter().find(|p| p.nonce == nonce);
+        let pending = match pending {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        let wait = min(
+            bump_interval,
+            deadline.saturating_duration_since(Instant::now()),
+        );
+        if web3
+            .wait_for_transaction(pending.txid, wait, None)
+            .await
+            .is_ok()
+        {
+            info!(
+                "{:?} with txid {:#066x} was included",
+                pending.submission, pending.txid
+            );
+            settle(web3, pending).await;
+            return Ok(());
+        }
+        if let Err(e) = manage_pending_transaction(web3, key, config, pending).await {
+            warn!("Failed to manage pending transaction with nonce {nonce} {e}");
+        }
+        if Instant::now() >= deadline {
+            warn!("Transaction with nonce {nonce} is still pending, it will be managed on following iterations");
+            return Ok(());
+        }
+    }
+}
+
+/// Checks every pending transaction from our address, removing included transactions, cancelling
+/// superseded ones, and replacing transactions that have waited longer than `tx_bump_interval`
+pub async fn process_pending_transactions(web3: &Web3, key: EthPrivateKey, config: &RelayerConfig) {
+    for pending in get_pending(key.to_address()) {
+        let nonce = pending.nonce;
+        if let Err(e) = manage_pending_transaction(web3, key, config, pending).await {
+            warn!("Failed to manage pending transaction with nonce {nonce} {e}");
+        }
+    }
+}
+
+async fn manage_pending_transaction(
+    web3: &Web3,
+    key: EthPrivateKey,
+    config: &RelayerConfig,
+    pending: PendingTransaction,
+) -> Result<(), GravityError> {
+    let from = pending.from;
+    if web3.eth_get_transaction_count(from).await? > pending.nonce {
+        info!(
+            "{:?} with nonce {} has been included",
+            pending.submission, pending.nonce
+        );
+        settle(web3, pending).await;
+        return Ok(());
+    }
+
+    // a cancelled submission is already known to be superseded, its cancel is only replaced once
+    // it is stale like any other transaction
+    let superseded = pending.is_cancelled()
+        || pending
+            .submission
+            .is_superseded(pending.gravity_contract, from, web3)
+            .await?;
+    let cancel_needed = superseded && !pending.is_cancelled();
+    let stale = now().saturating_sub(pending.submitted_at) >= config.tx_bump_interval;
+    let private_expired = match pending.private_until_block {
+        Some(until) => web3.eth_block_number().await?.to_u64().unwrap_or(u64::MAX) > until,
+        None => false,
+    };
+    // replacements stay private only for the remainder of the original private window
+    let submission_mode = if pending.private_until_block.is_some() && !private_expired {
+        config.submission_mode.clone()
+    } else {
+        SubmissionMode::Public
+    };
+    if !cancel_needed && !stale {
+        if private_expired {
+            return send_publicly(web3, key, config, pending).await;
+        }
+        return Ok(());
+    }
+
+    let max_fee_per_gas = bump_fee(pending.max_fee_per_gas, config.tx_bump_percentage);
+    let max_priority_fee_per_gas = min(
+        bump_fee(pending.max_priority_fee_per_gas, config.tx_bump_percentage),
+        max_fee_per_gas,
+    );
+    if let Some(cap) = config.max_fee_per_gas_cap {
+        if max_fee_per_gas > cap {
+            warn!(
+                "Can not replace {:?} with nonce {}, a max fee of {} gwei would exceed the cap",
+                pending.submission,
+                pending.nonce,
+                print_gwei(max_fee_per_gas)
+            );
+            return Ok(());
+        }
+    }
+    let max_cost = pending.get_replacement_cost(max_fee_per_gas, superseded);
+    if !check_funds(web3, from, max_cost, Some(pending.nonce), config).await {
+        return Ok(());
+    }
+
+    let (to, data) = if superseded {
+        info!(
+            "{:?} has already been submitted by another relayer, cancelling nonce {}",
+            pending.submission, pending.nonce
+        );
+        (from, String::new())
+    } else {
+        info!(
+            "{:?} with nonce {} has not been included, replacing it at a max fee of {} gwei",
+            pending.submission,
+            pending.nonce,
+            print_gwei(max_fee_per_gas)
+        );
+        (pending.to, pending.data.clone())
+    };
+    let bytes = decode_data(&data)?;
+    let (tx, txid) = sign_with_nonce(
+        web3,
+        key,
+        to,
+        bytes,
+        pending.nonce,
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+    )
+    .await?;
+    let mut replaced_txids = pending.replaced_txids.clone();
+    replaced_txids.push(pending.txid);
+    let mut cancel_txids = pending.cancel_txids.clone();
+    if superseded {
+        cancel_txids.push(txid);
+    }
+    let replacement = PendingTransaction {
+        submission: pending.submission.clone(),
+        from,
+        gravity_contract: pending.gravity_contract,
+        to,
+        data,
+        nonce: pending.nonce,
+        txid,
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+        max_cost,
+        submitted_at: now(),
+        replacements: pending.replacements + 1,
+        private_until_block: pending.private_until_block,
+        replaced_txids,
+        cancel_txids,
+        reward: pending.reward.clone(),
+    };
+    let private_until_block = record_and_send(
+        web3,
+        key,
+        tx,
+        replacement.clone(),
+        Some(pending),
+        &submission_mode,
+    )
+    .await?;
+    info!(
+        "Replaced nonce {} with txid {txid:#066x}",
+        replacement.nonce
+    );
+    // a replacement the private relay refused was sent publicly
+    if private_until_block.is_none() && replacement.private_until_block.is_some() {
+        TX_MANAGER.write().unwrap().upsert(PendingTransaction {
+            private_until_block: None,
+            ..replacement
+        });
+    }
+    Ok(())
+}
+
+/// Removes an included transaction from the pending set and records its outcome in the ledger, the
+/// included transaction may be any of the replacements sent for its nonce
+async fn settle(web3: &Web3, pending: PendingTransaction) {
+    TX_MANAGER
+        .write()
+        .unwrap()
+        .remove(pending.from, pending.nonce);
+
+    let mut receipt = None;
+    for txid in std::iter::once(pending.txid).chain(pending.replaced_txids.iter().rev().cloned()) {
+        match web3.eth_get_transaction_receipt(txid).await {
+            Ok(Some(r)) => {
+                receipt = Some((txid, r));
+                break;
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to get the receipt for {txid:#066x} {e:?}"),
+        }
+    }
+    let outcome = match &receipt {
+        None => Outcome::Unknown,
+        Some((txid, _)) if pending.cancel_txids.contains(txid) => Outcome::Beaten,
+        Some((_, r)) if r.status == Some(1u8.into()) => Outcome::Success,
+        Some(_) => {
+            // a revert is usually caused by another relayer submitting first
+            let beaten = pending
+                .submission
+                .is_superseded(pending.gravity_contract, pending.from, web3)
+                .await
+                .unwrap_or(false);
+            if beaten {
+                Outcome::Beaten
+            } else {
+                Outcome::Reverted
+            }
+        }
+    };
+    let entry = LedgerEntry {
+        submission: pending.submission,
+        txid: receipt
+            .as_ref()
+            .map(|(txid, _)| *txid)
+            .unwrap_or(pending.txid),
+        gas_used: receipt.as_ref().map(|(_, r)| r.gas_used),
+        effective_gas_price: receipt.as_ref().map(|(_, r)| r.effective_gas_price),
+        rewards: pending.reward.tokens,
+        reward_weth_value: pending.reward.weth_value,
+        outcome,
+        settled_at: now(),
+    };
+    record_spend(entry.settled_at, entry.get_cost());
+    metrics_relayer_submission(entry.submission.get_type(), entry.outcome.as_str());
+    record_settled_transaction(entry);
+}
+
+/// Sends a transaction the private relay failed to include to the public mempool, the transaction
+/// is signed again with the same nonce and fees so it is identical to the one sent privately
+async fn send_publicly(
+    web3: &Web3,
+    key: EthPrivateKey,
+    config: &RelayerConfig,
+    pending: PendingTransaction,
+) -> Result<(), GravityError> {
+    if !check_funds(
+        web3,
+        pending.from,
+        pending.max_cost,
+        Some(pending.nonce),
+        config,
+    )
+    .await
+    {
+        return Ok(());
+    }
+    info!(
+        "{:?} with nonce {} was not included by the private relay, sending it publicly",
+        pending.submission, pending.nonce
+    );
+    let bytes = decode_data(&pending.data)?;
+    let (tx, txid) = sign_with_nonce(
+        web3,
+        key,
+        pending.to,
+        bytes,
+        pending.nonce,
+        pending.max_fee_per_gas,
+        pending.max_priority_fee_per_gas,
+    )
+    .await?;
+    let record = PendingTransaction {
+        txid,
+        private_until_block: None,
+        ..pending.clone()
+    };
+    record_and_send(
+        web3,
+        key,
+        tx,
+        record,
+        Some(pending),
+        &SubmissionMode::Public,
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bump_fee() {
+        let fee: Uint256 = 1_000_000_000u64.into();
+        assert_eq!(bump_fee(fee, 0.15), 1_150_000_000u64.into());
+        // the minimum replacement increase is always applied
+        assert_eq!(bump_fee(fee, 0.01), 1_100_000_000u64.into());
+        assert_eq!(bump_fee(1u8.into(), 0.15), 2u8.into());
+    }
+
+    #[test]
+    fn test_replacement_cost() {
+        let gwei = |v: u64| Uint256::from(v * 1_000_000_000);
+        let gas_limit = Uint256::from(300_000u64);
+        let address =
+            EthAddress::parse_and_validate("0x00000000219ab540356cBB839Cbe05303d7705Fa").unwrap();
+        let pending = PendingTransaction {
+            submission: Submission::Valset { nonce: 1 },
+            from: address,
+            gravity_contract: address,
+            to: address,
+            data: String::new(),
+            nonce: 0u8.into(),
+            txid: 0u8.into(),
+            max_fee_per_gas: gwei(10),
+            max_priority_fee_per_gas: gwei(1),
+            max_cost: gwei(10) * gas_limit,
+            submitted_at: 0,
+            replacements: 0,
+            private_until_block: None,
+            replaced_txids: Vec::new(),
+            cancel_txids: Vec::new(),
+            reward: RelayReward::default(),
+        };
+        // a replacement keeps the gas limit of the original
+        assert_eq!(
+            pending.get_replacement_cost(gwei(11), false),
+            gwei(11) * gas_limit
+        );
+        // a cancel only pays for a transfer, as does every replacement of a cancel
+        let cancel_cost = gwei(11) * Uint256::from(CANCEL_GAS);
+        assert_eq!(pending.get_replacement_cost(gwei(11), true), cancel_cost);
+        let cancelled = PendingTransaction {
+            cancel_txids: vec![1u8.into()],
+            ..pending
+        };
+        assert_eq!(cancelled.get_replacement_cost(gwei(11), false), cancel_cost);
+    }
+}
@@ -4,32 +4,39 @@
 use clarity::address::Address as EthAddress;
 use clarity::PrivateKey as EthPrivateKey;
 use cosmos_gravity::query::get_latest_valsets;
-use cosmos_gravity::query::{get_all_valset_confirms, get_valset};
+use cosmos_gravity::query::{get_all_valset_confirms, get_valset, get_valset_height};
 use ethereum_gravity::message_signatures::encode_valset_confirm_hashed;
 use ethereum_gravity::{
-    utils::get_valset_nonce, utils::GasCost, valset_update::send_eth_valset_update,
+    utils::get_valset_nonce, utils::GasCost, valset_update::encode_valset_update_payload,
 };
+use gravity_proto::cosmos_sdk_proto::cosmos::base::tendermint::v1beta1::service_client::ServiceClient as TendermintServiceClient;
 use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::error::GravityError;
 use gravity_utils::num_conversion::{print_eth, print_gwei};
-use gravity_utils::prices::get_weth_price_with_retries;
-use gravity_utils::types::{RelayerConfig, Valset};
+use gravity_utils::prices::get_relayer_weth_price;
+use gravity_utils::types::{Erc20Token, RelayerConfig, Valset};
 use gravity_utils::types::{ValsetConfirmResponse, ValsetRelayingMode};
 use tonic::transport::Channel;
 use web30::client::Web3;
 
+use crate::altruistic::{get_submission_urgency, is_altruistic_gas_price};
 use crate::batch_relaying::get_cost_with_margin;
-use crate::main_loop::ETH_SUBMIT_WAIT_TIME;
+use crate::fee_strategy::get_relaying_fees;
+use crate::funds_guard::check_funds;
+use crate::ledger::RelayReward;
+use crate::tx_manager::{has_pending, submit_transaction, Submission};
 
 #[allow(clippy::too_many_arguments)]
 /// High level entry point for valset relaying, this function starts by finding
 /// what validator set is valid, then evaluating if it should be relayed according
 /// to the users preferences and finally relaying a validator set that is valid at
 /// this moment in time
+#[allow(clippy::too_many_arguments)]
 pub async fn relay_valsets(
     // the validator set currently in the contract on Ethereum
     current_valset: Valset,
     ethereum_key: EthPrivateKey,
+    tendermint_client: &TendermintServiceClient<Channel>,
     web3: &Web3,
     grpc_client: &mut GravityQueryClient<Channel>,
     gravity_contract_address: EthAddress,
@@ -66,11 +73,31 @@ pub async fn relay_valsets(
         }
     };
 
+    // only altruistic relaying weighs how long the valset has waited, counted from its creation
+    let created_height = if config.valset_relaying_mode == ValsetRelayingMode::Altruistic
+        && config.altruistic_urgency.is_some()
+    {
+        match get_valset_height(grpc_client, latest_submittable_valset.nonce).await {
+            Ok(v) => v,
+            Err(e) => {
+                warn!(
+                    "Could not get the height of valset {} {e:?}",
+                    latest_submittable_valset.nonce
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     relay_valid_valset(
         latest_cosmos_valset_nonce,
         latest_submittable_valset,
+        created_height,
         current_valset,
         confirms,
+        tendermint_client,
         web3,
         gravity_contract_address,
         gravity_id,
@@ -85,14 +112,33 @@ pub async fn relay_valsets(
 async fn relay_valid_valset(
     latest_cosmos_valset_nonce: u64,
     valset_to_relay: Valset,
+    created_height: Option<u64>,
     current_valset: Valset,
     conformations: Vec<ValsetConfirmResponse>,
+    tendermint_client: &TendermintServiceClient<Channel>,
     web3: &Web3,
     gravity_contract_address: EthAddress,
     gravity_id: String,
     ethereum_key: EthPrivateKey,
     config: RelayerConfig,
 ) {
+    let submission = Submission::Valset {
+        nonce: valset_to_relay.nonce,
+    };
+    if has_pending(&submission) {
+        info!(
+            "Valset {} already has a pending transaction, waiting for it",
+            valset_to_relay.nonce
+        );
+        return;
+    }
+    let fees = match get_relaying_fees(web3, &config).await {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("Not relaying valsets, could not determine transaction fees {e}");
+            return;
+        }
+    };
     let cost = ethereum_gravity::valset_update::estimate_valset_cost(
         &valset_to_relay,
         &current_valset,
@@ -101,6 +147,7 @@ async fn relay_valid_valset(
         gravity_contract_address,
         gravity_id.clone(),
         ethereum_key.to_address(),
+        fees,
     )
     .await;
     if cost.is_err() {
@@ -118,35 +165,70 @@ async fn relay_valid_valset(
     let cost = cost.unwrap();
 
     info!(
-       "We have detected that valset {} is valid to submit. Latest on Ethereum is {} This update is estimated to cost {} Gas @ {} Gwei/ {:.4} ETH to submit",
+       "We have detected that valset {} is valid to submit. Latest on Ethereum is {} This update is estimated to cost {} Gas @ {} Gwei/ {:.4} ETH to submit, at most {} Gwei / {:.4} ETH",
         valset_to_relay.nonce, current_valset.nonce,
         cost.gas.clone(),
         print_gwei(cost.gas_price),
-        print_eth(cost.get_total())
+        print_eth(cost.get_total()),
+        print_gwei(cost.max_fee_per_gas),
+        print_eth(cost.get_worst_case_total())
     );
 
+    let worst_case_cost = cost.get_worst_case_total();
     let should_relay = should_relay_valset(
         latest_cosmos_valset_nonce,
         &valset_to_relay,
+        created_height,
         ethereum_key.to_address(),
         cost,
+        tendermint_client,
         web3,
-        &config.valset_relaying_mode,
+        &config,
     )
     .await;
 
-    if should_relay {
-        let res = send_eth_valset_update(
+    if should_relay
+        && check_funds(
+            web3,
+            ethereum_key.to_address(),
+            worst_case_cost,
+            None,
+            &config,
+        )
+        .await
+    {
+        let reward_tokens = match valset_to_relay.reward_token {
+            Some(token) => vec![Erc20Token {
+                amount: valset_to_relay.reward_amount,
+                token_contract_address: token,
+            }],
+            None => Vec::new(),
+        };
+        let reward =
+            RelayReward::new(reward_tokens, ethereum_key.to_address(), web3, &config).await;
+        let res = match encode_valset_update_payload(
             valset_to_relay,
             current_valset,
             &conformations,
-            web3,
-            ETH_SUBMIT_WAIT_TIME,
-            gravity_contract_address,
             gravity_id,
-            ethereum_key,
-        )
-        .await;
+            false,
+        ) {
+            Ok(payload) => {
+                submit_transaction(
+                    web3,
+                    ethereum_key,
+                    gravity_contract_address,
+                    payload,
+                    fees,
+                    worst_case_cost,
+                    submission,
+                    reward,
+                    &config,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
         if let Err(e) = res {
             error!("Failed to relay validator set with {e:?}");
         }
@@ -216,23 +298,31 @@ async fn find_latest_valid_valset(
     }
 }
 
-/// determines if the provided valset is profitable
+/// determines if the provided valset is profitable at its worst case cost
+#[allow(clippy::too_many_arguments)]
 async fn should_relay_valset(
     latest_cosmos_valset_nonce: u64,
     valset: &Valset,
+    created_height: Option<u64>,
     pubkey: EthAddress,
     cost: GasCost,
+    tendermint_client: &TendermintServiceClient<Channel>,
     web3: &Web3,
-    config: &ValsetRelayingMode,
+    config: &RelayerConfig,
 ) -> bool {
-    match config {
+    match &config.valset_relaying_mode {
         // if the user has configured only profitable relaying then it is our only consideration
         ValsetRelayingMode::ProfitableOnly { margin } => match valset.reward_token {
             Some(reward_token) => {
-                let price =
-                    get_weth_price_with_retries(pubkey, reward_token, valset.reward_amount, web3)
-                        .await;
-                let cost_with_margin = get_cost_with_margin(cost.get_total(), *margin);
+                let price = get_relayer_weth_price(
+                    config,
+                    pubkey,
+                    reward_token,
+                    valset.reward_amount,
+                    web3,
+                )
+                .await;
+                let cost_with_margin = get_cost_with_margin(cost.get_worst_case_total(), *margin);
                 // we need to see how much WETH we can get for the reward token amount,
                 // and compare that value to the gas cost times the margin
                 match price {
@@ -263,7 +353,15 @@ async fn should_relay_valset(
         // since we store all the required signatures for as long as we may need them on the cosmos chain it's not fatal to wait, we can always play
         // them back later when we need them. Since 2/3 of voting power is required to spend funds and only 1/3 of voting power must change over
         // before this condition is triggered it should not risk a stale validator set in the Ethereum side of the bridge sending funds.
-        ValsetRelayingMode::Altruistic => latest_cosmos_valset_nonce != valset.nonce,
+        //
+        // The longer a required update waits the higher the gas price we accept for it, if urgency is configured
+        ValsetRelayingMode::Altruistic => {
+            latest_cosmos_valset_nonce != valset.nonce
+                && is_altruistic_gas_price(
+                    config,
+                    get_submission_urgency(config, tendermint_client, created_height, None).await,
+                )
+        }
     }
 }
 
@@ -41,11 +41,14 @@ use gravity_proto::gravity::v1::query_client::QueryClient as GravityQueryClient;
 use gravity_proto::gravity::v1::MsgSendToCosmosClaim;
 use gravity_proto::gravity::v2::MsgUpdateParamsProposal as GravityMsgUpdateParamsProposal;
 use gravity_proto::gravity::v2::Param as GravityParam;
+use gravity_utils::connection_prep::CosmosGrpcPool;
+use gravity_utils::eth_signer::EthSigner;
 use gravity_utils::types::BatchRelayingMode;
 use gravity_utils::types::BatchRequestMode;
 use gravity_utils::types::GravityBridgeToolsConfig;
 use gravity_utils::types::ValsetRelayingMode;
 use orchestrator::main_loop::orchestrator_main_loop;
+use orchestrator::signing_journal::SigningJournal;
 use rand::Rng;
 use std::thread;
 use std::time::{Duration, Instant};
@@ -375,16 +378,19 @@ pub async fn start_orchestrators(
             let fut = orchestrator_main_loop(
                 k.orch_key,
                 k.eth_key,
+                EthSigner::Local(k.eth_key),
                 web30,
-                contact,
-                grpc_client,
+                CosmosGrpcPool::new(COSMOS_NODE_GRPC.to_string(), contact, grpc_client),
                 gravity_address,
                 params.gravity_id,
                 get_fee(None),
                 config,
+                None,
+                SigningJournal::default(),
+                None,
             );
             let system = System::new();
-            system.block_on(fut);
+            system.block_on(fut).unwrap();
         });
         // used to break out of the loop early to simulate one validator
         // not running an orchestrator